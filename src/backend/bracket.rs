@@ -0,0 +1,317 @@
+/// Module Bracket
+///
+/// Backend that shows the game in a window of its own, using bracket-lib. bracket-lib runs its
+/// own main loop and calls back into the window once per frame, so the game runs on a thread of
+/// its own and trades frames and input events with the window over channels. Images are read
+/// from REX Paint files, and the window can draw scanlines over the game as post-processing.
+use std::fs::File;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bracket_lib::prelude::{
+    self as bracket, BResult, BTerm, BTermBuilder, GameState as BracketState, VirtualKeyCode,
+    XpFile, INPUT, RGB,
+};
+
+// internal modules
+use backend::colors::Color;
+use backend::console::{Cell, Console};
+use backend::input::{Event, Key, KeyCode, Mouse};
+use backend::Backend;
+
+// font that the console characters are drawn with, in the asset directory
+const ASSET_DIR: &str = "assets";
+const FONT: &str = "terminal16x16_gs_ro.png";
+const FONT_SIZE: u32 = 16;
+
+/// The side of the backend that the game talks to.
+pub struct BracketBackend {
+    frames: Sender<Vec<Cell>>,
+    events: Receiver<Event>,
+    closed: bool,
+    frame_time: Duration,
+    last_frame: Instant,
+}
+
+/// The side of the backend that bracket-lib calls every frame.
+struct Window {
+    width: i32,
+    frames: Receiver<Vec<Cell>>,
+    events: Sender<Event>,
+    /// Mouse position and buttons as of the last frame, so that only changes become events.
+    mouse: ((i32, i32), bool, bool),
+}
+
+/// Open a window of the given size and run the game in it. The game is given the backend that
+/// draws into the window. With `scanlines`, the window imitates an old CRT screen.
+pub fn run<F>(
+    width: i32,
+    height: i32,
+    title: &str,
+    fps: i32,
+    scanlines: bool,
+    game: F,
+) -> BResult<()>
+where
+    F: FnOnce(BracketBackend) + Send + 'static,
+{
+    let mut context = BTermBuilder::new()
+        .with_resource_path(ASSET_DIR)
+        .with_font(FONT, FONT_SIZE, FONT_SIZE)
+        .with_simple_console(width as u32, height as u32, FONT)
+        .with_tile_dimensions(FONT_SIZE, FONT_SIZE)
+        .with_title(title)
+        .with_fps_cap(fps as f32)
+        .build()?;
+    if scanlines {
+        context.with_post_scanlines(false);
+    }
+
+    let (frame_sender, frames) = mpsc::channel();
+    let (events, event_receiver) = mpsc::channel();
+    let backend = BracketBackend {
+        frames: frame_sender,
+        events: event_receiver,
+        closed: false,
+        frame_time: Duration::from_millis(1000 / fps.max(1) as u64),
+        last_frame: Instant::now(),
+    };
+    let game = thread::spawn(move || game(backend));
+    let window = Window {
+        width,
+        frames,
+        events,
+        mouse: ((-1, -1), false, false),
+    };
+    let result = bracket::main_loop(context, window);
+    let _ = game.join();
+    result
+}
+
+impl Backend for BracketBackend {
+    fn present(&mut self, console: &Console) {
+        let cells = (0..console.height())
+            .flat_map(|y| (0..console.width()).map(move |x| (x, y)))
+            .map(|(x, y)| console.cell(x, y))
+            .collect();
+        if self.frames.send(cells).is_err() {
+            self.closed = true;
+        }
+
+        // keep the frame rate, like libtcod does
+        let elapsed = self.last_frame.elapsed();
+        if elapsed < self.frame_time {
+            thread::sleep(self.frame_time - elapsed);
+        }
+        self.last_frame = Instant::now();
+    }
+
+    fn check_for_event(&mut self) -> Option<Event> {
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.closed = true;
+                None
+            }
+        }
+    }
+
+    fn wait_for_keypress(&mut self) -> Key {
+        loop {
+            match self.events.recv() {
+                Ok(Event::Key(key)) => return key,
+                Ok(Event::Mouse(_)) => {}
+                Err(_) => {
+                    self.closed = true;
+                    return Key::default();
+                }
+            }
+        }
+    }
+
+    fn window_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+
+    fn set_fullscreen(&mut self, _fullscreen: bool) {}
+
+    /// Draw the REX Paint file next to the given image, i.e. with the extension `.xp`, layer by
+    /// layer. Cells with a transparent background let the layers below show through.
+    fn draw_image(&mut self, path: &str, console: &mut Console) {
+        let image = File::open(Path::new(path).with_extension("xp"))
+            .and_then(|mut file| XpFile::read(&mut file));
+        let image = match image {
+            Ok(image) => image,
+            Err(_) => return,
+        };
+        for layer in &image.layers {
+            for y in 0..layer.height {
+                for x in 0..layer.width {
+                    let cell = match layer.get(x, y) {
+                        Some(cell) => cell,
+                        None => continue,
+                    };
+                    // REX Paint marks transparent cells with a magenta background
+                    if (cell.bg.r, cell.bg.g, cell.bg.b) == (255, 0, 255) {
+                        continue;
+                    }
+                    console.put_char_ex(
+                        x as i32,
+                        y as i32,
+                        bracket::to_char(cell.ch as u8),
+                        Color::new(cell.fg.r, cell.fg.g, cell.fg.b),
+                        Color::new(cell.bg.r, cell.bg.g, cell.bg.b),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl BracketState for Window {
+    fn tick(&mut self, ctx: &mut BTerm) {
+        // show the latest frame of the game, or close the window once the game is over
+        let mut frame = None;
+        loop {
+            match self.frames.try_recv() {
+                Ok(cells) => frame = Some(cells),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    ctx.quit();
+                    return;
+                }
+            }
+        }
+        if let Some(cells) = frame {
+            for (i, cell) in cells.iter().enumerate() {
+                let (x, y) = (i as i32 % self.width, i as i32 / self.width);
+                ctx.set(
+                    x,
+                    y,
+                    RGB::from_u8(cell.foreground.r, cell.foreground.g, cell.foreground.b),
+                    RGB::from_u8(cell.background.r, cell.background.g, cell.background.b),
+                    bracket::to_cp437(cell.chr),
+                );
+            }
+        }
+
+        if let Some(key) = ctx.key {
+            let _ = self.events.send(Event::Key(convert_key(key, ctx)));
+        }
+
+        // clicks count once the button is released, like in libtcod
+        let mouse = {
+            let input = INPUT.lock();
+            (
+                ctx.mouse_pos(),
+                input.is_mouse_button_pressed(0),
+                input.is_mouse_button_pressed(1),
+            )
+        };
+        if mouse != self.mouse {
+            let ((x, y), left, right) = mouse;
+            let (_, was_left, was_right) = self.mouse;
+            let _ = self.events.send(Event::Mouse(Mouse {
+                cx: x,
+                cy: y,
+                lbutton_pressed: was_left && !left,
+                rbutton_pressed: was_right && !right,
+            }));
+            self.mouse = mouse;
+        }
+    }
+}
+
+fn convert_key(key: VirtualKeyCode, ctx: &BTerm) -> Key {
+    use bracket_lib::prelude::VirtualKeyCode::*;
+    let shift = ctx.shift;
+    let letter = |lower: char| {
+        if shift {
+            lower.to_ascii_uppercase()
+        } else {
+            lower
+        }
+    };
+    let symbol = |plain: char, shifted: char| if shift { shifted } else { plain };
+    let (code, printable) = match key {
+        Escape => (KeyCode::Escape, '\0'),
+        Return | NumpadEnter => (KeyCode::Enter, '\0'),
+        Back => (KeyCode::Backspace, '\0'),
+        Tab => (KeyCode::Tab, '\0'),
+        Up => (KeyCode::Up, '\0'),
+        Down => (KeyCode::Down, '\0'),
+        Left => (KeyCode::Left, '\0'),
+        Right => (KeyCode::Right, '\0'),
+        PageUp => (KeyCode::PageUp, '\0'),
+        PageDown => (KeyCode::PageDown, '\0'),
+        Home => (KeyCode::Home, '\0'),
+        End => (KeyCode::End, '\0'),
+        Numpad1 => (KeyCode::NumPad1, '\0'),
+        Numpad2 => (KeyCode::NumPad2, '\0'),
+        Numpad3 => (KeyCode::NumPad3, '\0'),
+        Numpad4 => (KeyCode::NumPad4, '\0'),
+        Numpad5 => (KeyCode::NumPad5, '\0'),
+        Numpad6 => (KeyCode::NumPad6, '\0'),
+        Numpad7 => (KeyCode::NumPad7, '\0'),
+        Numpad8 => (KeyCode::NumPad8, '\0'),
+        Numpad9 => (KeyCode::NumPad9, '\0'),
+        Space => (KeyCode::Char, ' '),
+        Period => (KeyCode::Char, symbol('.', '>')),
+        Comma => (KeyCode::Char, symbol(',', '<')),
+        Slash => (KeyCode::Char, symbol('/', '?')),
+        Semicolon => (KeyCode::Char, symbol(';', ':')),
+        Minus => (KeyCode::Char, symbol('-', '_')),
+        Equals => (KeyCode::Char, symbol('=', '+')),
+        Key0 => (KeyCode::Char, '0'),
+        Key1 => (KeyCode::Char, '1'),
+        Key2 => (KeyCode::Char, '2'),
+        Key3 => (KeyCode::Char, '3'),
+        Key4 => (KeyCode::Char, '4'),
+        Key5 => (KeyCode::Char, '5'),
+        Key6 => (KeyCode::Char, '6'),
+        Key7 => (KeyCode::Char, '7'),
+        Key8 => (KeyCode::Char, '8'),
+        Key9 => (KeyCode::Char, '9'),
+        A => (KeyCode::Char, letter('a')),
+        B => (KeyCode::Char, letter('b')),
+        C => (KeyCode::Char, letter('c')),
+        D => (KeyCode::Char, letter('d')),
+        E => (KeyCode::Char, letter('e')),
+        F => (KeyCode::Char, letter('f')),
+        G => (KeyCode::Char, letter('g')),
+        H => (KeyCode::Char, letter('h')),
+        I => (KeyCode::Char, letter('i')),
+        J => (KeyCode::Char, letter('j')),
+        K => (KeyCode::Char, letter('k')),
+        L => (KeyCode::Char, letter('l')),
+        M => (KeyCode::Char, letter('m')),
+        N => (KeyCode::Char, letter('n')),
+        O => (KeyCode::Char, letter('o')),
+        P => (KeyCode::Char, letter('p')),
+        Q => (KeyCode::Char, letter('q')),
+        R => (KeyCode::Char, letter('r')),
+        S => (KeyCode::Char, letter('s')),
+        T => (KeyCode::Char, letter('t')),
+        U => (KeyCode::Char, letter('u')),
+        V => (KeyCode::Char, letter('v')),
+        W => (KeyCode::Char, letter('w')),
+        X => (KeyCode::Char, letter('x')),
+        Y => (KeyCode::Char, letter('y')),
+        Z => (KeyCode::Char, letter('z')),
+        _ => (KeyCode::Other, '\0'),
+    };
+    Key {
+        code,
+        printable,
+        alt: ctx.alt,
+        ctrl: ctx.control,
+        shift,
+    }
+}
@@ -0,0 +1,237 @@
+/// Module Colors
+///
+/// Colors in 24-bit RGB, independent of any backend. The named colors carry the same values as
+/// their libtcod counterparts, so that the game looks the same with every backend.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+}
+
+/// Blend two colors, where an amount of 0.0 returns the first and 1.0 the second one.
+pub fn lerp(from: Color, to: Color, amount: f32) -> Color {
+    let amount = amount.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    Color::new(mix(from.r, to.r), mix(from.g, to.g), mix(from.b, to.b))
+}
+
+pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+pub const DARKER_AMBER: Color = Color {
+    r: 127,
+    g: 95,
+    b: 0,
+};
+pub const DARKER_BLUE: Color = Color { r: 0, g: 0, b: 127 };
+pub const DARKER_GREEN: Color = Color { r: 0, g: 127, b: 0 };
+pub const DARKER_ORANGE: Color = Color {
+    r: 127,
+    g: 63,
+    b: 0,
+};
+pub const DARKER_RED: Color = Color { r: 127, g: 0, b: 0 };
+pub const DARK_AMBER: Color = Color {
+    r: 191,
+    g: 143,
+    b: 0,
+};
+pub const DARK_CRIMSON: Color = Color {
+    r: 191,
+    g: 0,
+    b: 47,
+};
+pub const DARK_GREY: Color = Color {
+    r: 95,
+    g: 95,
+    b: 95,
+};
+pub const DARK_ORANGE: Color = Color {
+    r: 191,
+    g: 95,
+    b: 0,
+};
+pub const DARK_RED: Color = Color { r: 191, g: 0, b: 0 };
+pub const DARK_SEPIA: Color = Color {
+    r: 94,
+    g: 75,
+    b: 47,
+};
+pub const DESATURATED_GREEN: Color = Color {
+    r: 63,
+    g: 127,
+    b: 63,
+};
+pub const FLAME: Color = Color {
+    r: 255,
+    g: 63,
+    b: 0,
+};
+pub const GOLD: Color = Color {
+    r: 229,
+    g: 191,
+    b: 0,
+};
+pub const GREEN: Color = Color { r: 0, g: 255, b: 0 };
+pub const GREY: Color = Color {
+    r: 127,
+    g: 127,
+    b: 127,
+};
+pub const LIGHTER_BLUE: Color = Color {
+    r: 166,
+    g: 166,
+    b: 255,
+};
+pub const LIGHTER_GREEN: Color = Color {
+    r: 166,
+    g: 255,
+    b: 166,
+};
+pub const LIGHTER_GREY: Color = Color {
+    r: 191,
+    g: 191,
+    b: 191,
+};
+pub const LIGHTEST_GREY: Color = Color {
+    r: 223,
+    g: 223,
+    b: 223,
+};
+pub const LIGHT_AMBER: Color = Color {
+    r: 255,
+    g: 220,
+    b: 115,
+};
+pub const LIGHT_AZURE: Color = Color {
+    r: 115,
+    g: 185,
+    b: 255,
+};
+pub const LIGHT_BLUE: Color = Color {
+    r: 115,
+    g: 115,
+    b: 255,
+};
+pub const LIGHT_CRIMSON: Color = Color {
+    r: 255,
+    g: 115,
+    b: 149,
+};
+pub const LIGHT_CYAN: Color = Color {
+    r: 115,
+    g: 255,
+    b: 255,
+};
+pub const LIGHT_FLAME: Color = Color {
+    r: 255,
+    g: 149,
+    b: 115,
+};
+pub const LIGHT_GREEN: Color = Color {
+    r: 115,
+    g: 255,
+    b: 115,
+};
+pub const LIGHT_GREY: Color = Color {
+    r: 159,
+    g: 159,
+    b: 159,
+};
+pub const LIGHT_LIME: Color = Color {
+    r: 220,
+    g: 255,
+    b: 115,
+};
+pub const LIGHT_MAGENTA: Color = Color {
+    r: 255,
+    g: 115,
+    b: 220,
+};
+pub const LIGHT_ORANGE: Color = Color {
+    r: 255,
+    g: 185,
+    b: 115,
+};
+pub const LIGHT_PINK: Color = Color {
+    r: 255,
+    g: 115,
+    b: 185,
+};
+pub const LIGHT_PURPLE: Color = Color {
+    r: 220,
+    g: 115,
+    b: 255,
+};
+pub const LIGHT_RED: Color = Color {
+    r: 255,
+    g: 115,
+    b: 115,
+};
+pub const LIGHT_SEPIA: Color = Color {
+    r: 158,
+    g: 134,
+    b: 100,
+};
+pub const LIGHT_SKY: Color = Color {
+    r: 115,
+    g: 220,
+    b: 255,
+};
+pub const LIGHT_TURQUOISE: Color = Color {
+    r: 115,
+    g: 255,
+    b: 220,
+};
+pub const LIGHT_VIOLET: Color = Color {
+    r: 185,
+    g: 115,
+    b: 255,
+};
+pub const LIGHT_YELLOW: Color = Color {
+    r: 255,
+    g: 255,
+    b: 115,
+};
+pub const LIME: Color = Color {
+    r: 191,
+    g: 255,
+    b: 0,
+};
+pub const ORANGE: Color = Color {
+    r: 255,
+    g: 127,
+    b: 0,
+};
+pub const RED: Color = Color { r: 255, g: 0, b: 0 };
+pub const SEPIA: Color = Color {
+    r: 127,
+    g: 101,
+    b: 63,
+};
+pub const SKY: Color = Color {
+    r: 0,
+    g: 191,
+    b: 255,
+};
+pub const VIOLET: Color = Color {
+    r: 127,
+    g: 0,
+    b: 255,
+};
+pub const WHITE: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+};
+pub const YELLOW: Color = Color {
+    r: 255,
+    g: 255,
+    b: 0,
+};
@@ -0,0 +1,319 @@
+/// Module Console
+///
+/// A grid of character cells that the game draws into. Consoles live entirely in memory, so
+/// drawing works the same no matter which backend shows the result on the screen.
+// internal modules
+use backend::colors::{self, lerp, Color};
+
+/// How a color is combined with the background color that a cell already has.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackgroundFlag {
+    /// Leave the background as it is.
+    None,
+    /// Replace the background.
+    Set,
+    /// Lighten the background with the color.
+    Screen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextAlignment {
+    Left,
+    Center,
+}
+
+/// A single character on the console, with its colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub chr: char,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+pub struct Console {
+    width: i32,
+    height: i32,
+    cells: Vec<Cell>,
+    default_foreground: Color,
+    default_background: Color,
+}
+
+impl Console {
+    pub fn new(width: i32, height: i32) -> Self {
+        let mut console = Console {
+            width,
+            height,
+            cells: vec![],
+            default_foreground: colors::WHITE,
+            default_background: colors::BLACK,
+        };
+        console.cells = vec![console.blank_cell(); (width * height).max(0) as usize];
+        console
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Return the cell at the given position. Positions outside of the console are blank.
+    pub fn cell(&self, x: i32, y: i32) -> Cell {
+        if self.contains(x, y) {
+            self.cells[(y * self.width + x) as usize]
+        } else {
+            self.blank_cell()
+        }
+    }
+
+    fn cell_mut(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    fn blank_cell(&self) -> Cell {
+        Cell {
+            chr: ' ',
+            foreground: self.default_foreground,
+            background: self.default_background,
+        }
+    }
+
+    /// Fill the whole console with blanks in the default colors.
+    pub fn clear(&mut self) {
+        let blank = self.blank_cell();
+        for cell in &mut self.cells {
+            *cell = blank;
+        }
+    }
+
+    pub fn set_default_foreground(&mut self, color: Color) {
+        self.default_foreground = color;
+    }
+
+    pub fn set_default_background(&mut self, color: Color) {
+        self.default_background = color;
+    }
+
+    /// Put a character in the default foreground color.
+    pub fn put_char(&mut self, x: i32, y: i32, chr: char, flag: BackgroundFlag) {
+        let (foreground, background) = (self.default_foreground, self.default_background);
+        if let Some(cell) = self.cell_mut(x, y) {
+            cell.chr = chr;
+            cell.foreground = foreground;
+            apply_background(cell, background, flag);
+        }
+    }
+
+    /// Put a character with the given colors.
+    // only the libtcod backend needs this, to copy images onto consoles
+    #[cfg_attr(not(feature = "tcod"), allow(dead_code))]
+    pub fn put_char_ex(&mut self, x: i32, y: i32, chr: char, foreground: Color, background: Color) {
+        if let Some(cell) = self.cell_mut(x, y) {
+            *cell = Cell {
+                chr,
+                foreground,
+                background,
+            };
+        }
+    }
+
+    pub fn set_char_background(&mut self, x: i32, y: i32, color: Color, flag: BackgroundFlag) {
+        if let Some(cell) = self.cell_mut(x, y) {
+            apply_background(cell, color, flag);
+        }
+    }
+
+    /// Fill a rectangle with the default background color, and optionally erase its characters.
+    pub fn rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        clear: bool,
+        flag: BackgroundFlag,
+    ) {
+        let background = self.default_background;
+        for cy in y..y + height {
+            for cx in x..x + width {
+                if let Some(cell) = self.cell_mut(cx, cy) {
+                    if clear {
+                        cell.chr = ' ';
+                    }
+                    apply_background(cell, background, flag);
+                }
+            }
+        }
+    }
+
+    /// Print text in the default foreground color, one line per line break.
+    pub fn print_ex<T: AsRef<str>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        flag: BackgroundFlag,
+        alignment: TextAlignment,
+        text: T,
+    ) {
+        for (i, line) in text.as_ref().split('\n').enumerate() {
+            self.print_line(x, y + i as i32, flag, alignment, line);
+        }
+    }
+
+    /// Print text left-aligned and wrapped to the given width. A height of 0 means that the text
+    /// may take as many lines as it needs.
+    pub fn print_rect<T: AsRef<str>>(&mut self, x: i32, y: i32, width: i32, height: i32, text: T) {
+        self.print_rect_ex(
+            x,
+            y,
+            width,
+            height,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            text,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn print_rect_ex<T: AsRef<str>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        flag: BackgroundFlag,
+        alignment: TextAlignment,
+        text: T,
+    ) {
+        let lines = wrap(text.as_ref(), width);
+        let max_lines = if height > 0 {
+            height as usize
+        } else {
+            lines.len()
+        };
+        let line_x = match alignment {
+            TextAlignment::Left => x,
+            TextAlignment::Center => x + width / 2,
+        };
+        for (i, line) in lines.iter().take(max_lines).enumerate() {
+            self.print_line(line_x, y + i as i32, flag, alignment, line);
+        }
+    }
+
+    /// Return the number of lines that the text takes when it is wrapped to the given width.
+    pub fn get_height_rect<T: AsRef<str>>(
+        &self,
+        _x: i32,
+        _y: i32,
+        width: i32,
+        height: i32,
+        text: T,
+    ) -> i32 {
+        let lines = wrap(text.as_ref(), width).len() as i32;
+        if height > 0 {
+            lines.min(height)
+        } else {
+            lines
+        }
+    }
+
+    fn print_line(
+        &mut self,
+        x: i32,
+        y: i32,
+        flag: BackgroundFlag,
+        alignment: TextAlignment,
+        line: &str,
+    ) {
+        let length = line.chars().count() as i32;
+        let start = match alignment {
+            TextAlignment::Left => x,
+            TextAlignment::Center => x - length / 2,
+        };
+        for (i, chr) in line.chars().enumerate() {
+            self.put_char(start + i as i32, y, chr, flag);
+        }
+    }
+}
+
+/// Copy a rectangle of one console onto another. The alpha values blend the copied colors with
+/// the colors that are already there, where 1.0 replaces them completely.
+pub fn blit(
+    source: &Console,
+    (source_x, source_y): (i32, i32),
+    (width, height): (i32, i32),
+    destination: &mut Console,
+    (destination_x, destination_y): (i32, i32),
+    foreground_alpha: f32,
+    background_alpha: f32,
+) {
+    let width = width.min(source.width - source_x);
+    let height = height.min(source.height - source_y);
+    for y in 0..height {
+        for x in 0..width {
+            let from = source.cell(source_x + x, source_y + y);
+            if let Some(to) = destination.cell_mut(destination_x + x, destination_y + y) {
+                to.chr = from.chr;
+                to.foreground = lerp(to.foreground, from.foreground, foreground_alpha);
+                to.background = lerp(to.background, from.background, background_alpha);
+            }
+        }
+    }
+}
+
+fn apply_background(cell: &mut Cell, color: Color, flag: BackgroundFlag) {
+    match flag {
+        BackgroundFlag::None => {}
+        BackgroundFlag::Set => cell.background = color,
+        BackgroundFlag::Screen => {
+            let screen = |a: u8, b: u8| 255 - ((255 - a as u32) * (255 - b as u32) / 255) as u8;
+            cell.background = Color::new(
+                screen(cell.background.r, color.r),
+                screen(cell.background.g, color.g),
+                screen(cell.background.b, color.b),
+            );
+        }
+    }
+}
+
+/// Break the text into lines that are at most `width` characters long. Lines break at spaces
+/// where possible, and at line breaks in the text.
+pub fn wrap(text: &str, width: i32) -> Vec<String> {
+    let width = width.max(1) as usize;
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line: Option<String> = None;
+        for word in paragraph.split(' ') {
+            let mut next = match line {
+                None => word.to_string(),
+                Some(line) => {
+                    if line.chars().count() + 1 + word.chars().count() <= width {
+                        line + " " + word
+                    } else {
+                        lines.push(line);
+                        word.to_string()
+                    }
+                }
+            };
+            // words that are too long for a single line are split wherever they reach the edge
+            while next.chars().count() > width {
+                let rest: String = next.chars().skip(width).collect();
+                lines.push(next.chars().take(width).collect());
+                next = rest;
+            }
+            line = Some(next);
+        }
+        lines.push(line.unwrap_or_default());
+    }
+    lines
+}
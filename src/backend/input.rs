@@ -0,0 +1,58 @@
+/// Module Input
+///
+/// Keyboard and mouse input, independent of the backend that reads it.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeyCode {
+    #[default]
+    NoKey,
+    Escape,
+    Enter,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    NumPad1,
+    NumPad2,
+    NumPad3,
+    NumPad4,
+    NumPad5,
+    NumPad6,
+    NumPad7,
+    NumPad8,
+    NumPad9,
+    /// A key that produces a character, given by `Key::printable`.
+    Char,
+    /// Any other key, which the game doesn't use.
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub printable: char,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+/// Position of the mouse in console cells, and whether a button was clicked since the last event.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Mouse {
+    pub cx: i32,
+    pub cy: i32,
+    pub lbutton_pressed: bool,
+    pub rbutton_pressed: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Key(Key),
+    Mouse(Mouse),
+}
@@ -0,0 +1,142 @@
+/// Module Libtcod
+///
+/// Backend that shows the game in a window of its own, using libtcod.
+use tcod::colors::Color as TcodColor;
+use tcod::console::{self as tcod_console, FontLayout, FontType, Offscreen};
+use tcod::image::{self, Image};
+use tcod::input::{self as tcod_input, KeyCode as TcodKeyCode};
+use tcod::Console as TcodConsole;
+
+// internal modules
+use backend::colors::Color;
+use backend::console::Console;
+use backend::input::{Event, Key, KeyCode, Mouse};
+use backend::Backend;
+
+// font that the console characters are drawn with
+const FONT: &str = "assets/terminal16x16_gs_ro.png";
+
+pub struct TcodBackend {
+    root: tcod_console::Root,
+}
+
+impl TcodBackend {
+    pub fn new(width: i32, height: i32, title: &str, fps: i32) -> Self {
+        let root = tcod_console::Root::initializer()
+            .font(FONT, FontLayout::AsciiInRow)
+            .font_type(FontType::Greyscale)
+            .size(width, height)
+            .title(title)
+            .init();
+        ::tcod::system::set_fps(fps);
+        TcodBackend { root }
+    }
+}
+
+impl Backend for TcodBackend {
+    fn present(&mut self, console: &Console) {
+        for y in 0..console.height() {
+            for x in 0..console.width() {
+                let cell = console.cell(x, y);
+                self.root.put_char_ex(
+                    x,
+                    y,
+                    cell.chr,
+                    to_tcod(cell.foreground),
+                    to_tcod(cell.background),
+                );
+            }
+        }
+        self.root.flush();
+    }
+
+    fn check_for_event(&mut self) -> Option<Event> {
+        match tcod_input::check_for_event(tcod_input::MOUSE | tcod_input::KEY_PRESS) {
+            Some((_, tcod_input::Event::Key(key))) => Some(Event::Key(convert_key(key))),
+            Some((_, tcod_input::Event::Mouse(mouse))) => Some(Event::Mouse(Mouse {
+                cx: mouse.cx as i32,
+                cy: mouse.cy as i32,
+                lbutton_pressed: mouse.lbutton_pressed,
+                rbutton_pressed: mouse.rbutton_pressed,
+            })),
+            None => None,
+        }
+    }
+
+    fn wait_for_keypress(&mut self) -> Key {
+        convert_key(self.root.wait_for_keypress(true))
+    }
+
+    fn window_closed(&self) -> bool {
+        self.root.window_closed()
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.root.is_fullscreen()
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.root.set_fullscreen(fullscreen);
+    }
+
+    fn draw_image(&mut self, path: &str, console: &mut Console) {
+        let img = Image::from_file(path).expect("Background image not found");
+        let mut canvas = Offscreen::new(console.width(), console.height());
+        image::blit_2x(&img, (0, 0), (-1, -1), &mut canvas, (0, 0));
+        for y in 0..console.height() {
+            for x in 0..console.width() {
+                console.put_char_ex(
+                    x,
+                    y,
+                    canvas.get_char(x, y),
+                    from_tcod(canvas.get_char_foreground(x, y)),
+                    from_tcod(canvas.get_char_background(x, y)),
+                );
+            }
+        }
+    }
+}
+
+fn to_tcod(color: Color) -> TcodColor {
+    TcodColor::new(color.r, color.g, color.b)
+}
+
+fn from_tcod(color: TcodColor) -> Color {
+    Color::new(color.r, color.g, color.b)
+}
+
+fn convert_key(key: tcod_input::Key) -> Key {
+    let code = match key.code {
+        TcodKeyCode::NoKey => KeyCode::NoKey,
+        TcodKeyCode::Escape => KeyCode::Escape,
+        TcodKeyCode::Enter | TcodKeyCode::NumPadEnter => KeyCode::Enter,
+        TcodKeyCode::Backspace => KeyCode::Backspace,
+        TcodKeyCode::Tab => KeyCode::Tab,
+        TcodKeyCode::Up => KeyCode::Up,
+        TcodKeyCode::Down => KeyCode::Down,
+        TcodKeyCode::Left => KeyCode::Left,
+        TcodKeyCode::Right => KeyCode::Right,
+        TcodKeyCode::PageUp => KeyCode::PageUp,
+        TcodKeyCode::PageDown => KeyCode::PageDown,
+        TcodKeyCode::Home => KeyCode::Home,
+        TcodKeyCode::End => KeyCode::End,
+        TcodKeyCode::NumPad1 => KeyCode::NumPad1,
+        TcodKeyCode::NumPad2 => KeyCode::NumPad2,
+        TcodKeyCode::NumPad3 => KeyCode::NumPad3,
+        TcodKeyCode::NumPad4 => KeyCode::NumPad4,
+        TcodKeyCode::NumPad5 => KeyCode::NumPad5,
+        TcodKeyCode::NumPad6 => KeyCode::NumPad6,
+        TcodKeyCode::NumPad7 => KeyCode::NumPad7,
+        TcodKeyCode::NumPad8 => KeyCode::NumPad8,
+        TcodKeyCode::NumPad9 => KeyCode::NumPad9,
+        _ if key.printable != '\0' => KeyCode::Char,
+        _ => KeyCode::Other,
+    };
+    Key {
+        code,
+        printable: key.printable,
+        alt: key.alt,
+        ctrl: key.ctrl,
+        shift: key.shift,
+    }
+}
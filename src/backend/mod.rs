@@ -0,0 +1,94 @@
+/// Module Backend
+///
+/// The game draws into in-memory consoles and reads its input as plain events. A backend takes
+/// care of the rest: showing the consoles on the screen and turning keyboard and mouse input into
+/// events. The libtcod and bracket-lib backends open a window, the terminal backend runs in any
+/// terminal that crossterm supports. The libtcod backend is only built with the `tcod` feature,
+/// which is enabled by default, and the bracket-lib backend with the `bracket-lib` feature.
+#[cfg(feature = "bracket-lib")]
+pub mod bracket;
+pub mod colors;
+pub mod console;
+pub mod input;
+#[cfg(feature = "tcod")]
+pub mod libtcod;
+pub mod terminal;
+
+use std::ops::{Deref, DerefMut};
+
+// internal modules
+use backend::console::Console;
+use backend::input::{Event, Key};
+
+pub trait Backend {
+    /// Show the console on the screen.
+    fn present(&mut self, console: &Console);
+    /// Return the next input event, or `None` if there is none pending.
+    fn check_for_event(&mut self) -> Option<Event>;
+    /// Wait until a key is pressed and return it.
+    fn wait_for_keypress(&mut self) -> Key;
+    fn window_closed(&self) -> bool;
+    fn is_fullscreen(&self) -> bool;
+    fn set_fullscreen(&mut self, fullscreen: bool);
+    /// Draw the image from the given file onto the console, at twice the console resolution.
+    /// Backends that can't show images leave the console as it is.
+    fn draw_image(&mut self, path: &str, console: &mut Console);
+}
+
+/// The console that is shown on the screen, together with the backend that shows it.
+pub struct Root {
+    console: Console,
+    backend: Box<dyn Backend>,
+}
+
+impl Root {
+    pub fn new(backend: Box<dyn Backend>, width: i32, height: i32) -> Self {
+        Root {
+            console: Console::new(width, height),
+            backend,
+        }
+    }
+
+    /// Show everything that was drawn so far on the screen.
+    pub fn flush(&mut self) {
+        self.backend.present(&self.console);
+    }
+
+    pub fn check_for_event(&mut self) -> Option<Event> {
+        self.backend.check_for_event()
+    }
+
+    pub fn wait_for_keypress(&mut self) -> Key {
+        self.backend.wait_for_keypress()
+    }
+
+    pub fn window_closed(&self) -> bool {
+        self.backend.window_closed()
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.backend.is_fullscreen()
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.backend.set_fullscreen(fullscreen);
+    }
+
+    pub fn draw_image(&mut self, path: &str) {
+        self.backend.draw_image(path, &mut self.console);
+    }
+}
+
+impl Deref for Root {
+    type Target = Console;
+
+    fn deref(&self) -> &Console {
+        &self.console
+    }
+}
+
+impl DerefMut for Root {
+    fn deref_mut(&mut self) -> &mut Console {
+        &mut self.console
+    }
+}
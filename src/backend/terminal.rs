@@ -0,0 +1,252 @@
+/// Module Terminal
+///
+/// Backend that runs the game inside a terminal, by way of crossterm. This works on every
+/// platform that crossterm supports. The terminal needs to support 24-bit colors and should be at
+/// least as large as the console.
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, KeyCode as TermKeyCode, KeyEvent, KeyEventKind,
+    KeyEventState, KeyModifiers, KeyboardEnhancementFlags, MouseButton, MouseEvent, MouseEventKind,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{
+    Color as TermColor, Print, ResetColor, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+
+// internal modules
+use backend::colors::Color;
+use backend::console::{Cell, Console};
+use backend::input::{Event, Key, KeyCode, Mouse};
+use backend::Backend;
+
+pub struct TerminalBackend {
+    closed: bool,
+    /// Whether the terminal tells numpad keys apart from the other keys.
+    keypad: bool,
+    /// What the terminal shows right now, so that only changed cells need to be redrawn.
+    screen: Vec<Cell>,
+    frame_time: Duration,
+    last_frame: Instant,
+}
+
+impl TerminalBackend {
+    pub fn new(fps: i32) -> Self {
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            Hide,
+            EnableMouseCapture,
+            Clear(ClearType::All)
+        );
+        // only some terminals can tell numpad keys apart, and only when asked to
+        let keypad = terminal::supports_keyboard_enhancement().unwrap_or(false)
+            && execute!(
+                io::stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )
+            .is_ok();
+
+        TerminalBackend {
+            closed: false,
+            keypad,
+            screen: vec![],
+            frame_time: Duration::from_millis(1000 / fps.max(1) as u64),
+            last_frame: Instant::now(),
+        }
+    }
+
+    /// Read the next event from the terminal, which blocks until there is one. Events that the
+    /// game has no use for are returned as `None`.
+    fn read_event(&mut self) -> Option<Event> {
+        match event::read() {
+            Ok(event::Event::Key(key)) if key.kind != KeyEventKind::Release => {
+                Some(Event::Key(to_key(key)))
+            }
+            Ok(event::Event::Mouse(mouse)) => Some(Event::Mouse(to_mouse(mouse))),
+            Ok(_) => None,
+            Err(_) => {
+                self.closed = true;
+                None
+            }
+        }
+    }
+
+    /// Draw every cell that changed since the last frame.
+    fn draw(&mut self, console: &Console) -> io::Result<()> {
+        let width = console.width();
+        let cells = (width * console.height()) as usize;
+        if self.screen.len() != cells {
+            // nothing is known about the screen yet, so redraw all of it
+            self.screen = vec![
+                Cell {
+                    chr: '\0',
+                    ..console.cell(0, 0)
+                };
+                cells
+            ];
+        }
+
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let mut cursor = None;
+        let mut colors = None;
+        for i in 0..cells {
+            let (x, y) = (i as i32 % width, i as i32 / width);
+            let cell = console.cell(x, y);
+            if cell == self.screen[i] {
+                continue;
+            }
+            if cursor != Some(i) {
+                queue!(out, MoveTo(x as u16, y as u16))?;
+            }
+            if colors != Some((cell.foreground, cell.background)) {
+                queue!(
+                    out,
+                    SetForegroundColor(to_term_color(cell.foreground)),
+                    SetBackgroundColor(to_term_color(cell.background))
+                )?;
+                colors = Some((cell.foreground, cell.background));
+            }
+            queue!(out, Print(cell.chr))?;
+            self.screen[i] = cell;
+            // the cursor wraps around at the end of a line on its own, but not reliably
+            cursor = if x + 1 < width { Some(i + 1) } else { None };
+        }
+        out.flush()
+    }
+}
+
+impl Drop for TerminalBackend {
+    fn drop(&mut self) {
+        if self.keypad {
+            let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(
+            io::stdout(),
+            ResetColor,
+            DisableMouseCapture,
+            Show,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn present(&mut self, console: &Console) {
+        if self.draw(console).is_err() {
+            self.closed = true;
+        }
+
+        // keep the frame rate, like libtcod does
+        let elapsed = self.last_frame.elapsed();
+        if elapsed < self.frame_time {
+            thread::sleep(self.frame_time - elapsed);
+        }
+        self.last_frame = Instant::now();
+    }
+
+    fn check_for_event(&mut self) -> Option<Event> {
+        while !self.closed && event::poll(Duration::from_secs(0)).unwrap_or(false) {
+            if let Some(event) = self.read_event() {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    fn wait_for_keypress(&mut self) -> Key {
+        while !self.closed {
+            if let Some(Event::Key(key)) = self.read_event() {
+                return key;
+            }
+        }
+        Key::default()
+    }
+
+    fn window_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+
+    fn set_fullscreen(&mut self, _fullscreen: bool) {}
+
+    fn draw_image(&mut self, _path: &str, _console: &mut Console) {}
+}
+
+fn to_term_color(color: Color) -> TermColor {
+    TermColor::Rgb {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+/// Translate a key press from crossterm into the game's own key.
+fn to_key(key: KeyEvent) -> Key {
+    let (code, printable) = match key.code {
+        TermKeyCode::Esc => (KeyCode::Escape, '\0'),
+        TermKeyCode::Enter => (KeyCode::Enter, '\0'),
+        TermKeyCode::Backspace => (KeyCode::Backspace, '\0'),
+        TermKeyCode::Tab => (KeyCode::Tab, '\0'),
+        TermKeyCode::Up => (KeyCode::Up, '\0'),
+        TermKeyCode::Down => (KeyCode::Down, '\0'),
+        TermKeyCode::Left => (KeyCode::Left, '\0'),
+        TermKeyCode::Right => (KeyCode::Right, '\0'),
+        TermKeyCode::PageUp => (KeyCode::PageUp, '\0'),
+        TermKeyCode::PageDown => (KeyCode::PageDown, '\0'),
+        TermKeyCode::Home => (KeyCode::Home, '\0'),
+        TermKeyCode::End => (KeyCode::End, '\0'),
+        // the center of the numpad, in terminals that send the other numpad keys as arrows
+        TermKeyCode::KeypadBegin => (KeyCode::NumPad5, '\0'),
+        TermKeyCode::Char(chr) if key.state.contains(KeyEventState::KEYPAD) => {
+            (numpad_key(chr), chr)
+        }
+        TermKeyCode::Char(chr) => (KeyCode::Char, chr),
+        _ => (KeyCode::Other, '\0'),
+    };
+    Key {
+        code,
+        printable,
+        alt: key.modifiers.contains(KeyModifiers::ALT),
+        ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+        shift: key.modifiers.contains(KeyModifiers::SHIFT) || printable.is_uppercase(),
+    }
+}
+
+/// Return the numpad key with the given digit, or a plain character key for the other keys of the
+/// numpad.
+fn numpad_key(digit: char) -> KeyCode {
+    match digit {
+        '1' => KeyCode::NumPad1,
+        '2' => KeyCode::NumPad2,
+        '3' => KeyCode::NumPad3,
+        '4' => KeyCode::NumPad4,
+        '5' => KeyCode::NumPad5,
+        '6' => KeyCode::NumPad6,
+        '7' => KeyCode::NumPad7,
+        '8' => KeyCode::NumPad8,
+        '9' => KeyCode::NumPad9,
+        _ => KeyCode::Char,
+    }
+}
+
+/// Translate a mouse event from crossterm. Clicks count once the button is released, like in
+/// libtcod.
+fn to_mouse(mouse: MouseEvent) -> Mouse {
+    Mouse {
+        cx: i32::from(mouse.column),
+        cy: i32::from(mouse.row),
+        lbutton_pressed: mouse.kind == MouseEventKind::Up(MouseButton::Left),
+        rbutton_pressed: mouse.kind == MouseEventKind::Up(MouseButton::Right),
+    }
+}
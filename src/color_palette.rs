@@ -2,7 +2,9 @@
 ///
 /// This module contains the color palette and related constants and methods
 /// for color calculation and manipulation.
-use tcod::colors::Color;
+// internal modules
+use backend::colors::Color;
+use world::theme::Theme;
 
 const COLOR_DARK_WALL: Color = Color {
     r: 100,
@@ -24,19 +26,213 @@ const COLOR_LIGHT_GROUND: Color = Color {
     g: 180,
     b: 50,
 };
+const COLOR_DARK_TOWN_WALL: Color = Color {
+    r: 70,
+    g: 50,
+    b: 40,
+};
+const COLOR_LIGHT_TOWN_WALL: Color = Color {
+    r: 160,
+    g: 120,
+    b: 90,
+};
+const COLOR_DARK_TOWN_GROUND: Color = Color {
+    r: 30,
+    g: 70,
+    b: 30,
+};
+const COLOR_LIGHT_TOWN_GROUND: Color = Color {
+    r: 80,
+    g: 160,
+    b: 70,
+};
+const COLOR_DARK_FLOODED_WALL: Color = Color {
+    r: 30,
+    g: 50,
+    b: 60,
+};
+const COLOR_LIGHT_FLOODED_WALL: Color = Color {
+    r: 70,
+    g: 120,
+    b: 130,
+};
+const COLOR_DARK_FLOODED_GROUND: Color = Color {
+    r: 40,
+    g: 60,
+    b: 55,
+};
+const COLOR_LIGHT_FLOODED_GROUND: Color = Color {
+    r: 100,
+    g: 150,
+    b: 130,
+};
+const COLOR_DARK_CAVE_WALL: Color = Color {
+    r: 60,
+    g: 45,
+    b: 30,
+};
+const COLOR_LIGHT_CAVE_WALL: Color = Color {
+    r: 120,
+    g: 95,
+    b: 60,
+};
+const COLOR_DARK_CAVE_GROUND: Color = Color {
+    r: 70,
+    g: 60,
+    b: 40,
+};
+const COLOR_LIGHT_CAVE_GROUND: Color = Color {
+    r: 150,
+    g: 130,
+    b: 90,
+};
+const COLOR_DARK_CRYPT_WALL: Color = Color {
+    r: 40,
+    g: 40,
+    b: 60,
+};
+const COLOR_LIGHT_CRYPT_WALL: Color = Color {
+    r: 110,
+    g: 110,
+    b: 140,
+};
+const COLOR_DARK_CRYPT_GROUND: Color = Color {
+    r: 50,
+    g: 55,
+    b: 50,
+};
+const COLOR_LIGHT_CRYPT_GROUND: Color = Color {
+    r: 130,
+    g: 140,
+    b: 120,
+};
+const COLOR_DARK_FORTRESS_WALL: Color = Color {
+    r: 70,
+    g: 70,
+    b: 70,
+};
+const COLOR_LIGHT_FORTRESS_WALL: Color = Color {
+    r: 150,
+    g: 150,
+    b: 150,
+};
+const COLOR_DARK_FORTRESS_GROUND: Color = Color {
+    r: 90,
+    g: 40,
+    b: 30,
+};
+const COLOR_LIGHT_FORTRESS_GROUND: Color = Color {
+    r: 180,
+    g: 90,
+    b: 60,
+};
+const COLOR_DOOR: Color = Color {
+    r: 220,
+    g: 150,
+    b: 60,
+};
+const COLOR_LOCKED_DOOR: Color = Color {
+    r: 200,
+    g: 200,
+    b: 220,
+};
+const COLOR_DARK_WATER: Color = Color {
+    r: 20,
+    g: 40,
+    b: 110,
+};
+const COLOR_LIGHT_WATER: Color = Color {
+    r: 40,
+    g: 90,
+    b: 200,
+};
+const COLOR_DARK_SHALLOW_WATER: Color = Color {
+    r: 30,
+    g: 60,
+    b: 100,
+};
+const COLOR_LIGHT_SHALLOW_WATER: Color = Color {
+    r: 70,
+    g: 130,
+    b: 190,
+};
+const COLOR_DARK_LAVA: Color = Color {
+    r: 130,
+    g: 30,
+    b: 0,
+};
+const COLOR_LIGHT_LAVA: Color = Color {
+    r: 250,
+    g: 110,
+    b: 20,
+};
+const COLOR_DARK_CHASM: Color = Color { r: 10, g: 5, b: 15 };
+const COLOR_LIGHT_CHASM: Color = Color {
+    r: 35,
+    g: 25,
+    b: 45,
+};
+
+/// Return the light and dark wall colors of the given level theme.
+pub fn get_col_walls(theme: Theme) -> (Color, Color) {
+    match theme {
+        Theme::Town => (COLOR_LIGHT_TOWN_WALL, COLOR_DARK_TOWN_WALL),
+        Theme::Sewers => (COLOR_LIGHT_WALL, COLOR_DARK_WALL),
+        Theme::Caves => (COLOR_LIGHT_CAVE_WALL, COLOR_DARK_CAVE_WALL),
+        Theme::Crypts => (COLOR_LIGHT_CRYPT_WALL, COLOR_DARK_CRYPT_WALL),
+        Theme::Fortress => (COLOR_LIGHT_FORTRESS_WALL, COLOR_DARK_FORTRESS_WALL),
+        Theme::Flooded => (COLOR_LIGHT_FLOODED_WALL, COLOR_DARK_FLOODED_WALL),
+    }
+}
+
+/// Return the light and dark ground colors of the given level theme.
+pub fn get_col_grounds(theme: Theme) -> (Color, Color) {
+    match theme {
+        Theme::Town => (COLOR_LIGHT_TOWN_GROUND, COLOR_DARK_TOWN_GROUND),
+        Theme::Sewers => (COLOR_LIGHT_GROUND, COLOR_DARK_GROUND),
+        Theme::Caves => (COLOR_LIGHT_CAVE_GROUND, COLOR_DARK_CAVE_GROUND),
+        Theme::Crypts => (COLOR_LIGHT_CRYPT_GROUND, COLOR_DARK_CRYPT_GROUND),
+        Theme::Fortress => (COLOR_LIGHT_FORTRESS_GROUND, COLOR_DARK_FORTRESS_GROUND),
+        Theme::Flooded => (COLOR_LIGHT_FLOODED_GROUND, COLOR_DARK_FLOODED_GROUND),
+    }
+}
+
+pub fn get_col_door() -> Color {
+    COLOR_DOOR
+}
+
+pub fn get_col_locked_door() -> Color {
+    COLOR_LOCKED_DOOR
+}
+
+pub fn get_col_dark_water() -> Color {
+    COLOR_DARK_WATER
+}
+
+pub fn get_col_light_water() -> Color {
+    COLOR_LIGHT_WATER
+}
+
+pub fn get_col_dark_shallow_water() -> Color {
+    COLOR_DARK_SHALLOW_WATER
+}
+
+pub fn get_col_light_shallow_water() -> Color {
+    COLOR_LIGHT_SHALLOW_WATER
+}
 
-pub fn get_col_dark_wall() -> Color {
-    COLOR_DARK_WALL
+pub fn get_col_dark_lava() -> Color {
+    COLOR_DARK_LAVA
 }
 
-pub fn get_col_light_wall() -> Color {
-    COLOR_LIGHT_WALL
+pub fn get_col_light_lava() -> Color {
+    COLOR_LIGHT_LAVA
 }
 
-pub fn get_col_dark_ground() -> Color {
-    COLOR_DARK_GROUND
+pub fn get_col_dark_chasm() -> Color {
+    COLOR_DARK_CHASM
 }
 
-pub fn get_col_light_ground() -> Color {
-    COLOR_LIGHT_GROUND
+pub fn get_col_light_chasm() -> Color {
+    COLOR_LIGHT_CHASM
 }
@@ -2,19 +2,100 @@
 ///
 /// Structures and methods for constructing the game ai.
 use rand::Rng;
-use tcod::colors;
+use std::cmp;
+use std::mem;
 
+use backend::colors::Color;
+use entity::behavior::{Action, Behavior, Condition};
+use entity::boss::{enter_phase, Phase, PhaseAction};
+use entity::fighter::DeathCallback;
+use entity::item::{take_one, Item, HEAL_AMOUNT};
 use entity::object::Object;
-use game_io::{FovMap, MessageLog};
+use entity::spell::{cast_spell, Spell, BOLT_RANGE, BREATH_RANGE};
+use entity::status::{has_status, is_incapacitated, Effect};
+use entity::stealth::update_awareness;
+use game_io::{FovMap, MessageLog, Severity};
 use game_state::{move_by, move_towards, GameState, PLAYER};
 use util::mut_two;
+use world::dijkstra::DijkstraMap;
+use world::{find_path, has_line_of_fire, is_blocked, is_blocked_for};
+
+// ranged monsters back away from the player when it comes closer than this
+const RANGED_MIN_DISTANCE: f32 = 3.0;
+// monsters flee when their hit points drop below this fraction of their maximum
+const FLEE_THRESHOLD: f32 = 0.25;
+// how strongly fleeing monsters are drawn towards other monsters, in percent
+const ALLY_WEIGHT: i32 = 50;
+// how far monsters with a scripted ranged attack can shoot
+const SCRIPTED_RANGE: i32 = 6;
+// turns that monsters search for the player after reaching the spot where they lost it
+const SEARCH_TURNS: i32 = 5;
+// searching monsters stay this close to the spot where they lost the player
+const SEARCH_RADIUS: f32 = 3.0;
+// companions attack enemies that are this close to both them and the player
+const COMPANION_RANGE: f32 = 5.0;
+// monsters that carry items go and fetch those within this distance
+const ITEM_SEEK_DISTANCE: f32 = 6.0;
+// pack monsters only attack once this many members of their pack are next to the player
+const PACK_ATTACK_SIZE: usize = 3;
+// bosses that back off to recover regain this many hit points per turn, until they are back at
+// this fraction of their maximum
+const BOSS_HEAL_RATE: i32 = 2;
+const BOSS_RECOVERED: f32 = 0.6;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Ai {
     Basic,
-    Confused {
+    /// Keeps its distance and attacks from up to `range` tiles away.
+    Ranged {
+        range: i32,
+    },
+    /// Keeps its distance and casts spells: bolts at the player, healing on itself and haste
+    /// on allies that fight the player.
+    Caster,
+    /// Hunts together with the other members of its pack, which share the same id.
+    Pack {
+        pack: usize,
+    },
+    /// Walks along a route of waypoints until it spots the player, then chases it as long as
+    /// the player stays in sight.
+    Patrol {
+        route: Vec<(i32, i32)>,
+        waypoint: usize,
+    },
+    /// Does nothing until it is woken up by noise or by seeing the player close by.
+    Asleep {
+        previous_ai: Box<Ai>,
+    },
+    /// Stays close to the player and attacks hostile creatures that come near.
+    Companion,
+    /// Follows a behavior that was loaded from a file, currently being in the given state.
+    Scripted {
+        behavior: Behavior,
+        state: usize,
+    },
+    /// Goes to where it has last seen the player and looks around there for a number of turns.
+    Searching {
         previous_ai: Box<Ai>,
-        num_turns: i32,
+        turns: i32,
+    },
+    /// Poses as an object with the given looks until the player comes close, then reveals
+    /// itself and attacks.
+    Disguised {
+        previous_ai: Box<Ai>,
+        name: String,
+        chr: char,
+        color: Color,
+    },
+    /// Runs away from the player until it has recovered.
+    Fleeing {
+        previous_ai: Box<Ai>,
+    },
+    /// Fights like a basic monster, but changes its tactics whenever it enters one of its
+    /// remaining phases.
+    Boss {
+        phases: Vec<Phase>,
+        recovering: bool,
     },
 }
 
@@ -29,16 +110,73 @@ pub fn ai_take_turn(
     game_state: &mut GameState,
     objects: &mut [Object],
     fov_map: &FovMap,
+    player_map: &DijkstraMap,
     monster_id: usize,
 ) {
     use self::Ai::*;
+    // monsters that are slowed down by terrain wait until they have entered their tile
+    if objects[monster_id].delay > 0 {
+        objects[monster_id].delay -= 1;
+        return;
+    }
+    // frozen and stunned monsters lose their turn, confused ones stagger about
+    if is_incapacitated(&objects[monster_id]) {
+        return;
+    }
+    if has_status(&objects[monster_id], Effect::Confused) {
+        stumble(game_state, objects, monster_id);
+        return;
+    }
+    if let Some(caster) = objects[monster_id].caster.as_mut() {
+        caster.recover();
+    }
+    if drink_healing_potion(game_state, objects, fov_map, monster_id) {
+        return;
+    }
+    update_awareness(game_state, objects, fov_map, monster_id);
     if let Some(ai) = objects[monster_id].ai.take() {
+        let ai = track_player(objects, fov_map, monster_id, ai);
+        let ai = start_fleeing(game_state, objects, fov_map, monster_id, ai);
         let new_ai = match ai {
-            Basic => ai_basic(game_state, objects, fov_map, monster_id),
-            Confused {
+            Basic => ai_basic(game_state, objects, fov_map, player_map, monster_id),
+            Ranged { range } => {
+                ai_ranged(game_state, objects, fov_map, player_map, monster_id, range)
+            }
+            Companion => ai_companion(game_state, objects, fov_map, monster_id),
+            Caster => ai_caster(game_state, objects, fov_map, player_map, monster_id),
+            Pack { pack } => ai_pack(game_state, objects, fov_map, player_map, monster_id, pack),
+            Patrol { route, waypoint } => ai_patrol(
+                game_state, objects, fov_map, player_map, monster_id, route, waypoint,
+            ),
+            Asleep { previous_ai } => {
+                ai_asleep(game_state, objects, fov_map, monster_id, previous_ai)
+            }
+            Scripted { behavior, state } => ai_scripted(
+                game_state, objects, fov_map, player_map, monster_id, behavior, state,
+            ),
+            Searching { previous_ai, turns } => {
+                ai_searching(game_state, objects, monster_id, previous_ai, turns)
+            }
+            Disguised {
                 previous_ai,
-                num_turns,
-            } => ai_confused(game_state, objects, monster_id, previous_ai, num_turns),
+                name,
+                chr,
+                color,
+            } => ai_disguised(
+                game_state,
+                objects,
+                fov_map,
+                player_map,
+                monster_id,
+                previous_ai,
+                Disguise { name, chr, color },
+            ),
+            Fleeing { previous_ai } => {
+                ai_fleeing(game_state, objects, player_map, monster_id, previous_ai)
+            }
+            Boss { phases, recovering } => ai_boss(
+                game_state, objects, fov_map, player_map, monster_id, phases, recovering,
+            ),
         };
         objects[monster_id].ai = Some(new_ai);
     }
@@ -48,51 +186,940 @@ fn ai_basic(
     game_state: &mut GameState,
     objects: &mut [Object],
     fov_map: &FovMap,
+    player_map: &DijkstraMap,
     monster_id: usize,
 ) -> Ai {
-    // A basic monster takes its turn. If you can see it, it can see you.
-    let (monster_x, monster_y) = objects[monster_id].pos();
-    if fov_map.is_in_fov(monster_x, monster_y) {
-        if objects[monster_id].distance_to(&objects[PLAYER]) >= 2.0 {
+    // A basic monster takes its turn, and hunts the player once it has noticed it.
+    if sees_player(objects, fov_map, monster_id) {
+        if fight_adjacent_enemy(game_state, objects, monster_id) {
+            // busy fighting another monster
+        } else if objects[monster_id].distance_to(&objects[PLAYER]) >= 2.0 {
             // move towards player if far away
-            let (player_x, player_y) = objects[PLAYER].pos();
-            move_towards(&game_state.world, objects, monster_id, player_x, player_y);
+            chase_player(game_state, objects, player_map, monster_id);
         } else if objects[PLAYER].fighter.map_or(false, |f| f.hp > 0) {
             // Close enough, attack! (if player is still alive)
             let (monster, player) = mut_two(objects, monster_id, PLAYER);
             monster.attack(player, game_state);
         }
+    } else if !follow_scent(game_state, objects, monster_id) {
+        seek_item(game_state, objects, monster_id);
     }
     Ai::Basic
 }
 
-fn ai_confused(
+/// A boss enters the next of its phases once its hit points drop below the phase's threshold.
+/// While recovering, it backs away from the player and heals, otherwise it fights like a basic
+/// monster.
+fn ai_boss(
     game_state: &mut GameState,
     objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
     monster_id: usize,
-    previous_ai: Box<Ai>,
-    num_turns: i32,
+    mut phases: Vec<Phase>,
+    mut recovering: bool,
 ) -> Ai {
-    if num_turns >= 0 {
-        // still confused...
-        // move in a random direction, and decrease the number of tuns confused
-        move_by(
+    let (x, y) = objects[monster_id].pos();
+    let visible = fov_map.is_in_fov(x, y);
+    while !phases.is_empty() && hp_fraction(&objects[monster_id], game_state) < phases[0].threshold
+    {
+        let phase = phases.remove(0);
+        enter_phase(game_state, &mut objects[monster_id], &phase.action, visible);
+        recovering |= phase.action == PhaseAction::Heal;
+    }
+
+    if recovering && hp_fraction(&objects[monster_id], game_state) >= BOSS_RECOVERED {
+        recovering = false;
+    }
+    if recovering {
+        objects[monster_id].heal(game_state, BOSS_HEAL_RATE);
+        if !retreat(game_state, objects, player_map, monster_id) {
+            fight_adjacent_enemy(game_state, objects, monster_id);
+        }
+    } else {
+        ai_basic(game_state, objects, fov_map, player_map, monster_id);
+    }
+    Ai::Boss { phases, recovering }
+}
+
+/// Let a hurt monster drink a healing potion from its inventory. Return true if it did.
+fn drink_healing_potion(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    monster_id: usize,
+) -> bool {
+    if !is_hurt(&objects[monster_id], game_state) {
+        return false;
+    }
+    let potion_id = objects[monster_id]
+        .inventory
+        .as_ref()
+        .and_then(|inventory| {
+            inventory
+                .iter()
+                .position(|item| item.item == Some(Item::Heal))
+        });
+    match potion_id {
+        Some(potion_id) => {
+            if let Some(inventory) = objects[monster_id].inventory.as_mut() {
+                take_one(inventory, potion_id);
+            }
+            objects[monster_id].heal(game_state, HEAL_AMOUNT);
+            let (x, y) = objects[monster_id].pos();
+            if fov_map.is_in_fov(x, y) && objects[monster_id].is_perceptible(game_state) {
+                game_state.log.add(
+                    format!("The {} drinks a healing potion.", objects[monster_id].name),
+                    Severity::Info,
+                );
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Let a monster that tracks scent take a step up the player's trail. Return true if it did.
+fn follow_scent(game_state: &mut GameState, objects: &mut [Object], monster_id: usize) -> bool {
+    if !objects[monster_id].tracks_scent {
+        return false;
+    }
+    let (x, y) = objects[monster_id].pos();
+    let step = game_state.world.scent.follow(
+        &game_state.world,
+        objects,
+        x,
+        y,
+        objects[monster_id].move_mode,
+    );
+    match step {
+        Some((new_x, new_y)) => {
+            move_by(
+                &mut game_state.world,
+                objects,
+                monster_id,
+                new_x - x,
+                new_y - y,
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+/// Let a monster that carries items walk towards the nearest item lying around. It is picked up
+/// once the monster stands on it.
+fn seek_item(game_state: &mut GameState, objects: &mut [Object], monster_id: usize) {
+    if objects[monster_id].inventory.is_none() {
+        return;
+    }
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let item = objects
+        .iter()
+        .filter(|object| {
+            object.item.is_some()
+                && object.pos() != (monster_x, monster_y)
+                && objects[monster_id].distance_to(object) <= ITEM_SEEK_DISTANCE
+        })
+        .map(|object| object.pos())
+        .min_by_key(|&(x, y)| (x - monster_x).pow(2) + (y - monster_y).pow(2));
+    match item {
+        // paths end in front of their target, so the last step is taken directly
+        Some((x, y)) if (x - monster_x).abs() <= 1 && (y - monster_y).abs() <= 1 => move_by(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            x - monster_x,
+            y - monster_y,
+        ),
+        Some(target) => move_along_path(game_state, objects, monster_id, target),
+        None => {}
+    }
+}
+
+/// A ranged monster backs away from the player when it comes too close and attacks from a
+/// distance whenever nothing stands in the line of fire.
+fn ai_ranged(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    range: i32,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let player_alive = objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+    if !sees_player(objects, fov_map, monster_id)
+        || !player_alive
+        || fight_adjacent_enemy(game_state, objects, monster_id)
+    {
+        return Ai::Ranged { range };
+    }
+
+    let distance = objects[monster_id].distance_to(&objects[PLAYER]);
+    let player_pos = objects[PLAYER].pos();
+    if distance < RANGED_MIN_DISTANCE && retreat(game_state, objects, player_map, monster_id) {
+        // backed away
+    } else if distance <= range as f32
+        && has_line_of_fire(
             &game_state.world,
             objects,
+            (monster_x, monster_y),
+            player_pos,
+        )
+    {
+        let (monster, player) = mut_two(objects, monster_id, PLAYER);
+        monster.ranged_attack(player, game_state);
+    } else {
+        chase_player(game_state, objects, player_map, monster_id);
+    }
+    Ai::Ranged { range }
+}
+
+/// A companion attacks the closest visible enemy near the player, or follows the player if
+/// there is none.
+fn ai_companion(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    companion_id: usize,
+) -> Ai {
+    let companion = &objects[companion_id];
+    let (x, y) = companion.pos();
+    let enemy = objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, other)| {
+            id != companion_id
+                && other.fighter.is_some()
+                && companion.faction.is_hostile_to(other.faction)
+                && fov_map.is_in_fov(other.x, other.y)
+                && companion.distance_to(other) <= COMPANION_RANGE
+                && other.distance_to(&objects[PLAYER]) <= COMPANION_RANGE
+        })
+        .map(|(id, other)| (id, other.pos()))
+        .min_by_key(|&(_, (other_x, other_y))| (other_x - x).pow(2) + (other_y - y).pow(2));
+
+    match enemy {
+        Some((enemy_id, _)) if objects[companion_id].distance_to(&objects[enemy_id]) < 2.0 => {
+            let (companion, enemy) = mut_two(objects, companion_id, enemy_id);
+            companion.attack(enemy, game_state);
+        }
+        Some((_, position)) => move_along_path(game_state, objects, companion_id, position),
+        None if objects[companion_id].distance_to(&objects[PLAYER]) >= 2.0 => {
+            let target = objects[PLAYER].pos();
+            move_along_path(game_state, objects, companion_id, target);
+        }
+        None => {}
+    }
+    Ai::Companion
+}
+
+/// A scripted monster first switches to the next state of its behavior, if any transition of
+/// the current state applies, and then performs the action of that state.
+fn ai_scripted(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    behavior: Behavior,
+    state: usize,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let player_pos = objects[PLAYER].pos();
+    let player_alive = objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+    let distance = objects[monster_id].distance_to(&objects[PLAYER]);
+    let sees_player = sees_player(objects, fov_map, monster_id) && player_alive;
+    let line_of_fire = sees_player
+        && distance <= SCRIPTED_RANGE as f32
+        && has_line_of_fire(
+            &game_state.world,
+            objects,
+            (monster_x, monster_y),
+            player_pos,
+        );
+    let state = behavior.next_state(state, |condition| match condition {
+        Condition::SeesPlayer => sees_player,
+        Condition::NextToPlayer => sees_player && distance < 2.0,
+        Condition::NearPlayer => sees_player && distance < RANGED_MIN_DISTANCE,
+        Condition::LineOfFire => line_of_fire,
+        Condition::Hurt => is_hurt(&objects[monster_id], game_state),
+        Condition::BadlyHurt => is_badly_hurt(&objects[monster_id], game_state),
+    });
+
+    match behavior.action(state) {
+        Action::Idle => {}
+        Action::Wander => {
+            let dx = rand::thread_rng().gen_range(-1, 2);
+            let dy = rand::thread_rng().gen_range(-1, 2);
+            move_by(&mut game_state.world, objects, monster_id, dx, dy);
+        }
+        Action::Attack if player_alive && distance < 2.0 => {
+            let (monster, player) = mut_two(objects, monster_id, PLAYER);
+            monster.attack(player, game_state);
+        }
+        Action::RangedAttack if line_of_fire => {
+            let (monster, player) = mut_two(objects, monster_id, PLAYER);
+            monster.ranged_attack(player, game_state);
+        }
+        Action::Chase | Action::Attack | Action::RangedAttack => {
+            if player_alive {
+                chase_player(game_state, objects, player_map, monster_id);
+            }
+        }
+        Action::Retreat => {
+            retreat(game_state, objects, player_map, monster_id);
+        }
+        Action::SeekItem => seek_item(game_state, objects, monster_id),
+    }
+    Ai::Scripted { behavior, state }
+}
+
+/// A caster heals itself when it is badly hurt and hastens allies that fight the player. Else
+/// it keeps its distance like a ranged monster and hurls bolts at the player, waiting for its
+/// mana to return in between.
+fn ai_caster(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let player_alive = objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+    // blast the player away when it comes too close
+    let can_repel = objects[monster_id]
+        .caster
+        .as_ref()
+        .is_some_and(|caster| caster.can_cast(Spell::Repel));
+    if can_repel
+        && player_alive
+        && sees_player(objects, fov_map, monster_id)
+        && objects[monster_id].distance_to(&objects[PLAYER]) < 2.0
+    {
+        cast_spell(
+            game_state,
+            objects,
+            fov_map,
             monster_id,
-            rand::thread_rng().gen_range(-1, 2),
-            rand::thread_rng().gen_range(-1, 2),
+            PLAYER,
+            Spell::Repel,
         );
-        Ai::Confused {
-            previous_ai,
-            num_turns: num_turns - 1,
+        return Ai::Caster;
+    }
+    if !sees_player(objects, fov_map, monster_id)
+        || !player_alive
+        || fight_adjacent_enemy(game_state, objects, monster_id)
+    {
+        return Ai::Caster;
+    }
+
+    let can_cast = |objects: &[Object], spell: Spell| {
+        objects[monster_id]
+            .caster
+            .as_ref()
+            .is_some_and(|caster| caster.can_cast(spell))
+    };
+    let hurt = is_hurt(&objects[monster_id], game_state);
+    let faction = objects[monster_id].faction;
+    let ally_id = objects.iter().enumerate().position(|(id, other)| {
+        id != PLAYER
+            && id != monster_id
+            && other.ai.is_some()
+            && other.haste == 0
+            && !faction.is_hostile_to(other.faction)
+            && other.distance_to(&objects[PLAYER]) < 2.0
+            && fov_map.is_in_fov(other.x, other.y)
+    });
+    let distance = objects[monster_id].distance_to(&objects[PLAYER]);
+    let player_pos = objects[PLAYER].pos();
+    let in_line_of_fire = distance <= BOLT_RANGE as f32
+        && has_line_of_fire(
+            &game_state.world,
+            objects,
+            (monster_x, monster_y),
+            player_pos,
+        );
+
+    let in_breath_range = in_line_of_fire && distance <= BREATH_RANGE as f32;
+    let breath = [Spell::FireBreath, Spell::FrostBreath]
+        .iter()
+        .cloned()
+        .find(|&spell| can_cast(objects, spell));
+
+    if hurt && can_cast(objects, Spell::Heal) {
+        cast_spell(
+            game_state,
+            objects,
+            fov_map,
+            monster_id,
+            monster_id,
+            Spell::Heal,
+        );
+    } else if let Some(ally_id) = ally_id.filter(|_| can_cast(objects, Spell::Haste)) {
+        cast_spell(
+            game_state,
+            objects,
+            fov_map,
+            monster_id,
+            ally_id,
+            Spell::Haste,
+        );
+    } else if distance < RANGED_MIN_DISTANCE && retreat(game_state, objects, player_map, monster_id)
+    {
+        // backed away
+    } else if let Some(breath) = breath.filter(|_| in_breath_range) {
+        cast_spell(game_state, objects, fov_map, monster_id, PLAYER, breath);
+    } else if in_line_of_fire && can_cast(objects, Spell::Bolt) {
+        cast_spell(
+            game_state,
+            objects,
+            fov_map,
+            monster_id,
+            PLAYER,
+            Spell::Bolt,
+        );
+    } else if distance < 2.0 {
+        // cornered and out of mana
+        let (monster, player) = mut_two(objects, monster_id, PLAYER);
+        monster.attack(player, game_state);
+    } else if !in_line_of_fire {
+        chase_player(game_state, objects, player_map, monster_id);
+    }
+    Ai::Caster
+}
+
+/// Take a step away from the player, avoiding dead ends. Return false if there is no way back.
+fn retreat(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    player_map: &DijkstraMap,
+    monster_id: usize,
+) -> bool {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let step = player_map.inverted(&game_state.world).descend(
+        &game_state.world,
+        objects,
+        monster_x,
+        monster_y,
+    );
+    match step {
+        Some((x, y)) => {
+            move_by(
+                &mut game_state.world,
+                objects,
+                monster_id,
+                x - monster_x,
+                y - monster_y,
+            );
+            true
         }
+        None => false,
+    }
+}
+
+/// A pack monster tries to take a free spot around the player, so that the pack surrounds it
+/// instead of queueing up. It only attacks once enough of the pack is next to the player.
+fn ai_pack(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    pack: usize,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let player_alive = objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+    if !sees_player(objects, fov_map, monster_id)
+        || !player_alive
+        || fight_adjacent_enemy(game_state, objects, monster_id)
+    {
+        return Ai::Pack { pack };
+    }
+
+    // the monster itself is not counted, since its AI is taken while it is its turn
+    let next_to_player = |object: &Object| object.distance_to(&objects[PLAYER]) < 2.0;
+    let members = objects
+        .iter()
+        .filter(|object| object.ai == Some(Ai::Pack { pack }))
+        .count()
+        + 1;
+    let members_at_player = objects
+        .iter()
+        .filter(|object| object.ai == Some(Ai::Pack { pack }) && next_to_player(object))
+        .count()
+        + 1;
+
+    if next_to_player(&objects[monster_id]) {
+        if members_at_player >= cmp::min(PACK_ATTACK_SIZE, members) {
+            let (monster, player) = mut_two(objects, monster_id, PLAYER);
+            monster.attack(player, game_state);
+        }
+        return Ai::Pack { pack };
+    }
+
+    // head for the closest free spot around the player
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let free_spot = (player_y - 1..=player_y + 1)
+        .flat_map(|y| (player_x - 1..=player_x + 1).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            game_state.world.in_bounds(x, y)
+                && !is_blocked_for(
+                    &game_state.world,
+                    objects,
+                    x,
+                    y,
+                    objects[monster_id].move_mode,
+                )
+                && !game_state.world.get(x, y).is_hazardous()
+        })
+        .min_by_key(|&(x, y)| (x - monster_x).pow(2) + (y - monster_y).pow(2));
+    match free_spot {
+        // paths end in front of their target, so the last step is taken directly
+        Some((x, y)) if (x - monster_x).abs() <= 1 && (y - monster_y).abs() <= 1 => move_by(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            x - monster_x,
+            y - monster_y,
+        ),
+        Some(spot) => move_along_path(game_state, objects, monster_id, spot),
+        None => chase_player(game_state, objects, player_map, monster_id),
+    }
+    Ai::Pack { pack }
+}
+
+/// A patrolling monster acts like a basic one while it sees the player. Otherwise it walks to
+/// its next waypoint, which counts as reached once the monster stands next to it.
+fn ai_patrol(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    route: Vec<(i32, i32)>,
+    mut waypoint: usize,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    if sees_player(objects, fov_map, monster_id) {
+        ai_basic(game_state, objects, fov_map, player_map, monster_id);
+    } else if !route.is_empty() {
+        let (x, y) = route[waypoint];
+        if (x - monster_x).abs() <= 1 && (y - monster_y).abs() <= 1 {
+            waypoint = (waypoint + 1) % route.len();
+        }
+        move_along_path(game_state, objects, monster_id, route[waypoint]);
+    }
+    Ai::Patrol { route, waypoint }
+}
+
+/// Attack a creature of a hostile faction next to the monster, unless the player is within
+/// reach. Return true if the monster attacked.
+fn fight_adjacent_enemy(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    monster_id: usize,
+) -> bool {
+    if objects[monster_id].distance_to(&objects[PLAYER]) < 2.0 {
+        return false;
+    }
+    let monster = &objects[monster_id];
+    let enemy_id = objects.iter().enumerate().position(|(id, other)| {
+        id != PLAYER
+            && id != monster_id
+            && other.fighter.is_some()
+            && monster.faction.is_hostile_to(other.faction)
+            && monster.distance_to(other) < 2.0
+    });
+    match enemy_id {
+        Some(enemy_id) => {
+            let (monster, enemy) = mut_two(objects, monster_id, enemy_id);
+            monster.attack(enemy, game_state);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Move the monster one step towards the player, down the shared map if it fits the monster.
+fn chase_player(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    player_map: &DijkstraMap,
+    monster_id: usize,
+) {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let step = if objects[monster_id].move_mode == player_map.move_mode {
+        player_map.descend(&game_state.world, objects, monster_x, monster_y)
     } else {
-        // restore the previous AI (this one will be deleted)
-        game_state.log.add(
-            format!("The {} is no longer confused!", objects[monster_id].name),
-            colors::RED,
-        );
+        None
+    };
+    match step {
+        Some((x, y)) => move_by(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            x - monster_x,
+            y - monster_y,
+        ),
+        None => {
+            let target = objects[PLAYER].pos();
+            move_along_path(game_state, objects, monster_id, target);
+        }
+    }
+}
+
+/// Move the monster one step along its path to the target. The path is kept between turns and
+/// only searched anew when the target has moved away from its end or the next step is blocked.
+/// Without any path, the monster walks straight towards the target.
+fn move_along_path(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    monster_id: usize,
+    target: (i32, i32),
+) {
+    let (x, y) = objects[monster_id].pos();
+    let move_mode = objects[monster_id].move_mode;
+    let mut path = mem::take(&mut objects[monster_id].path);
+
+    // a target that took a single step can still be followed along the old path
+    let adjacent = |a: (i32, i32), b: (i32, i32)| (a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1;
+    if let Some(&end) = path.last() {
+        if end != target && adjacent(end, target) && !path.contains(&target) {
+            path.push(target);
+        }
+    }
+    let valid = path.last() == Some(&target)
+        && path.first().is_some_and(|&next| {
+            adjacent(next, (x, y))
+                && (next == target
+                    || !is_blocked_for(&game_state.world, objects, next.0, next.1, move_mode))
+        });
+    if !valid {
+        path = find_path(&game_state.world, objects, (x, y), target, move_mode);
+    }
+
+    match path.first() {
+        Some(&next) if next != target => {
+            move_by(
+                &mut game_state.world,
+                objects,
+                monster_id,
+                next.0 - x,
+                next.1 - y,
+            );
+            if objects[monster_id].pos() == next {
+                path.remove(0);
+            }
+        }
+        Some(_) => {}
+        None => move_towards(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            target.0,
+            target.1,
+        ),
+    }
+    objects[monster_id].path = path;
+}
+
+/// A sleeping monster wakes up when it notices the player close by.
+fn ai_asleep(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    monster_id: usize,
+    previous_ai: Box<Ai>,
+) -> Ai {
+    if sees_player(objects, fov_map, monster_id) {
+        if objects[monster_id].is_perceptible(game_state) {
+            game_state.log.add(
+                format!("The {} notices you!", objects[monster_id].name),
+                Severity::Warning,
+            );
+        }
         *previous_ai
+    } else {
+        Ai::Asleep { previous_ai }
+    }
+}
+
+/// The true looks of a disguised monster.
+struct Disguise {
+    name: String,
+    chr: char,
+    color: Color,
+}
+
+/// A disguised monster keeps still until the player comes next to it. Then it takes on its
+/// true looks and attacks right away.
+fn ai_disguised(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    previous_ai: Box<Ai>,
+    disguise: Disguise,
+) -> Ai {
+    if objects[monster_id].distance_to(&objects[PLAYER]) >= 2.0 {
+        return Ai::Disguised {
+            previous_ai,
+            name: disguise.name,
+            chr: disguise.chr,
+            color: disguise.color,
+        };
+    }
+    let monster = &mut objects[monster_id];
+    game_state.log.add(
+        format!("The {} is really a {}!", monster.name, disguise.name),
+        Severity::Warning,
+    );
+    monster.name = disguise.name;
+    monster.chr = disguise.chr;
+    monster.color = disguise.color;
+    match *previous_ai {
+        Ai::Basic => ai_basic(game_state, objects, fov_map, player_map, monster_id),
+        ai => ai,
+    }
+}
+
+/// Hide the monster's true looks behind those of the given object, until the player comes
+/// close.
+pub fn disguise_as(monster: &mut Object, name: &str, chr: char, color: Color) {
+    let previous_ai = monster.ai.take().unwrap_or(Ai::Basic);
+    monster.ai = Some(Ai::Disguised {
+        previous_ai: Box::new(previous_ai),
+        name: mem::replace(&mut monster.name, name.into()),
+        chr: mem::replace(&mut monster.chr, chr),
+        color: mem::replace(&mut monster.color, color),
+    });
+}
+
+/// Wake up the monster if it is asleep. Return true if it woke up.
+pub fn wake_up(monster: &mut Object) -> bool {
+    match monster.ai.take() {
+        Some(Ai::Asleep { previous_ai }) => {
+            monster.ai = Some(*previous_ai);
+            true
+        }
+        ai => {
+            monster.ai = ai;
+            false
+        }
+    }
+}
+
+/// Return true if the monster sees the player and has noticed it.
+fn sees_player(objects: &[Object], fov_map: &FovMap, monster_id: usize) -> bool {
+    let (x, y) = objects[monster_id].pos();
+    fov_map.is_in_fov(x, y) && objects[monster_id].aware
+}
+
+/// Remember where the monster sees the player. A monster that hunts the player starts
+/// searching for it once it loses sight of it, and stops as soon as it sees it again.
+fn track_player(objects: &mut [Object], fov_map: &FovMap, monster_id: usize, ai: Ai) -> Ai {
+    if let Ai::Asleep { .. } = ai {
+        return ai;
+    }
+    let sees_player = sees_player(objects, fov_map, monster_id)
+        && objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+    if sees_player {
+        objects[monster_id].last_seen = Some(objects[PLAYER].pos());
+    }
+    match ai {
+        Ai::Searching { previous_ai, .. } if sees_player => *previous_ai,
+        Ai::Basic | Ai::Ranged { .. } | Ai::Caster | Ai::Pack { .. } | Ai::Patrol { .. }
+            if !sees_player && objects[monster_id].last_seen.is_some() =>
+        {
+            Ai::Searching {
+                previous_ai: Box::new(ai),
+                turns: SEARCH_TURNS,
+            }
+        }
+        _ => ai,
+    }
+}
+
+/// A searching monster walks to where it has last seen the player and then wanders around
+/// that spot until it gives up and returns to what it did before.
+fn ai_searching(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    monster_id: usize,
+    previous_ai: Box<Ai>,
+    turns: i32,
+) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let (x, y) = match objects[monster_id].last_seen {
+        Some(position) => position,
+        None => return *previous_ai,
+    };
+    if turns <= 0 {
+        objects[monster_id].last_seen = None;
+        return *previous_ai;
+    }
+    // as long as there is a trail, the search goes on
+    if follow_scent(game_state, objects, monster_id) {
+        return Ai::Searching {
+            previous_ai,
+            turns: SEARCH_TURNS,
+        };
+    }
+
+    if objects[monster_id].distance(x, y) >= 2.0 {
+        move_along_path(game_state, objects, monster_id, (x, y));
+        // a monster that cannot get any closer gives up eventually
+        let stuck = objects[monster_id].pos() == (monster_x, monster_y);
+        return Ai::Searching {
+            previous_ai,
+            turns: if stuck { turns - 1 } else { turns },
+        };
+    }
+    if (monster_x, monster_y) != (x, y) && !is_blocked(&game_state.world, objects, x, y) {
+        // paths end in front of their target, so the last step is taken directly
+        move_by(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            x - monster_x,
+            y - monster_y,
+        );
+    } else {
+        // look around, without straying too far
+        let dx = rand::thread_rng().gen_range(-1, 2);
+        let dy = rand::thread_rng().gen_range(-1, 2);
+        let (new_x, new_y) = (monster_x + dx, monster_y + dy);
+        if ((new_x - x).pow(2) + (new_y - y).pow(2)) as f32 <= SEARCH_RADIUS * SEARCH_RADIUS {
+            move_by(&mut game_state.world, objects, monster_id, dx, dy);
+        }
+    }
+    Ai::Searching {
+        previous_ai,
+        turns: turns - 1,
+    }
+}
+
+/// Return true if the monster has lost more than half of its hit points.
+fn is_hurt(monster: &Object, game_state: &GameState) -> bool {
+    let max_hp = monster.max_hp(game_state);
+    monster
+        .fighter
+        .is_some_and(|fighter| fighter.hp * 2 < max_hp)
+}
+
+/// Return true if the monster is hurt so badly that it would rather flee than fight.
+fn is_badly_hurt(monster: &Object, game_state: &GameState) -> bool {
+    let max_hp = monster.max_hp(game_state);
+    monster.fighter.is_some_and(|fighter| {
+        fighter.on_death != DeathCallback::Boss
+            && (fighter.hp as f32) < FLEE_THRESHOLD * max_hp as f32
+    })
+}
+
+/// Return the fraction of its maximum hit points that the monster has left.
+fn hp_fraction(monster: &Object, game_state: &GameState) -> f32 {
+    let max_hp = monster.max_hp(game_state);
+    monster
+        .fighter
+        .map_or(1.0, |fighter| fighter.hp as f32 / max_hp as f32)
+}
+
+/// Let a badly hurt monster start to flee, unless it is confused or already fleeing. Return
+/// the AI it follows from now on.
+fn start_fleeing(
+    game_state: &mut GameState,
+    objects: &[Object],
+    fov_map: &FovMap,
+    monster_id: usize,
+    ai: Ai,
+) -> Ai {
+    match ai {
+        Ai::Basic | Ai::Ranged { .. } | Ai::Caster | Ai::Pack { .. } | Ai::Patrol { .. }
+            if is_badly_hurt(&objects[monster_id], game_state) =>
+        {
+            let (x, y) = objects[monster_id].pos();
+            if fov_map.is_in_fov(x, y) && objects[monster_id].is_perceptible(game_state) {
+                game_state.log.add(
+                    format!("The {} turns to flee!", objects[monster_id].name),
+                    Severity::Info,
+                );
+            }
+            Ai::Fleeing {
+                previous_ai: Box::new(ai),
+            }
+        }
+        _ => ai,
+    }
+}
+
+/// A fleeing monster runs away from the player, preferably towards other monsters. If it is
+/// cornered, it fights back.
+fn ai_fleeing(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    player_map: &DijkstraMap,
+    monster_id: usize,
+    previous_ai: Box<Ai>,
+) -> Ai {
+    if !is_badly_hurt(&objects[monster_id], game_state) {
+        return *previous_ai;
+    }
+
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let faction = objects[monster_id].faction;
+    let allies: Vec<(i32, i32)> = objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, object)| {
+            id != PLAYER
+                && id != monster_id
+                && object.ai.is_some()
+                && !faction.is_hostile_to(object.faction)
+        })
+        .map(|(_, object)| object.pos())
+        .collect();
+    let mut flee_map = player_map.inverted(&game_state.world);
+    if !allies.is_empty() {
+        let ally_map = DijkstraMap::new(&game_state.world, &allies, player_map.move_mode);
+        flee_map = flee_map.added(&ally_map, ALLY_WEIGHT);
+    }
+
+    match flee_map.descend(&game_state.world, objects, monster_x, monster_y) {
+        Some((x, y)) => move_by(
+            &mut game_state.world,
+            objects,
+            monster_id,
+            x - monster_x,
+            y - monster_y,
+        ),
+        None => {
+            let player_alive = objects[PLAYER].fighter.is_some_and(|f| f.hp > 0);
+            if objects[monster_id].distance_to(&objects[PLAYER]) < 2.0 && player_alive {
+                let (monster, player) = mut_two(objects, monster_id, PLAYER);
+                monster.attack(player, game_state);
+            }
+        }
+    }
+    Ai::Fleeing { previous_ai }
+}
+
+/// Let a confused monster move in a random direction, attacking whoever stands there no
+/// matter the faction.
+fn stumble(game_state: &mut GameState, objects: &mut [Object], monster_id: usize) {
+    let dx = rand::thread_rng().gen_range(-1, 2);
+    let dy = rand::thread_rng().gen_range(-1, 2);
+    let (x, y) = (objects[monster_id].x + dx, objects[monster_id].y + dy);
+    let victim_id = objects.iter().enumerate().position(|(id, other)| {
+        id != monster_id && other.fighter.is_some() && other.pos() == (x, y)
+    });
+    match victim_id {
+        Some(victim_id) => {
+            let (monster, victim) = mut_two(objects, monster_id, victim_id);
+            monster.attack(victim, game_state);
+        }
+        None => move_by(&mut game_state.world, objects, monster_id, dx, dy),
     }
 }
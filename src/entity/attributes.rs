@@ -0,0 +1,132 @@
+/// Module Attributes
+///
+/// The player has four attributes that most of its other values are derived from. Strength
+/// adds to the damage of attacks and to how much the player can carry, dexterity makes attacks
+/// more accurate and easier to evade, intelligence strengthens spells and constitution grants
+/// hit points. Every attribute starts out at ten, which neither helps nor hinders.
+use std::cmp;
+
+// internal modules
+use entity::item::INVENTORY_SIZE;
+use entity::object::Object;
+use entity::polymorph::lasting_form;
+
+// value of an attribute that gives no bonus at all
+const BASE_ATTRIBUTE: i32 = 10;
+// number of stacks the player can carry without any strength
+const CARRY_BASE: i32 = 12;
+// hit points and mana gained for every point of constitution and intelligence
+const HP_PER_CONSTITUTION: i32 = 10;
+const MANA_PER_INTELLIGENCE: i32 = 3;
+/// Points that an attribute is raised by when the player levels up.
+pub const ATTRIBUTE_PER_LEVEL: i32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Intelligence,
+    Constitution,
+}
+
+/// All attributes, in the order they are shown to the player.
+pub const ATTRIBUTES: [Attribute; 4] = [
+    Attribute::Strength,
+    Attribute::Dexterity,
+    Attribute::Intelligence,
+    Attribute::Constitution,
+];
+
+impl Attribute {
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::Strength => "Strength",
+            Attribute::Dexterity => "Dexterity",
+            Attribute::Intelligence => "Intelligence",
+            Attribute::Constitution => "Constitution",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Attributes {
+    pub strength: i32,
+    pub dexterity: i32,
+    pub intelligence: i32,
+    pub constitution: i32,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes {
+            strength: BASE_ATTRIBUTE,
+            dexterity: BASE_ATTRIBUTE,
+            intelligence: BASE_ATTRIBUTE,
+            constitution: BASE_ATTRIBUTE,
+        }
+    }
+}
+
+impl Attributes {
+    pub fn get(&self, attribute: Attribute) -> i32 {
+        match attribute {
+            Attribute::Strength => self.strength,
+            Attribute::Dexterity => self.dexterity,
+            Attribute::Intelligence => self.intelligence,
+            Attribute::Constitution => self.constitution,
+        }
+    }
+
+    fn get_mut(&mut self, attribute: Attribute) -> &mut i32 {
+        match attribute {
+            Attribute::Strength => &mut self.strength,
+            Attribute::Dexterity => &mut self.dexterity,
+            Attribute::Intelligence => &mut self.intelligence,
+            Attribute::Constitution => &mut self.constitution,
+        }
+    }
+
+    /// Return the bonus that the attribute grants, one for every two points above ten, or a
+    /// penalty if it is below.
+    pub fn bonus(&self, attribute: Attribute) -> i32 {
+        (self.get(attribute) - BASE_ATTRIBUTE).div_euclid(2)
+    }
+
+    /// Return the number of stacks of items that the player can carry.
+    pub fn carry_capacity(&self) -> usize {
+        cmp::min(CARRY_BASE + self.strength, INVENTORY_SIZE as i32).max(0) as usize
+    }
+
+    /// Return the bonus that intelligence adds to the damage of spells.
+    pub fn spell_power(&self) -> i32 {
+        self.bonus(Attribute::Intelligence)
+    }
+}
+
+/// Raise the attribute of the player by the given amount, or lower it if the amount is
+/// negative. Hit points and mana grow and shrink along with constitution and intelligence, those
+/// of the player's own form if it is polymorphed.
+pub fn raise_attribute(
+    player: &mut Object,
+    attributes: &mut Attributes,
+    attribute: Attribute,
+    amount: i32,
+) {
+    *attributes.get_mut(attribute) += amount;
+    let (fighter, caster) = lasting_form(player);
+    match attribute {
+        Attribute::Constitution => {
+            if let Some(fighter) = fighter {
+                fighter.base_max_hp += amount * HP_PER_CONSTITUTION;
+                fighter.hp += amount * HP_PER_CONSTITUTION;
+            }
+        }
+        Attribute::Intelligence => {
+            if let Some(caster) = caster {
+                caster.max_mana += amount * MANA_PER_INTELLIGENCE;
+                caster.mana += amount * MANA_PER_INTELLIGENCE;
+            }
+        }
+        Attribute::Strength | Attribute::Dexterity => {}
+    }
+}
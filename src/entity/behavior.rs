@@ -0,0 +1,212 @@
+/// Module Behavior
+///
+/// Behaviors are monster AIs written as data instead of code. A behavior is a small state
+/// machine in a plain-text file, named after the behavior, in which every state performs one
+/// action per turn and lists the transitions to other states:
+///
+/// ```text
+/// state lurk: wander
+///     sees_player -> hunt
+/// state hunt: attack
+///     hurt -> run
+///     not sees_player -> lurk
+/// state run: retreat
+///     not near_player -> lurk
+/// ```
+///
+/// The first state is the initial one. Each turn, the first transition whose condition holds
+/// switches to its state before the action is performed. Conditions are `sees_player`,
+/// `next_to_player`, `near_player`, `line_of_fire`, `hurt` and `badly_hurt`, each of which can
+/// be negated with `not`. Actions are `idle`, `wander`, `chase`, `attack`, `ranged_attack`,
+/// `retreat` and `seek_item`.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+// internal modules
+use util::load_dir;
+
+/// Directory that is searched for behavior files.
+pub const BEHAVIOR_DIR: &str = "assets/behaviors";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Idle,
+    /// Steps in a random direction.
+    Wander,
+    /// Moves towards the player.
+    Chase,
+    /// Attacks the player when next to it and chases it otherwise.
+    Attack,
+    /// Attacks the player from afar when it is in the line of fire and chases it otherwise.
+    RangedAttack,
+    /// Moves away from the player.
+    Retreat,
+    /// Walks towards items lying around, if the monster can carry them.
+    SeekItem,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    SeesPlayer,
+    NextToPlayer,
+    /// The player is too close for comfort of ranged monsters.
+    NearPlayer,
+    LineOfFire,
+    /// Lost more than half of its hit points.
+    Hurt,
+    /// Hurt so badly that most monsters would flee.
+    BadlyHurt,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Transition {
+    condition: Condition,
+    negated: bool,
+    target: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct State {
+    name: String,
+    action: Action,
+    transitions: Vec<Transition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Behavior {
+    states: Vec<State>,
+}
+
+impl Behavior {
+    /// Return the state that follows the given one, using `holds` to check the conditions of
+    /// its transitions.
+    pub fn next_state<F>(&self, state: usize, holds: F) -> usize
+    where
+        F: Fn(Condition) -> bool,
+    {
+        self.states[state]
+            .transitions
+            .iter()
+            .find(|transition| holds(transition.condition) != transition.negated)
+            .map_or(state, |transition| transition.target)
+    }
+
+    /// Return the action that is performed in the given state.
+    pub fn action(&self, state: usize) -> Action {
+        self.states[state].action
+    }
+}
+
+thread_local! {
+    // the behaviors from the behavior directory by name, loaded once at startup
+    static BEHAVIORS: RefCell<Option<HashMap<String, Behavior>>> = const { RefCell::new(None) };
+}
+
+/// Load the behaviors from the behavior directory, unless they have been loaded already.
+pub fn cache_behaviors() {
+    BEHAVIORS.with(|behaviors| {
+        if behaviors.borrow().is_some() {
+            return;
+        }
+        let loaded = load_behaviors(BEHAVIOR_DIR).unwrap_or_else(|e| {
+            eprintln!("unable to load behaviors: {}", e);
+            HashMap::new()
+        });
+        *behaviors.borrow_mut() = Some(loaded);
+    });
+}
+
+/// Return the behavior of the given name, if there is one.
+pub fn behavior(name: &str) -> Option<Behavior> {
+    cache_behaviors();
+    BEHAVIORS.with(|behaviors| behaviors.borrow().as_ref()?.get(name).cloned())
+}
+
+/// Load all behavior files (ending in `.txt`) from the given directory, keyed by the file name.
+pub fn load_behaviors<P: AsRef<Path>>(dir: P) -> Result<HashMap<String, Behavior>, Box<dyn Error>> {
+    let behaviors = load_dir(dir, "txt", |name, text| {
+        Ok((name.to_string(), parse_behavior(text)?))
+    })?;
+    Ok(behaviors.into_iter().collect())
+}
+
+fn parse_behavior(text: &str) -> Result<Behavior, Box<dyn Error>> {
+    let mut states: Vec<State> = vec![];
+    // transitions refer to states by name until all states are known
+    let mut targets: Vec<Vec<&str>> = vec![];
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if line.starts_with("state ") {
+            let (name, action) = match words.as_slice() {
+                ["state", name, action] if name.ends_with(':') => {
+                    (name.trim_end_matches(':'), parse_action(action)?)
+                }
+                _ => return Err(format!("invalid state '{}'", line).into()),
+            };
+            states.push(State {
+                name: name.to_string(),
+                action,
+                transitions: vec![],
+            });
+            targets.push(vec![]);
+            continue;
+        }
+
+        let (negated, condition, target) = match words.as_slice() {
+            ["not", condition, "->", target] => (true, condition, target),
+            [condition, "->", target] => (false, condition, target),
+            _ => return Err(format!("invalid transition '{}'", line).into()),
+        };
+        let state = states
+            .last_mut()
+            .ok_or_else(|| format!("transition '{}' outside of a state", line))?;
+        state.transitions.push(Transition {
+            condition: parse_condition(condition)?,
+            negated,
+            target: 0,
+        });
+        targets.last_mut().unwrap().push(target);
+    }
+
+    if states.is_empty() {
+        return Err("no states".into());
+    }
+    for (i, names) in targets.iter().enumerate() {
+        for (j, name) in names.iter().enumerate() {
+            let target = states
+                .iter()
+                .position(|state| state.name == *name)
+                .ok_or_else(|| format!("unknown state '{}'", name))?;
+            states[i].transitions[j].target = target;
+        }
+    }
+    Ok(Behavior { states })
+}
+
+fn parse_action(word: &str) -> Result<Action, String> {
+    match word {
+        "idle" => Ok(Action::Idle),
+        "wander" => Ok(Action::Wander),
+        "chase" => Ok(Action::Chase),
+        "attack" => Ok(Action::Attack),
+        "ranged_attack" => Ok(Action::RangedAttack),
+        "retreat" => Ok(Action::Retreat),
+        "seek_item" => Ok(Action::SeekItem),
+        _ => Err(format!("unknown action '{}'", word)),
+    }
+}
+
+fn parse_condition(word: &str) -> Result<Condition, String> {
+    match word {
+        "sees_player" => Ok(Condition::SeesPlayer),
+        "next_to_player" => Ok(Condition::NextToPlayer),
+        "near_player" => Ok(Condition::NearPlayer),
+        "line_of_fire" => Ok(Condition::LineOfFire),
+        "hurt" => Ok(Condition::Hurt),
+        "badly_hurt" => Ok(Condition::BadlyHurt),
+        _ => Err(format!("unknown condition '{}'", word)),
+    }
+}
@@ -0,0 +1,81 @@
+/// Module Boss
+///
+/// Bosses fight in phases. Whenever their hit points drop below the threshold of their next
+/// phase, they change the way they fight: they call for help, fly into a rage or back off to
+/// recover.
+// internal modules
+use entity::champion::Affix;
+use entity::object::Object;
+use entity::spawner::Spawner;
+use game_io::{MessageLog, Severity};
+use game_state::GameState;
+
+// turns between two minions that a boss summons
+const SUMMON_INTERVAL: i32 = 8;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PhaseAction {
+    /// Keeps calling minions of the given spawn table entry to its side.
+    Summon(String),
+    /// Hits harder and acts twice per turn.
+    Enrage,
+    /// Backs away from the player and heals until it has recovered.
+    Heal,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Phase {
+    /// Fraction of the maximum hit points below which the phase begins.
+    pub threshold: f32,
+    pub action: PhaseAction,
+}
+
+/// Return the phases of a boss that summons the given minions, ordered by falling thresholds.
+pub fn boss_phases(minion: &str) -> Vec<Phase> {
+    vec![
+        Phase {
+            threshold: 0.75,
+            action: PhaseAction::Summon(minion.into()),
+        },
+        Phase {
+            threshold: 0.5,
+            action: PhaseAction::Enrage,
+        },
+        Phase {
+            threshold: 0.25,
+            action: PhaseAction::Heal,
+        },
+    ]
+}
+
+/// Let the boss begin the given phase, announcing it if the player can see the boss.
+pub fn enter_phase(
+    game_state: &mut GameState,
+    boss: &mut Object,
+    action: &PhaseAction,
+    visible: bool,
+) {
+    let message = match action {
+        PhaseAction::Summon(minion) => {
+            boss.spawner = Some(Spawner {
+                monster: minion.clone(),
+                interval: SUMMON_INTERVAL,
+                countdown: 0,
+            });
+            format!("The {} calls for help!", boss.name)
+        }
+        PhaseAction::Enrage => {
+            if let Some(fighter) = boss.fighter.as_mut() {
+                fighter.base_power += fighter.base_power / 2;
+            }
+            if !boss.affixes.contains(&Affix::Fast) {
+                boss.affixes.push(Affix::Fast);
+            }
+            format!("The {} flies into a rage!", boss.name)
+        }
+        PhaseAction::Heal => format!("The {} backs off to recover!", boss.name),
+    };
+    if visible {
+        game_state.log.add(message, Severity::Warning);
+    }
+}
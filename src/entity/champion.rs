@@ -0,0 +1,116 @@
+/// Module Champion
+///
+/// Champions are stronger versions of ordinary monsters. Each of them has one or two affixes
+/// that change how it fights, shows them in its name and is worth more experience.
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// internal modules
+use backend::colors::{self, Color};
+use entity::object::Object;
+use game_io::{MessageLog, Severity};
+use game_state::GameState;
+
+const CHAMPION_COLOR: Color = colors::YELLOW;
+/// Number of turns a venomous bite keeps poisoning its victim.
+pub const VENOM_TURNS: i32 = 5;
+/// Number of turns the player stays blind after being hurt by a blinding champion.
+pub const BLINDING_TURNS: i32 = 4;
+/// Vampiric monsters heal by the damage they deal divided by this.
+pub const VAMPIRIC_DIVISOR: i32 = 2;
+// maximum hit points that the player loses to every hit of a draining monster, and the least
+// maximum hit points that draining leaves it with
+const DRAIN_AMOUNT: i32 = 2;
+const MIN_DRAINED_HP: i32 = 10;
+// extra defense of shielded champions
+const SHIELD_DEFENSE: i32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Affix {
+    /// Acts twice per turn.
+    Fast,
+    /// Regains a hit point every turn.
+    Regenerating,
+    /// Poisons whoever it hurts.
+    Venomous,
+    /// Has a higher defense.
+    Shielded,
+    /// Blinds the player when it hurts it.
+    Blinding,
+    /// Heals by part of the damage it deals.
+    Vampiric,
+    /// Drains the maximum hit points of the player when it hurts it, until they are restored.
+    /// Only undead are born with it, champions never gain it.
+    Draining,
+}
+
+impl Affix {
+    fn adjective(self) -> &'static str {
+        match self {
+            Affix::Fast => "fast",
+            Affix::Regenerating => "regenerating",
+            Affix::Venomous => "venomous",
+            Affix::Shielded => "shielded",
+            Affix::Blinding => "blinding",
+            Affix::Vampiric => "vampiric",
+            Affix::Draining => "draining",
+        }
+    }
+}
+
+const AFFIXES: [Affix; 6] = [
+    Affix::Fast,
+    Affix::Regenerating,
+    Affix::Venomous,
+    Affix::Shielded,
+    Affix::Blinding,
+    Affix::Vampiric,
+];
+
+/// Drain some of the maximum hit points of the player. They stay lost until the player drinks
+/// a potion of restoration.
+pub fn drain_max_hp(game_state: &mut GameState, player: &mut Object) {
+    if let Some(fighter) = player.fighter.as_mut() {
+        let amount = DRAIN_AMOUNT.min(fighter.base_max_hp - MIN_DRAINED_HP);
+        if amount <= 0 {
+            return;
+        }
+        fighter.base_max_hp -= amount;
+        game_state.drained += amount;
+    }
+    let max_hp = player.max_hp(game_state);
+    if let Some(fighter) = player.fighter.as_mut() {
+        fighter.hp = fighter.hp.min(max_hp);
+    }
+    game_state
+        .log
+        .add("You feel your life force drain away!", Severity::Danger);
+}
+
+/// Turn the monster into a champion with one or two random affixes. Its hit points and the
+/// experience it is worth are raised by half and doubled, respectively.
+pub fn make_champion(monster: &mut Object, rng: &mut StdRng) {
+    let first = AFFIXES[rng.gen_range(0, AFFIXES.len())];
+    let second = AFFIXES[rng.gen_range(0, AFFIXES.len())];
+    monster.affixes = if first == second || rng.gen() {
+        vec![first]
+    } else {
+        vec![first, second]
+    };
+
+    if let Some(fighter) = monster.fighter.as_mut() {
+        fighter.base_max_hp += fighter.base_max_hp / 2;
+        fighter.hp = fighter.base_max_hp;
+        fighter.xp *= 2;
+        if monster.affixes.contains(&Affix::Shielded) {
+            fighter.base_defense += SHIELD_DEFENSE;
+        }
+    }
+    let adjectives: Vec<&str> = monster
+        .affixes
+        .iter()
+        .map(|affix| affix.adjective())
+        .collect();
+    monster.name = format!("{} {}", adjectives.join(" "), monster.name);
+    monster.color = CHAMPION_COLOR;
+}
@@ -0,0 +1,165 @@
+use backend::colors::{self, Color};
+/// Module Container
+///
+/// Containers like chests hold items that are handed to the player when they are opened. Bags
+/// are containers that the player carries around to hold more items than fit into the inventory.
+// internal modules
+use backend::Root;
+use entity::equipment::can_let_go;
+use entity::item::{add_to_inventory, can_carry, has_room};
+use entity::object::Object;
+use game_io::{menu, MessageLog, Severity};
+use game_state::GameState;
+
+/// Number of stacks of items that fit into a bag.
+pub const BAG_SIZE: usize = 10;
+// width of the menu showing the contents of a bag
+const BAG_WIDTH: i32 = 50;
+
+// look of closed and opened chests, also used by anything that wants to pass as a chest
+pub const CHEST_CHAR: char = '=';
+pub const CHEST_COLOR: Color = colors::DARK_AMBER;
+const OPEN_CHEST_CHAR: char = '_';
+const OPEN_CHEST_COLOR: Color = colors::DARKER_AMBER;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Container {
+    pub items: Vec<Object>,
+    pub open: bool,
+}
+
+/// Open the container `chest` and move as many of its items into the player's inventory as fit.
+/// Opened containers stay open and can be emptied later.
+pub fn open_container(game_state: &mut GameState, chest: &mut Object) {
+    let name = chest.name.clone();
+    let container = match chest.container.as_mut() {
+        Some(container) => container,
+        None => return,
+    };
+    if !container.open {
+        container.open = true;
+        chest.chr = OPEN_CHEST_CHAR;
+        chest.color = OPEN_CHEST_COLOR;
+        game_state
+            .log
+            .add(format!("You open the {}.", name), Severity::Info);
+    }
+
+    if container.items.is_empty() {
+        game_state
+            .log
+            .add(format!("The {} is empty.", name), Severity::Info);
+    }
+    while !container.items.is_empty() {
+        if !can_carry(game_state, &container.items[0]) {
+            game_state.log.add(
+                format!("Your inventory is full, some items remain in the {}.", name),
+                Severity::Warning,
+            );
+            break;
+        }
+        let item = container.items.remove(0);
+        game_state.log.add(
+            format!("You take a {} from the {}.", item.stack_name(), name),
+            Severity::Good,
+        );
+        add_to_inventory(&mut game_state.inventory, item);
+    }
+}
+
+/// Let the player look into the bag at the given position in its inventory, and take an item
+/// out of it or put one in. Return true if an item was moved.
+pub fn open_bag(root: &mut Root, game_state: &mut GameState, bag_id: usize) -> bool {
+    let bag = &game_state.inventory[bag_id];
+    let contents = match bag.container.as_ref() {
+        Some(container) => &container.items,
+        None => return false,
+    };
+    let header = format!(
+        "The {} holds {} of {} items.\n",
+        bag.name,
+        contents.len(),
+        BAG_SIZE
+    );
+    let mut options: Vec<String> = contents
+        .iter()
+        .map(|item| format!("Take out {}", item.stack_name()))
+        .collect();
+    options.push(format!("Put an item into the {}", bag.name));
+    match menu(&header, &options, BAG_WIDTH, root) {
+        Some(index) if index + 1 < options.len() => take_out_of_bag(game_state, bag_id, index),
+        Some(_) => put_into_bag(root, game_state, bag_id),
+        None => false,
+    }
+}
+
+fn take_out_of_bag(game_state: &mut GameState, bag_id: usize, index: usize) -> bool {
+    let fits = match game_state.inventory[bag_id].container.as_ref() {
+        Some(container) => can_carry(game_state, &container.items[index]),
+        None => return false,
+    };
+    if !fits {
+        game_state
+            .log
+            .add("Your inventory is full.", Severity::Warning);
+        return false;
+    }
+    let item = match game_state.inventory[bag_id].container.as_mut() {
+        Some(container) => container.items.remove(index),
+        None => return false,
+    };
+    let message = format!(
+        "You take the {} out of the {}.",
+        item.stack_name(),
+        game_state.inventory[bag_id].name
+    );
+    game_state.log.add(message, Severity::Info);
+    add_to_inventory(&mut game_state.inventory, item);
+    true
+}
+
+fn put_into_bag(root: &mut Root, game_state: &mut GameState, bag_id: usize) -> bool {
+    let options: Vec<String> = game_state
+        .inventory
+        .iter()
+        .map(|item| item.stack_name())
+        .collect();
+    let index = match menu("Put which item away?\n", &options, BAG_WIDTH, root) {
+        Some(index) => index,
+        None => return false,
+    };
+    let bag_name = game_state.inventory[bag_id].name.clone();
+    let item = &game_state.inventory[index];
+    let fits = game_state.inventory[bag_id]
+        .container
+        .as_ref()
+        .is_some_and(|container| has_room(&container.items, item, BAG_SIZE));
+    if item.container.is_some() {
+        let message = format!("The {} does not fit into the {}.", item.name, bag_name);
+        game_state.log.add(message, Severity::Info);
+        return false;
+    }
+    if !can_let_go(item) {
+        let message = format!("You cannot let go of the cursed {}.", item.name);
+        game_state.log.add(message, Severity::Warning);
+        return false;
+    }
+    if !fits {
+        let message = format!("The {} is full.", bag_name);
+        game_state.log.add(message, Severity::Warning);
+        return false;
+    }
+
+    let mut item = game_state.inventory.remove(index);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = false;
+    }
+    let message = format!("You put the {} into the {}.", item.stack_name(), bag_name);
+    game_state.log.add(message, Severity::Info);
+    // the bag moves up if an item before it was taken out of the inventory
+    let bag_id = if index < bag_id { bag_id - 1 } else { bag_id };
+    if let Some(container) = game_state.inventory[bag_id].container.as_mut() {
+        add_to_inventory(&mut container.items, item);
+    }
+    true
+}
@@ -0,0 +1,49 @@
+/// Module Corpse
+///
+/// Slain monsters leave their corpses behind, which rot away after a while. A corpse remembers
+/// the monster it belonged to, so that it can be eaten, butchered or brought back to life.
+// internal modules
+use backend::colors;
+use entity::object::Object;
+
+// number of turns until a corpse has rotted away
+const DECAY_TURNS: i32 = 200;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Corpse {
+    /// Name of the monster that this corpse belonged to.
+    pub monster: String,
+    // turns until the corpse has rotted away
+    pub decay: i32,
+}
+
+/// Create the corpse of the given monster at its position. Whatever the monster carried stays
+/// with the corpse, to be dropped on the floor.
+pub fn make_corpse(monster: &mut Object) -> Object {
+    let mut corpse = Object::new(
+        monster.x,
+        monster.y,
+        &format!("{} corpse", monster.name),
+        false,
+        '%',
+        colors::DARK_RED,
+    );
+    corpse.corpse = Some(Corpse {
+        monster: monster.name.clone(),
+        decay: DECAY_TURNS,
+    });
+    corpse.inventory = monster.inventory.take();
+    corpse
+}
+
+/// Let all corpses rot a little and remove those that have rotted away. Needs to be called once
+/// per turn.
+pub fn decay_corpses(objects: &mut Vec<Object>) {
+    for corpse in objects
+        .iter_mut()
+        .filter_map(|object| object.corpse.as_mut())
+    {
+        corpse.decay -= 1;
+    }
+    objects.retain(|object| object.corpse.as_ref().is_none_or(|corpse| corpse.decay > 0));
+}
@@ -0,0 +1,231 @@
+/// Module Crafting
+///
+/// Crafting turns materials into potions and improvements of the player's gear. Recipes are
+/// read from a plain-text file in which every recipe names its product, optionally followed by
+/// `at workbench` if it can only be made next to a workbench, and lists its ingredients with
+/// their amounts on indented lines:
+///
+/// ```text
+/// recipe healing potion
+///     2 medicinal herb
+/// recipe weapon upgrade at workbench
+///     3 mineral ore
+///     1 membrane
+/// ```
+///
+/// Products are either items or one of the upgrades `weapon upgrade` and `armor upgrade`, which
+/// improve the equipment the player is wearing instead of producing something new.
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// internal modules
+use backend::Root;
+use entity::item::{add_to_inventory, enchant_armor, enchant_weapon, has_room, take_one};
+use entity::object::Object;
+use game_io::{menu, MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+use world::make_item;
+
+/// File that holds all recipes.
+pub const RECIPE_FILE: &str = "assets/recipes.txt";
+// width of the crafting menu
+const CRAFTING_WIDTH: i32 = 50;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Product {
+    /// An item of the given name.
+    Item(String),
+    /// Improves the weapon that the player wields.
+    WeaponUpgrade,
+    /// Improves a random piece of armor that the player wears.
+    ArmorUpgrade,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recipe {
+    product: Product,
+    ingredients: Vec<(String, i32)>,
+    needs_workbench: bool,
+}
+
+impl Recipe {
+    fn name(&self) -> &str {
+        match self.product {
+            Product::Item(ref name) => name,
+            Product::WeaponUpgrade => "weapon upgrade",
+            Product::ArmorUpgrade => "armor upgrade",
+        }
+    }
+
+    /// Return a description of the recipe for the crafting menu.
+    fn describe(&self) -> String {
+        let ingredients: Vec<String> = self
+            .ingredients
+            .iter()
+            .map(|&(ref name, amount)| format!("{} {}", amount, name))
+            .collect();
+        let workbench = if self.needs_workbench { "*" } else { "" };
+        format!("{}{}: {}", self.name(), workbench, ingredients.join(", "))
+    }
+}
+
+/// Load all recipes from the given file.
+pub fn load_recipes<P: AsRef<Path>>(path: P) -> Result<Vec<Recipe>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let recipes = parse_recipes(&text)
+        .map_err(|e| format!("invalid recipe file {}: {}", path.display(), e))?;
+    Ok(recipes)
+}
+
+fn parse_recipes(text: &str) -> Result<Vec<Recipe>, Box<dyn Error>> {
+    let mut recipes: Vec<Recipe> = vec![];
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(name) = line.strip_prefix("recipe ") {
+            let name = name.trim();
+            let (name, needs_workbench) = if name.ends_with(" at workbench") {
+                (name.trim_end_matches(" at workbench"), true)
+            } else {
+                (name, false)
+            };
+            let product = match name {
+                "weapon upgrade" => Product::WeaponUpgrade,
+                "armor upgrade" => Product::ArmorUpgrade,
+                _ if make_item(name, 0, 0).is_some() => Product::Item(name.to_string()),
+                _ => return Err(format!("unknown product '{}'", name).into()),
+            };
+            recipes.push(Recipe {
+                product,
+                ingredients: vec![],
+                needs_workbench,
+            });
+            continue;
+        }
+
+        let line = line.trim();
+        let (amount, name) = match line.find(' ') {
+            Some(space) => (line[..space].parse::<i32>().ok(), line[space..].trim()),
+            None => (None, line),
+        };
+        let amount = match amount {
+            Some(amount) if amount > 0 => amount,
+            _ => return Err(format!("invalid ingredient '{}'", line).into()),
+        };
+        let recipe = recipes
+            .last_mut()
+            .ok_or_else(|| format!("ingredient '{}' outside of a recipe", line))?;
+        recipe.ingredients.push((name.to_string(), amount));
+    }
+    Ok(recipes)
+}
+
+/// Return how many items of the given name the player carries.
+fn carried(game_state: &GameState, name: &str) -> i32 {
+    game_state
+        .inventory
+        .iter()
+        .filter(|item| item.name == name)
+        .map(|item| item.count)
+        .sum()
+}
+
+fn has_ingredients(game_state: &GameState, recipe: &Recipe) -> bool {
+    recipe
+        .ingredients
+        .iter()
+        .all(|&(ref name, amount)| carried(game_state, name) >= amount)
+}
+
+fn near_workbench(objects: &[Object]) -> bool {
+    let (x, y) = objects[PLAYER].pos();
+    objects
+        .iter()
+        .any(|object| object.name == "workbench" && object.distance(x, y) < 2.0)
+}
+
+/// Let the player choose a recipe and craft it. Return true if something was crafted.
+pub fn craft(root: &mut Root, game_state: &mut GameState, objects: &[Object]) -> bool {
+    let recipes = match load_recipes(RECIPE_FILE) {
+        Ok(recipes) => recipes,
+        Err(e) => {
+            let message = format!("Unable to load recipes: {}", e);
+            game_state.log.add(message, Severity::Warning);
+            return false;
+        }
+    };
+    if recipes.is_empty() {
+        return false;
+    }
+    let options: Vec<String> = recipes.iter().map(|recipe| recipe.describe()).collect();
+    let header = "Choose what to craft (* only at a workbench), or press any other key.\n";
+    let recipe = match menu(header, &options, CRAFTING_WIDTH, root) {
+        Some(index) => &recipes[index],
+        None => return false,
+    };
+
+    if recipe.needs_workbench && !near_workbench(objects) {
+        let message = format!("You need a workbench to make a {}.", recipe.name());
+        game_state.log.add(message, Severity::Info);
+        return false;
+    }
+    if !has_ingredients(game_state, recipe) {
+        let message = format!("You lack the materials for a {}.", recipe.name());
+        game_state.log.add(message, Severity::Info);
+        return false;
+    }
+
+    match recipe.product {
+        Product::Item(ref name) => craft_item(game_state, recipe, name),
+        Product::WeaponUpgrade | Product::ArmorUpgrade => {
+            let upgraded = if recipe.product == Product::WeaponUpgrade {
+                enchant_weapon(game_state)
+            } else {
+                enchant_armor(game_state)
+            };
+            if upgraded {
+                consume(&mut game_state.inventory, recipe);
+            } else {
+                game_state
+                    .log
+                    .add("You have nothing to upgrade.", Severity::Info);
+            }
+            upgraded
+        }
+    }
+}
+
+fn craft_item(game_state: &mut GameState, recipe: &Recipe, name: &str) -> bool {
+    let item = match make_item(name, 0, 0) {
+        Some(item) => item,
+        None => return false,
+    };
+    // using up the ingredients may free a slot for the product
+    let mut inventory = game_state.inventory.clone();
+    consume(&mut inventory, recipe);
+    if !has_room(&inventory, &item, game_state.attributes.carry_capacity()) {
+        game_state
+            .log
+            .add("Your inventory is full.", Severity::Warning);
+        return false;
+    }
+    game_state
+        .log
+        .add(format!("You craft a {}.", item.name), Severity::Good);
+    add_to_inventory(&mut inventory, item);
+    game_state.inventory = inventory;
+    true
+}
+
+/// Remove the ingredients of the recipe from the inventory.
+fn consume(inventory: &mut Vec<Object>, recipe: &Recipe) {
+    for &(ref name, amount) in &recipe.ingredients {
+        for _ in 0..amount {
+            if let Some(index) = inventory.iter().position(|item| item.name == *name) {
+                take_one(inventory, index);
+            }
+        }
+    }
+}
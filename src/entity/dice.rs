@@ -0,0 +1,119 @@
+/// Module Dice
+///
+/// Damage is rolled with dice, written as in tabletop games: `2d4+1` rolls two four-sided dice
+/// and adds one to their sum. Both the number of dice and the bonus may be left out, as in
+/// `d6` or `1d8`.
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Dice {
+    pub count: i32,
+    pub sides: i32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn new(count: i32, sides: i32, bonus: i32) -> Self {
+        Dice {
+            count,
+            sides,
+            bonus,
+        }
+    }
+
+    /// Roll the dice and return their sum plus the bonus.
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> i32 {
+        let sum: i32 = (0..self.count)
+            .map(|_| rng.gen_range(1, self.sides.max(1) + 1))
+            .sum();
+        sum + self.bonus
+    }
+
+    /// Return the sum that the dice roll on average, rounded down.
+    pub fn average(&self) -> i32 {
+        self.count * (self.sides + 1) / 2 + self.bonus
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.bonus != 0 {
+            write!(f, "{:+}", self.bonus)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid dice '{}'", text);
+        // numbers are plain digits, the only sign is the one in front of the bonus
+        let number = |digits: &str| {
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            digits.parse::<i32>().map_err(|_| invalid())
+        };
+        let d = text.find('d').ok_or_else(invalid)?;
+        let count = match &text[..d] {
+            "" => 1,
+            count => number(count)?,
+        };
+        let rest = &text[d + 1..];
+        let (sides, bonus) = match rest.find(['+', '-']) {
+            Some(sign) if rest[sign..].starts_with('-') => {
+                (&rest[..sign], -number(&rest[sign + 1..])?)
+            }
+            Some(sign) => (&rest[..sign], number(&rest[sign + 1..])?),
+            None => (rest, 0),
+        };
+        let sides = number(sides)?;
+        if count > 0 && sides > 0 {
+            Ok(Dice::new(count, sides, bonus))
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_dice() {
+        assert_eq!("d6".parse(), Ok(Dice::new(1, 6, 0)));
+        assert_eq!("2d4+1".parse(), Ok(Dice::new(2, 4, 1)));
+        assert_eq!("1d8-2".parse(), Ok(Dice::new(1, 8, -2)));
+    }
+
+    #[test]
+    fn rejects_invalid_dice() {
+        for text in &[
+            "0d6", "2d", "2d4+-1", "2d4-+1", "+2d4", "2d+4", "2d4+", "d", "six",
+        ] {
+            assert!(text.parse::<Dice>().is_err(), "{} should be invalid", text);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in &["1d6", "2d4+1", "1d8-2", "3d10"] {
+            let dice: Dice = text.parse().unwrap();
+            assert_eq!(dice.to_string(), *text);
+            assert_eq!(dice.to_string().parse(), Ok(dice));
+        }
+    }
+
+    #[test]
+    fn average_rounds_down() {
+        assert_eq!(Dice::new(1, 6, 0).average(), 3);
+        assert_eq!(Dice::new(2, 4, 1).average(), 6);
+        assert_eq!(Dice::new(1, 8, -2).average(), 2);
+    }
+}
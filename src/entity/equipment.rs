@@ -0,0 +1,652 @@
+/// Module Equipment
+///
+/// Equipment is worn or wielded by the player and improves its combat stats while it is.
+/// Monsters that carry items wield the best weapon they come across.
+/// Cursed equipment hinders instead and cannot be taken off again until the curse is lifted.
+/// Weapons and armor wear down in combat, which weakens them until they are repaired.
+/// Enchanting equipment raises its bonuses for good, but the more often it has been enchanted
+/// already, the more likely the magic fails or even curses it.
+use rand::Rng;
+
+// internal modules
+use entity::dice::Dice;
+use entity::object::Object;
+use game_io::{MessageLog, Messages, Severity};
+use game_state::GameState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    LeftHand,
+    RightHand,
+    /// Two-handed weapons take up both hands at once.
+    BothHands,
+    Head,
+    Body,
+    Feet,
+    LeftRing,
+    RightRing,
+    Neck,
+}
+
+impl Slot {
+    pub fn name(self) -> &'static str {
+        match self {
+            Slot::LeftHand => "left hand",
+            Slot::RightHand => "right hand",
+            Slot::BothHands => "both hands",
+            Slot::Head => "head",
+            Slot::Body => "body",
+            Slot::Feet => "feet",
+            Slot::LeftRing => "left ring finger",
+            Slot::RightRing => "right ring finger",
+            Slot::Neck => "neck",
+        }
+    }
+
+    /// Return true if equipment in this slot cannot be worn together with equipment in the
+    /// other one.
+    pub fn overlaps(self, other: Slot) -> bool {
+        match (self, other) {
+            (Slot::BothHands, Slot::LeftHand)
+            | (Slot::BothHands, Slot::RightHand)
+            | (Slot::LeftHand, Slot::BothHands)
+            | (Slot::RightHand, Slot::BothHands) => true,
+            _ => self == other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Beatitude {
+    Uncursed,
+    /// Cannot be taken off once it is worn.
+    Cursed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Element {
+    Fire,
+    /// Slows down whoever it hurts.
+    Frost,
+    /// Poisons whoever it hurts.
+    Poison,
+}
+
+impl Element {
+    /// Return how creatures are described that are hurt by this element.
+    pub fn hurt_adjective(self) -> &'static str {
+        match self {
+            Element::Fire => "burned",
+            Element::Frost => "chilled",
+            Element::Poison => "poisoned",
+        }
+    }
+}
+
+/// An effect that a weapon triggers by chance when it hits.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Proc {
+    /// Sets the target on fire.
+    Burn,
+    /// Freezes the target in place for a few turns.
+    Freeze,
+    /// Heals the attacker by part of the damage dealt.
+    Lifesteal,
+    /// Pushes the target away from the attacker.
+    Knockback,
+}
+
+/// A single bonus of a piece of equipment. Bonuses of the same kind add up, and cursed
+/// equipment has negative ones.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Bonus {
+    Power(i32),
+    Defense(i32),
+    MaxHp(i32),
+    /// Hit points regained every turn.
+    Regeneration(i32),
+    /// Damage of the given element that attacks deal on top, regardless of defense.
+    Elemental(Element, i32),
+    /// Chance in percent that attacks trigger the given effect.
+    Proc(Proc, i32),
+    /// Chance in percent added to that of landing a critical hit.
+    Critical(i32),
+    /// Tiles added to the radius of the player's light.
+    Light(i32),
+    /// Chance in percent that the wearer blocks an attack that would have hit it.
+    Block(i32),
+    /// Chance in percent that the wearer parries an attack that would have hit it.
+    Parry(i32),
+    /// Percentage of the damage of every hit that heals the attacker.
+    Vampiric(i32),
+}
+
+impl Bonus {
+    fn amount(self) -> i32 {
+        match self {
+            Bonus::Power(amount)
+            | Bonus::Defense(amount)
+            | Bonus::MaxHp(amount)
+            | Bonus::Regeneration(amount)
+            | Bonus::Elemental(_, amount)
+            | Bonus::Proc(_, amount)
+            | Bonus::Critical(amount)
+            | Bonus::Light(amount)
+            | Bonus::Block(amount)
+            | Bonus::Parry(amount)
+            | Bonus::Vampiric(amount) => amount,
+        }
+    }
+
+    /// Return how much gold the bonus is worth. Penalties are worth a negative amount.
+    fn value(self) -> i32 {
+        match self {
+            Bonus::Power(amount) => 25 * amount,
+            Bonus::Defense(amount) => 20 * amount,
+            Bonus::MaxHp(amount) => 2 * amount,
+            Bonus::Regeneration(amount) => 40 * amount,
+            Bonus::Elemental(_, amount) => 15 * amount,
+            Bonus::Proc(_, amount) => 2 * amount,
+            Bonus::Critical(amount) => 3 * amount,
+            Bonus::Light(amount) => 10 * amount,
+            Bonus::Block(amount) | Bonus::Parry(amount) => 2 * amount,
+            Bonus::Vampiric(amount) => 3 * amount,
+        }
+    }
+
+    fn with_amount(self, amount: i32) -> Self {
+        match self {
+            Bonus::Power(_) => Bonus::Power(amount),
+            Bonus::Defense(_) => Bonus::Defense(amount),
+            Bonus::MaxHp(_) => Bonus::MaxHp(amount),
+            Bonus::Regeneration(_) => Bonus::Regeneration(amount),
+            Bonus::Elemental(element, _) => Bonus::Elemental(element, amount),
+            Bonus::Proc(effect, _) => Bonus::Proc(effect, amount),
+            Bonus::Critical(_) => Bonus::Critical(amount),
+            Bonus::Light(_) => Bonus::Light(amount),
+            Bonus::Block(_) => Bonus::Block(amount),
+            Bonus::Parry(_) => Bonus::Parry(amount),
+            Bonus::Vampiric(_) => Bonus::Vampiric(amount),
+        }
+    }
+}
+
+/// Durability of equipment in mint condition.
+pub const MAX_DURABILITY: i32 = 100;
+// chance that a weapon wears down with every attack, and armor with every hit taken
+const WEAR_CHANCE: f32 = 0.2;
+// enchantment level up to which enchanting always succeeds
+const SAFE_ENCHANTMENT: i32 = 3;
+// chance of failure for every level above the safe one, and that a failure curses the item
+const FAILURE_PER_LEVEL: f32 = 0.2;
+const CURSE_CHANCE: f32 = 0.5;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Equipment {
+    pub slot: Slot,
+    pub equipped: bool,
+    pub bonuses: Vec<Bonus>,
+    pub beatitude: Beatitude,
+    /// Drops as weapons and armor are used, down to zero.
+    pub durability: i32,
+    /// Number of times the equipment has been enchanted successfully.
+    pub enchantment: i32,
+    /// Dice rolled for the damage of weapons, instead of those of the wielder.
+    pub damage: Option<Dice>,
+}
+
+impl Equipment {
+    pub fn new(slot: Slot, bonuses: Vec<Bonus>) -> Self {
+        Equipment {
+            slot,
+            equipped: false,
+            bonuses,
+            beatitude: Beatitude::Uncursed,
+            durability: MAX_DURABILITY,
+            enchantment: 0,
+            damage: None,
+        }
+    }
+
+    pub fn power_bonus(&self) -> i32 {
+        self.worn(self.total(|bonus| match bonus {
+            Bonus::Power(amount) => amount,
+            _ => 0,
+        }))
+    }
+
+    pub fn defense_bonus(&self) -> i32 {
+        self.worn(self.total(|bonus| match bonus {
+            Bonus::Defense(amount) => amount,
+            _ => 0,
+        }))
+    }
+
+    /// Reduce the given bonus according to the wear of the equipment, by up to half of it once
+    /// the equipment is completely worn down. Penalties stay as they are.
+    fn worn(&self, amount: i32) -> i32 {
+        if amount <= 0 {
+            return amount;
+        }
+        amount - amount * (MAX_DURABILITY - self.durability) / (2 * MAX_DURABILITY)
+    }
+
+    /// Return a description of how worn down the equipment is, if it is noticeably.
+    pub fn condition(&self) -> Option<&'static str> {
+        if self.durability <= MAX_DURABILITY / 4 {
+            Some("badly worn")
+        } else if self.durability <= MAX_DURABILITY / 2 {
+            Some("worn")
+        } else {
+            None
+        }
+    }
+
+    pub fn is_weapon(&self) -> bool {
+        self.slot == Slot::RightHand || self.slot == Slot::BothHands
+    }
+
+    pub fn is_armor(&self) -> bool {
+        matches!(
+            self.slot,
+            Slot::LeftHand | Slot::Head | Slot::Body | Slot::Feet
+        )
+    }
+
+    pub fn max_hp_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::MaxHp(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn critical_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Critical(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn light_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Light(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn block_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Block(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn parry_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Parry(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn vampiric_bonus(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Vampiric(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    pub fn regeneration(&self) -> i32 {
+        self.total(|bonus| match bonus {
+            Bonus::Regeneration(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    /// Return the elemental damage that this equipment adds to attacks.
+    pub fn elemental_damage(&self) -> Vec<(Element, i32)> {
+        self.bonuses
+            .iter()
+            .filter_map(|&bonus| match bonus {
+                Bonus::Elemental(element, amount) => Some((element, amount)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Return the effects that this equipment may trigger on a hit, with their chances in
+    /// percent.
+    pub fn procs(&self) -> Vec<(Proc, i32)> {
+        self.bonuses
+            .iter()
+            .filter_map(|&bonus| match bonus {
+                Bonus::Proc(effect, chance) => Some((effect, chance)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Return how much gold all bonuses together are worth.
+    pub fn value(&self) -> i32 {
+        self.total(Bonus::value)
+    }
+
+    fn total<F: Fn(Bonus) -> i32>(&self, amount: F) -> i32 {
+        self.bonuses.iter().map(|&bonus| amount(bonus)).sum()
+    }
+}
+
+/// Compare the given unequipped item with the equipment worn in its slot and return how the
+/// player's power, defense and maximum hit points would change by swapping them, like
+/// "+2 pow, -1 def". Return `None` if the item is no equipment or is equipped already.
+pub fn compare(inventory: &[Object], item: &Object) -> Option<String> {
+    let equipment = match item.equipment.as_ref() {
+        Some(equipment) if !equipment.equipped => equipment,
+        _ => return None,
+    };
+    // a two-handed weapon replaces whatever is held in either hand
+    let current: Vec<&Equipment> = inventory
+        .iter()
+        .filter_map(|other| other.equipment.as_ref())
+        .filter(|other| other.equipped && other.slot.overlaps(equipment.slot))
+        .collect();
+    let stat = |stat: fn(&Equipment) -> i32| {
+        stat(equipment) - current.iter().map(|&other| stat(other)).sum::<i32>()
+    };
+    let deltas: Vec<String> = [
+        (
+            stat(|other| other.damage.map_or(0, |damage| damage.average())),
+            "dmg",
+        ),
+        (stat(Equipment::power_bonus), "pow"),
+        (stat(Equipment::defense_bonus), "def"),
+        (stat(Equipment::max_hp_bonus), "hp"),
+        (stat(Equipment::block_bonus), "block"),
+        (stat(Equipment::parry_bonus), "parry"),
+        (stat(Equipment::light_bonus), "light"),
+    ]
+    .iter()
+    .filter(|&&(delta, _)| delta != 0)
+    .map(|&(delta, name)| format!("{:+} {}", delta, name))
+    .collect();
+    if deltas.is_empty() {
+        Some("no change".into())
+    } else {
+        Some(deltas.join(", "))
+    }
+}
+
+/// Enchant the given item with the given bonus. Above the safe enchantment level, every level
+/// adds to the chance that the enchantment fails, and a failure may curse the item.
+pub fn enchant(item: &mut Object, bonus: Bonus, log: &mut Messages) {
+    let enchantment = match item.equipment.as_ref() {
+        Some(equipment) => equipment.enchantment,
+        None => return,
+    };
+    let mut rng = rand::thread_rng();
+    let failure = (enchantment - SAFE_ENCHANTMENT) as f32 * FAILURE_PER_LEVEL;
+    if rng.gen::<f32>() < failure {
+        if rng.gen::<f32>() < CURSE_CHANCE {
+            curse(item);
+            let message = format!("Your {} flares black and feels heavy!", item.name);
+            log.add(message, Severity::Warning);
+        } else {
+            let message = format!("Your {} flickers, but the magic fails to hold.", item.name);
+            log.add(message, Severity::Info);
+        }
+        return;
+    }
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.bonuses.push(bonus);
+        equipment.enchantment += 1;
+    }
+    let glow = match bonus {
+        Bonus::Defense(_) => "silver",
+        _ => "blue",
+    };
+    let message = format!("Your {} glows {} for a moment.", item.name, glow);
+    log.add(message, Severity::Good);
+}
+
+/// Curse the equipment of the given item, which turns its bonuses into penalties.
+pub fn curse(item: &mut Object) {
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.beatitude = Beatitude::Cursed;
+        for bonus in equipment.bonuses.iter_mut() {
+            *bonus = bonus.with_amount(-bonus.amount().abs());
+        }
+    }
+}
+
+/// Return false if the item is cursed equipment that the player is wearing and thus cannot
+/// let go of.
+pub fn can_let_go(item: &Object) -> bool {
+    item.equipment
+        .as_ref()
+        .is_none_or(|e| !e.equipped || e.beatitude != Beatitude::Cursed)
+}
+
+/// Let a monster wield the best weapon in its inventory, if it beats the weapon it holds. Cursed
+/// weapons are never let go. Return the name of the newly wielded weapon, if there is one.
+pub fn wield_best_weapon(inventory: &mut [Object]) -> Option<String> {
+    let rating = |item: &Object| {
+        item.equipment
+            .as_ref()
+            .filter(|e| e.is_weapon())
+            .map(|e| e.damage.map_or(0, |dice| dice.average()) + e.power_bonus())
+    };
+    let wielded = inventory.iter().find(|item| {
+        item.equipment
+            .as_ref()
+            .is_some_and(|e| e.equipped && e.is_weapon())
+    });
+    if wielded.is_some_and(|item| !can_let_go(item)) {
+        return None;
+    }
+    let wielded_rating = wielded.and_then(rating);
+    let (best, best_rating) = inventory
+        .iter()
+        .enumerate()
+        .filter_map(|(id, item)| rating(item).map(|rating| (id, rating)))
+        .max_by_key(|&(_, rating)| rating)?;
+    if wielded_rating.is_some_and(|rating| rating >= best_rating) {
+        return None;
+    }
+    for (id, item) in inventory.iter_mut().enumerate() {
+        if let Some(equipment) = item.equipment.as_mut().filter(|e| e.is_weapon()) {
+            equipment.equipped = id == best;
+        }
+    }
+    Some(inventory[best].name.clone())
+}
+
+/// Take off all equipment in the given inventory, e.g. once the monster carrying it has died.
+pub fn unequip_all(inventory: &mut [Object]) {
+    for equipment in inventory
+        .iter_mut()
+        .filter_map(|item| item.equipment.as_mut())
+    {
+        equipment.equipped = false;
+    }
+}
+
+/// Equip the item at the given position in the player's inventory, or take it off if it is
+/// equipped already.
+pub fn toggle_equipment(game_state: &mut GameState, inventory_id: usize) {
+    let (mut slot, equipped) = match game_state.inventory[inventory_id].equipment.as_ref() {
+        Some(equipment) => (equipment.slot, equipment.equipped),
+        None => return,
+    };
+    if equipped {
+        dequip(game_state, inventory_id);
+        return;
+    }
+    let is_free = |slot: Slot| {
+        !game_state.inventory.iter().any(|item| {
+            item.equipment
+                .as_ref()
+                .is_some_and(|e| e.equipped && e.slot.overlaps(slot))
+        })
+    };
+    // rings go onto whichever hand has a finger free
+    let other_ring = match slot {
+        Slot::LeftRing => Some(Slot::RightRing),
+        Slot::RightRing => Some(Slot::LeftRing),
+        _ => None,
+    };
+    if let Some(other_ring) = other_ring {
+        if !is_free(slot) && is_free(other_ring) {
+            slot = other_ring;
+        }
+    }
+    // make room in every slot the item needs first, but only if all of them can be freed
+    let current_ids: Vec<usize> = game_state
+        .inventory
+        .iter()
+        .enumerate()
+        .filter(|&(_, item)| {
+            item.equipment
+                .as_ref()
+                .is_some_and(|e| e.equipped && e.slot.overlaps(slot))
+        })
+        .map(|(id, _)| id)
+        .collect();
+    let cursed = current_ids
+        .iter()
+        .find(|&&id| !can_let_go(&game_state.inventory[id]));
+    if let Some(&cursed_id) = cursed {
+        let message = format!(
+            "You cannot take off the cursed {}.",
+            game_state.inventory[cursed_id].name
+        );
+        game_state.log.add(message, Severity::Warning);
+        return;
+    }
+    for current_id in current_ids {
+        dequip(game_state, current_id);
+    }
+
+    let item = &mut game_state.inventory[inventory_id];
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.slot = slot;
+        equipment.equipped = true;
+        game_state.log.add(
+            format!("Equipped {} on {}.", item.name, equipment.slot.name()),
+            Severity::Info,
+        );
+        if equipment.beatitude == Beatitude::Cursed {
+            game_state.log.add(
+                format!("The {} is cursed! It will not come off again.", item.name),
+                Severity::Warning,
+            );
+        }
+    }
+}
+
+/// Take off the item at the given position in the player's inventory. Return false if it is
+/// cursed and stays where it is.
+fn dequip(game_state: &mut GameState, inventory_id: usize) -> bool {
+    let item = &mut game_state.inventory[inventory_id];
+    if !can_let_go(item) {
+        game_state.log.add(
+            format!("You cannot take off the cursed {}.", item.name),
+            Severity::Warning,
+        );
+        return false;
+    }
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = false;
+        game_state.log.add(
+            format!("Dequipped {} from {}.", item.name, equipment.slot.name()),
+            Severity::Info,
+        );
+    }
+    true
+}
+
+/// Possibly wear down the weapon that the player wields. Needs to be called whenever the player
+/// attacks.
+pub fn wear_weapon(game_state: &mut GameState) {
+    wear(game_state, Equipment::is_weapon);
+}
+
+/// Possibly wear down a random piece of armor that the player wears. Needs to be called
+/// whenever the player is hit.
+pub fn wear_armor(game_state: &mut GameState) {
+    wear(game_state, Equipment::is_armor);
+}
+
+fn wear(game_state: &mut GameState, fits: fn(&Equipment) -> bool) {
+    let mut rng = rand::thread_rng();
+    if rng.gen::<f32>() >= WEAR_CHANCE {
+        return;
+    }
+    let ids: Vec<usize> = game_state
+        .inventory
+        .iter()
+        .enumerate()
+        .filter(|&(_, item)| {
+            item.equipment
+                .as_ref()
+                .is_some_and(|e| e.equipped && e.durability > 0 && fits(e))
+        })
+        .map(|(id, _)| id)
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    let item = &mut game_state.inventory[ids[rng.gen_range(0, ids.len())]];
+    if let Some(equipment) = item.equipment.as_mut() {
+        let before = equipment.condition();
+        equipment.durability -= 1;
+        match equipment.condition() {
+            Some(worse) if Some(worse) != before => game_state.log.add(
+                format!("Your {} is getting {}.", item.name, worse),
+                Severity::Warning,
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Restore all equipment in the inventory that fits the given condition to mint condition.
+/// Return the number of repaired items.
+pub fn repair(inventory: &mut [Object], fits: fn(&Equipment) -> bool) -> usize {
+    let mut repaired = 0;
+    for equipment in inventory
+        .iter_mut()
+        .filter_map(|item| item.equipment.as_mut())
+    {
+        if equipment.durability < MAX_DURABILITY && fits(equipment) {
+            equipment.durability = MAX_DURABILITY;
+            repaired += 1;
+        }
+    }
+    repaired
+}
+
+/// Return the number of durability points that all equipment in the inventory has lost.
+pub fn total_wear(inventory: &[Object]) -> i32 {
+    inventory
+        .iter()
+        .filter_map(|item| item.equipment.as_ref())
+        .map(|equipment| MAX_DURABILITY - equipment.durability)
+        .sum()
+}
+
+/// Lift the curses from all equipment in the inventory. Return the number of lifted curses.
+pub fn remove_curses(inventory: &mut [Object]) -> usize {
+    let cursed = inventory
+        .iter_mut()
+        .filter_map(|item| item.equipment.as_mut())
+        .filter(|equipment| equipment.beatitude == Beatitude::Cursed);
+    let mut lifted = 0;
+    for equipment in cursed {
+        // the bonuses of cursed equipment have been turned into penalties
+        equipment.beatitude = Beatitude::Uncursed;
+        for bonus in equipment.bonuses.iter_mut() {
+            *bonus = bonus.with_amount(bonus.amount().abs());
+        }
+        lifted += 1;
+    }
+    lifted
+}
@@ -0,0 +1,110 @@
+/// Module Experience
+///
+/// How much experience the player needs to level up, and how much more monsters are worth the
+/// deeper they are met, is read from a plain-text file with one setting per line:
+///
+/// ```text
+/// level_up_base 200
+/// level_up_factor 150
+/// level_up_growth 0
+/// depth_bonus 10
+/// ```
+///
+/// Reaching the next level takes `level_up_base + level_up_factor * level + level_up_growth *
+/// level * level` experience. Every level of depth adds `depth_bonus` percent to the experience
+/// that a monster is worth. Settings that are left out keep their default values.
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// File that holds the experience settings.
+pub const EXPERIENCE_FILE: &str = "assets/experience.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct XpCurve {
+    pub level_up_base: i32,
+    pub level_up_factor: i32,
+    pub level_up_growth: i32,
+    /// Percent of experience that monsters are worth more for every level of depth.
+    pub depth_bonus: i32,
+}
+
+impl Default for XpCurve {
+    fn default() -> Self {
+        XpCurve {
+            level_up_base: 200,
+            level_up_factor: 150,
+            level_up_growth: 0,
+            depth_bonus: 10,
+        }
+    }
+}
+
+thread_local! {
+    // the experience settings, loaded once at startup
+    static CURVE: RefCell<Option<XpCurve>> = const { RefCell::new(None) };
+}
+
+/// Load the experience settings from the experience file, unless they have been loaded already.
+pub fn cache_xp_curve() {
+    CURVE.with(|curve| {
+        if curve.borrow().is_some() {
+            return;
+        }
+        let loaded = load_xp_curve(EXPERIENCE_FILE).unwrap_or_else(|e| {
+            eprintln!("unable to load experience settings: {}", e);
+            XpCurve::default()
+        });
+        *curve.borrow_mut() = Some(loaded);
+    });
+}
+
+fn xp_curve() -> XpCurve {
+    cache_xp_curve();
+    CURVE.with(|curve| curve.borrow().unwrap())
+}
+
+/// Return the experience that the player needs to advance from the given level to the next.
+pub fn level_up_xp(level: i32) -> i32 {
+    let curve = xp_curve();
+    curve.level_up_base + curve.level_up_factor * level + curve.level_up_growth * level * level
+}
+
+/// Return the experience that a monster of the given base experience is worth at the given
+/// depth.
+pub fn monster_xp(xp: i32, depth: u32) -> i32 {
+    xp * (100 + xp_curve().depth_bonus * depth as i32) / 100
+}
+
+/// Load the experience settings from the given file.
+pub fn load_xp_curve<P: AsRef<Path>>(path: P) -> Result<XpCurve, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let curve = parse_xp_curve(&text)
+        .map_err(|e| format!("invalid experience file {}: {}", path.display(), e))?;
+    Ok(curve)
+}
+
+fn parse_xp_curve(text: &str) -> Result<XpCurve, Box<dyn Error>> {
+    let mut curve = XpCurve::default();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let value = match words.as_slice() {
+            [_, value] => value
+                .parse::<i32>()
+                .map_err(|_| format!("invalid value in '{}'", line))?,
+            _ => return Err(format!("invalid setting '{}'", line).into()),
+        };
+        match words[0] {
+            "level_up_base" => curve.level_up_base = value,
+            "level_up_factor" => curve.level_up_factor = value,
+            "level_up_growth" => curve.level_up_growth = value,
+            "depth_bonus" => curve.depth_bonus = value,
+            name => return Err(format!("unknown setting '{}'", name).into()),
+        }
+    }
+    Ok(curve)
+}
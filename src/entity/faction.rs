@@ -0,0 +1,35 @@
+/// Module Faction
+///
+/// Every creature belongs to a faction. Members of hostile factions attack each other on
+/// sight, so monsters of different kinds may end up fighting among themselves.
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Faction {
+    Player,
+    /// Creatures that fight on the player's side, like its pet.
+    Allies,
+    Viruses,
+    Bacteria,
+    Protists,
+    Fungi,
+    Prions,
+    /// Peaceful creatures that nobody attacks, like the inhabitants of the town.
+    Neutral,
+}
+
+impl Faction {
+    /// Return true if members of this faction attack members of the other one.
+    pub fn is_hostile_to(self, other: Faction) -> bool {
+        use self::Faction::*;
+        match (self, other) {
+            (Neutral, _) | (_, Neutral) => false,
+            (Player, Player) | (Player, Allies) | (Allies, Player) | (Allies, Allies) => false,
+            (Player, _) | (_, Player) | (Allies, _) | (_, Allies) => true,
+            // bacteria are preyed upon by viruses and protists and fought by fungi
+            (Viruses, Bacteria) | (Bacteria, Viruses) => true,
+            (Protists, Bacteria) | (Bacteria, Protists) => true,
+            (Fungi, Bacteria) | (Bacteria, Fungi) => true,
+            _ => false,
+        }
+    }
+}
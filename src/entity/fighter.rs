@@ -1,11 +1,14 @@
 /// Module Fighter
 ///
 /// This module contains the structures and methods that make up the combat system.
-use tcod::colors;
-
 // internal modules
+use backend::colors;
+use entity::corpse::make_corpse;
+use entity::dice::Dice;
+use entity::equipment::unequip_all;
 use entity::object::Object;
-use game_io::{MessageLog, Messages};
+use game_io::{MessageLog, Messages, Severity};
+use world::drops::roll_drops;
 
 // combat related poperties and methods (monster, player, NPC)
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -13,7 +16,10 @@ pub struct Fighter {
     pub hp: i32,
     pub base_max_hp: i32,
     pub base_defense: i32,
+    /// Added to the damage rolled with the dice.
     pub base_power: i32,
+    /// Dice rolled for the damage of attacks without a weapon.
+    pub damage: Dice,
     pub on_death: DeathCallback,
     pub xp: i32,
 }
@@ -22,6 +28,8 @@ pub struct Fighter {
 pub enum DeathCallback {
     Player,
     Monster,
+    Boss,
+    Nest,
 }
 
 impl DeathCallback {
@@ -30,6 +38,8 @@ impl DeathCallback {
         let callback: fn(&mut Object, &mut Messages) = match self {
             Player => player_death,
             Monster => monster_death,
+            Boss => boss_death,
+            Nest => nest_death,
         };
         callback(object, messages);
     }
@@ -37,7 +47,7 @@ impl DeathCallback {
 
 pub fn player_death(player: &mut Object, messages: &mut Messages) {
     // the game ended!
-    messages.add("You died!", colors::RED);
+    messages.add("You died!", Severity::Danger);
 
     // for added effect, transform the player into a corpse
     // TODO: move color and character change to module game_io or object!
@@ -46,18 +56,32 @@ pub fn player_death(player: &mut Object, messages: &mut Messages) {
 }
 
 pub fn monster_death(monster: &mut Object, messages: &mut Messages) {
+    messages.add(format!("{} is dead!", monster.name), Severity::Combat);
+    let dropped = match monster.drops {
+        Some(ref table) => roll_drops(table, monster.x, monster.y),
+        None => vec![],
+    };
+    // the corpse keeps the inventory and leaves the drops on its tile
+    let inventory = monster.inventory.get_or_insert_with(Vec::new);
+    unequip_all(inventory);
+    inventory.extend(dropped);
+    *monster = make_corpse(monster);
+}
+
+pub fn nest_death(nest: &mut Object, messages: &mut Messages) {
+    messages.add(format!("The {} is destroyed!", nest.name), Severity::Combat);
+    nest.chr = '%';
+    nest.color = colors::DARKER_ORANGE;
+    nest.blocks = false;
+    nest.fighter = None;
+    nest.spawner = None;
+    nest.name = format!("remains of {}", nest.name);
+}
+
+pub fn boss_death(boss: &mut Object, messages: &mut Messages) {
+    monster_death(boss, messages);
     messages.add(
-        format!(
-            "{} is dead! You gain {} XP",
-            monster.name,
-            monster.fighter.unwrap().xp
-        ),
-        colors::ORANGE,
+        "With a rumble, the stairs down are unsealed.",
+        Severity::Good,
     );
-    monster.chr = '%';
-    monster.color = colors::DARK_RED;
-    monster.blocks = false;
-    monster.fighter = None;
-    monster.ai = None;
-    monster.name = format!("remains of {}", monster.name);
 }
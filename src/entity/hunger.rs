@@ -0,0 +1,100 @@
+/// Module Hunger
+///
+/// The player grows hungry over time and has to eat, either food rations or the corpses of
+/// slain monsters. Weak players hit less hard and starving ones lose health every turn.
+// internal modules
+use backend::colors::{self, Color};
+use entity::object::Object;
+use game_io::{MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+
+/// The most nutrition the player can have, which is also the amount it starts out with.
+pub const MAX_NUTRITION: i32 = 2000;
+// nutrition below which the player is hungry and weak, respectively
+const HUNGRY_NUTRITION: i32 = 300;
+const WEAK_NUTRITION: i32 = 100;
+/// How much weaker a player hits that is weak with hunger.
+pub const WEAK_POWER_PENALTY: i32 = 2;
+// nutrition gained from eating a corpse
+const CORPSE_NUTRITION: i32 = 300;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hunger {
+    Satiated,
+    Hungry,
+    Weak,
+    Starving,
+}
+
+impl Hunger {
+    /// Return how hungry a player with the given nutrition is.
+    pub fn from_nutrition(nutrition: i32) -> Self {
+        if nutrition <= 0 {
+            Hunger::Starving
+        } else if nutrition < WEAK_NUTRITION {
+            Hunger::Weak
+        } else if nutrition < HUNGRY_NUTRITION {
+            Hunger::Hungry
+        } else {
+            Hunger::Satiated
+        }
+    }
+
+    /// Return the name and color in which this state is shown to the player, if at all.
+    pub fn indicator(self) -> Option<(&'static str, Color)> {
+        match self {
+            Hunger::Satiated => None,
+            Hunger::Hungry => Some(("Hungry", colors::YELLOW)),
+            Hunger::Weak => Some(("Weak", colors::ORANGE)),
+            Hunger::Starving => Some(("Starving", colors::RED)),
+        }
+    }
+}
+
+/// Let the player grow a little hungrier, and starve if there is nothing left to digest. Needs
+/// to be called once per turn.
+pub fn update_hunger(game_state: &mut GameState, player: &mut Object) {
+    let before = Hunger::from_nutrition(game_state.nutrition);
+    game_state.nutrition = (game_state.nutrition - 1).max(0);
+    let after = Hunger::from_nutrition(game_state.nutrition);
+    if after != before {
+        let message = match after {
+            Hunger::Hungry => "You are getting hungry.",
+            Hunger::Weak => "You feel weak with hunger.",
+            _ => "You are starving!",
+        };
+        game_state.log.add(message, Severity::Warning);
+    }
+    if after == Hunger::Starving {
+        player.take_damage(1, game_state);
+    }
+}
+
+/// Let the player eat something with the given nutrition.
+pub fn eat(game_state: &mut GameState, nutrition: i32) {
+    game_state.nutrition = (game_state.nutrition + nutrition).min(MAX_NUTRITION);
+}
+
+/// Let the player eat a corpse lying under it. Return true if there was one.
+pub fn eat_corpse(game_state: &mut GameState, objects: &mut Vec<Object>) -> bool {
+    let player_pos = objects[PLAYER].pos();
+    let corpse_id = objects
+        .iter()
+        .position(|object| object.corpse.is_some() && object.pos() == player_pos);
+    match corpse_id {
+        Some(corpse_id) => {
+            let corpse = objects.remove(corpse_id);
+            game_state
+                .log
+                .add(format!("You eat the {}.", corpse.name), Severity::Good);
+            eat(game_state, CORPSE_NUTRITION);
+            true
+        }
+        None => {
+            game_state
+                .log
+                .add("There is nothing here to eat.", Severity::Info);
+            false
+        }
+    }
+}
@@ -0,0 +1,858 @@
+/// Module Item
+///
+/// Items are objects that can be picked up and carried around in the player's inventory.
+use rand::Rng;
+
+// internal modules
+use entity::ai::Ai;
+use entity::equipment::{
+    can_let_go, enchant, remove_curses, repair, toggle_equipment, Bonus, Equipment,
+};
+use entity::faction::Faction;
+use entity::hunger::eat;
+use entity::npc::provoke;
+use entity::object::{MoveMode, Object};
+use entity::polymorph::{lasting_form, polymorph};
+use entity::spell::hit_area;
+use entity::status::{apply_status, Effect};
+use game_io::{MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+use world::area::burst;
+use world::noise::{Noise, COMBAT_NOISE};
+use world::{is_blocked_for, WORLD_HEIGHT, WORLD_WIDTH};
+
+/// Number of slots in the player's inventory.
+pub const INVENTORY_SIZE: usize = 26;
+/// Maximum distance at which the player can throw items.
+pub const THROW_RANGE: f32 = 8.0;
+/// Maximum distance at which the player can fire arrows.
+pub const BOW_RANGE: f32 = 10.0;
+// damage that thrown daggers and arrows deal, before the defense of the creature they hit
+const DAGGER_DAMAGE: i32 = 6;
+const ARROW_DAMAGE: i32 = 8;
+// chance that a fired arrow stays intact and can be picked up again
+const ARROW_RECOVERY_CHANCE: f32 = 0.5;
+// amount of hit points a healing potion restores
+pub const HEAL_AMOUNT: i32 = 40;
+// nutrition gained from eating a food ration
+const RATION_NUTRITION: i32 = 800;
+// number of turns during which a potion of true sight lets the player see invisible creatures
+const TRUE_SIGHT_TURNS: i32 = 50;
+// number of turns that the other potions last
+const SPEED_TURNS: i32 = 20;
+const POISON_TURNS: i32 = 10;
+const BLINDNESS_TURNS: i32 = 30;
+const PARALYSIS_TURNS: i32 = 5;
+const CONFUSION_TURNS: i32 = 8;
+/// Maximum distance at which a fireball can be aimed.
+pub const FIREBALL_RANGE: f32 = 8.0;
+// radius of a fireball, the damage it deals and how long it keeps burning
+const FIREBALL_RADIUS: i32 = 2;
+const FIREBALL_DAMAGE: i32 = 12;
+const FIREBALL_BURN_TURNS: i32 = 3;
+const REGENERATION_TURNS: i32 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Item {
+    /// Unlocks a single locked door and is used up in the process.
+    Key,
+    /// Restores some health of the player, or of monsters that drink it.
+    Heal,
+    /// Digs through walls, one stroke per turn.
+    Pickaxe,
+    /// Lets the player see invisible creatures for a while.
+    SeeInvisible,
+    /// Permanently increases the power of whoever drinks it.
+    Strength,
+    /// Lets the player act twice per turn for a while.
+    Speed,
+    Poison,
+    /// Makes the player blind for a while.
+    Blindness,
+    /// Makes whoever drinks it unable to move for a few turns.
+    Paralysis,
+    /// Makes whoever drinks it stagger about for a while.
+    Confusion,
+    /// Lets whoever drinks it regain hit points every turn for a while.
+    Regeneration,
+    /// Gives the player back the maximum hit points that have been drained from it.
+    Restoration,
+    /// Turns whoever drinks it into another creature for a while.
+    Polymorph,
+    /// Is worn or wielded, as described by the equipment of the object.
+    Equipment,
+    /// A weapon that can also be thrown at enemies.
+    Dagger,
+    /// A weapon that fires arrows from afar.
+    Bow,
+    Arrow,
+    /// Lifts the curses from all equipment the player carries.
+    RemoveCurse,
+    /// Moves the player to a random spot on the level.
+    Teleport,
+    /// Reveals the layout of the level.
+    MagicMapping,
+    /// Improves the weapon that the player wields.
+    EnchantWeapon,
+    /// Improves a random piece of armor that the player wears.
+    EnchantArmor,
+    /// Explodes in a ball of fire wherever the player aims it.
+    Fireball,
+    /// Stills the player's hunger.
+    Food,
+    /// Is combined with other materials into something useful.
+    Material,
+    /// Goes into the player's purse instead of the inventory.
+    Gold,
+    /// Holds other items, as described by the container of the object.
+    Bag,
+    /// Restores worn weapons.
+    Whetstone,
+    /// Restores worn armor.
+    RepairKit,
+}
+
+enum UseResult {
+    UsedUp,
+    Cancelled,
+}
+
+/// Use the item at the given position in the player's inventory. Items that are used up are
+/// removed from it.
+pub fn use_item(inventory_id: usize, game_state: &mut GameState, objects: &mut [Object]) {
+    let on_use: fn(&mut GameState, &mut [Object]) -> UseResult =
+        match game_state.inventory[inventory_id].item {
+            Some(Item::Heal) => cast_heal,
+            Some(Item::Key) => use_key,
+            Some(Item::Pickaxe) => use_pickaxe,
+            Some(Item::Arrow) => use_arrow,
+            Some(Item::Food) => eat_ration,
+            Some(Item::Material) => use_material,
+            Some(Item::Whetstone) => use_whetstone,
+            Some(Item::RepairKit) => use_repair_kit,
+            Some(Item::SeeInvisible) => drink_true_sight,
+            Some(Item::Strength) => drink_strength,
+            Some(Item::Speed) => drink_speed,
+            Some(Item::Poison) => drink_poison,
+            Some(Item::Blindness) => drink_blindness,
+            Some(Item::Paralysis) => drink_paralysis,
+            Some(Item::Confusion) => drink_confusion,
+            Some(Item::Regeneration) => drink_regeneration,
+            Some(Item::Restoration) => drink_restoration,
+            Some(Item::Polymorph) => drink_polymorph,
+            Some(Item::RemoveCurse) => read_remove_curse,
+            Some(Item::Teleport) => read_teleportation,
+            Some(Item::MagicMapping) => read_magic_mapping,
+            Some(Item::EnchantWeapon) => read_enchant_weapon,
+            Some(Item::EnchantArmor) => read_enchant_armor,
+            Some(Item::Equipment) | Some(Item::Dagger) | Some(Item::Bow) => {
+                toggle_equipment(game_state, inventory_id);
+                // taking off equipment may leave the player with more hit points than the maximum
+                let max_hp = objects[PLAYER].max_hp(game_state);
+                if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+                    fighter.hp = fighter.hp.min(max_hp);
+                }
+                return;
+            }
+            Some(Item::Gold) | Some(Item::Bag) | Some(Item::Fireball) | None => {
+                let message = format!(
+                    "The {} cannot be used.",
+                    game_state.inventory[inventory_id].name
+                );
+                game_state.log.add(message, Severity::Info);
+                return;
+            }
+        };
+    match on_use(game_state, objects) {
+        UseResult::UsedUp => {
+            take_one(&mut game_state.inventory, inventory_id);
+        }
+        UseResult::Cancelled => {
+            game_state.log.add("Cancelled", Severity::Info);
+        }
+    }
+}
+
+/// Return how much gold a single one of the given items is worth. Equipment is worth more the
+/// better its bonuses are.
+pub fn item_value(item: &Object) -> i32 {
+    let base = match item.item {
+        Some(Item::Key) => 20,
+        Some(Item::Heal) => 30,
+        Some(Item::Pickaxe) => 60,
+        Some(Item::SeeInvisible) => 40,
+        Some(Item::Strength) => 150,
+        Some(Item::Speed) => 50,
+        Some(Item::Poison)
+        | Some(Item::Blindness)
+        | Some(Item::Paralysis)
+        | Some(Item::Confusion) => 15,
+        Some(Item::Regeneration) => 40,
+        Some(Item::Restoration) => 60,
+        Some(Item::Polymorph) => 30,
+        Some(Item::Equipment) => 30,
+        Some(Item::Dagger) => 25,
+        Some(Item::Bow) => 50,
+        Some(Item::Arrow) => 2,
+        Some(Item::RemoveCurse) => 60,
+        Some(Item::Teleport) => 40,
+        Some(Item::MagicMapping) => 50,
+        Some(Item::EnchantWeapon) | Some(Item::EnchantArmor) => 80,
+        Some(Item::Fireball) => 60,
+        Some(Item::Food) => 15,
+        Some(Item::Material) => 5,
+        Some(Item::Gold) => 1,
+        Some(Item::Bag) => 40,
+        Some(Item::Whetstone) => 30,
+        Some(Item::RepairKit) => 50,
+        None => 0,
+    };
+    let bonuses = item.equipment.as_ref().map_or(0, |e| e.value());
+    let contents: i32 = item.container.as_ref().map_or(0, |container| {
+        container
+            .items
+            .iter()
+            .map(|item| item_value(item) * item.count)
+            .sum()
+    });
+    (base + bonuses + contents).max(1)
+}
+
+/// Return true if both items are alike and can be put onto the same stack.
+fn stacks_with(item: &Object, other: &Object) -> bool {
+    item.item.is_some()
+        && item.equipment.is_none()
+        && item.container.is_none()
+        && item.item == other.item
+        && item.name == other.name
+}
+
+/// Return true if the item can be put into the player's inventory, either onto a stack of
+/// equal items or into a free slot. The stronger the player, the more it can carry.
+pub fn can_carry(game_state: &GameState, item: &Object) -> bool {
+    has_room(
+        &game_state.inventory,
+        item,
+        game_state.attributes.carry_capacity(),
+    )
+}
+
+/// Return true if the item can be put into a list of items that holds at most `capacity` stacks.
+pub fn has_room(items: &[Object], item: &Object, capacity: usize) -> bool {
+    items.len() < capacity || items.iter().any(|other| stacks_with(item, other))
+}
+
+/// Put the item into the inventory, onto a stack of equal items if there is one.
+pub fn add_to_inventory(inventory: &mut Vec<Object>, item: Object) {
+    match inventory.iter_mut().find(|other| stacks_with(&item, other)) {
+        Some(stack) => stack.count += item.count,
+        None => inventory.push(item),
+    }
+}
+
+/// Take a single item off the stack at the given position in the inventory. The stack is
+/// removed once it is empty.
+pub fn take_one(inventory: &mut Vec<Object>, inventory_id: usize) -> Object {
+    if inventory[inventory_id].count > 1 {
+        inventory[inventory_id].count -= 1;
+        let mut item = inventory[inventory_id].clone();
+        item.count = 1;
+        item
+    } else {
+        inventory.remove(inventory_id)
+    }
+}
+
+/// Put the item onto the floor at its position, onto a pile of equal items if there is one.
+pub fn drop_onto_floor(objects: &mut Vec<Object>, item: Object) {
+    let pile = objects
+        .iter_mut()
+        .find(|other| other.pos() == item.pos() && stacks_with(&item, other));
+    match pile {
+        Some(pile) => pile.count += item.count,
+        None => objects.push(item),
+    }
+}
+
+/// Drop the whole stack at the given position in the player's inventory under the player.
+pub fn drop_item(inventory_id: usize, game_state: &mut GameState, objects: &mut Vec<Object>) {
+    if !can_let_go(&game_state.inventory[inventory_id]) {
+        let message = format!(
+            "You cannot let go of the cursed {}.",
+            game_state.inventory[inventory_id].name
+        );
+        game_state.log.add(message, Severity::Warning);
+        return;
+    }
+    let mut item = game_state.inventory.remove(inventory_id);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = false;
+    }
+    let (x, y) = objects[PLAYER].pos();
+    item.set_pos(x, y);
+    game_state.log.add(
+        format!("You dropped a {}.", item.stack_name()),
+        Severity::Info,
+    );
+    drop_onto_floor(objects, item);
+}
+
+/// Throw a single item of the stack at the given position in the player's inventory along the
+/// path of a projectile. Potions shatter and affect the creature they hit, daggers hurt it and
+/// anything else just lands at the end of the path.
+pub fn throw_item(
+    inventory_id: usize,
+    game_state: &mut GameState,
+    objects: &mut Vec<Object>,
+    path: &[(i32, i32)],
+) {
+    if path.is_empty() {
+        return;
+    }
+    if !can_let_go(&game_state.inventory[inventory_id]) {
+        let message = format!(
+            "You cannot let go of the cursed {}.",
+            game_state.inventory[inventory_id].name
+        );
+        game_state.log.add(message, Severity::Warning);
+        return;
+    }
+    let mut item = take_one(&mut game_state.inventory, inventory_id);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = false;
+    }
+    let (target_id, (x, y)) = projectile_end(game_state, objects, path);
+
+    match (item.item, target_id) {
+        (potion, _) if is_potion(potion) => {
+            game_state
+                .log
+                .add(format!("The {} shatters.", item.name), Severity::Info);
+            if let Some(target_id) = target_id {
+                shatter_potion(game_state, &mut objects[target_id], item.item);
+            }
+            return;
+        }
+        (Some(Item::Dagger), Some(target_id)) => {
+            hit_with_projectile(game_state, objects, target_id, "dagger", DAGGER_DAMAGE);
+        }
+        _ => {}
+    }
+    item.set_pos(x, y);
+    drop_onto_floor(objects, item);
+}
+
+/// Return true if the item is a potion, which shatters when it is thrown.
+fn is_potion(item: Option<Item>) -> bool {
+    matches!(
+        item,
+        Some(Item::Heal)
+            | Some(Item::SeeInvisible)
+            | Some(Item::Strength)
+            | Some(Item::Speed)
+            | Some(Item::Poison)
+            | Some(Item::Blindness)
+            | Some(Item::Paralysis)
+            | Some(Item::Confusion)
+            | Some(Item::Regeneration)
+            | Some(Item::Restoration)
+            | Some(Item::Polymorph)
+    )
+}
+
+/// Let a thrown potion take effect on the creature that it hit.
+fn shatter_potion(game_state: &mut GameState, target: &mut Object, potion: Option<Item>) {
+    let name = target.visible_name(game_state);
+    match potion {
+        Some(Item::Speed) => {
+            target.haste = SPEED_TURNS;
+            game_state
+                .log
+                .add(format!("The {} speeds up!", name), Severity::Info);
+        }
+        Some(Item::Poison) => {
+            apply_status(target, Effect::Poisoned, POISON_TURNS);
+            game_state
+                .log
+                .add(format!("The {} is poisoned!", name), Severity::Info);
+        }
+        Some(Item::Paralysis) => {
+            if apply_status(target, Effect::Stunned, PARALYSIS_TURNS) {
+                game_state
+                    .log
+                    .add(format!("The {} is paralyzed!", name), Severity::Info);
+            } else {
+                game_state.log.add(
+                    format!("The {} shrugs off the vapors.", name),
+                    Severity::Info,
+                );
+            }
+        }
+        Some(Item::Confusion) => {
+            apply_status(target, Effect::Confused, CONFUSION_TURNS);
+            game_state
+                .log
+                .add(format!("The {} looks confused!", name), Severity::Info);
+        }
+        Some(Item::Regeneration) => {
+            apply_status(target, Effect::Regenerating, REGENERATION_TURNS);
+            game_state
+                .log
+                .add(format!("The {} looks healthier!", name), Severity::Info);
+        }
+        Some(Item::Polymorph) => {
+            polymorph(game_state, target, true);
+        }
+        Some(Item::Heal) => {
+            target.heal(game_state, HEAL_AMOUNT);
+            game_state.log.add(
+                format!("The {} looks healthier.", target.visible_name(game_state)),
+                Severity::Info,
+            );
+        }
+        Some(Item::SeeInvisible) if target.invisible => {
+            target.invisible = false;
+            game_state.log.add(
+                format!("The potion drenches the {} and reveals it!", target.name),
+                Severity::Info,
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Return the creature that a projectile on the given path hits, if any, and the spot where the
+/// projectile comes down. Projectiles that hit a wall fall down in front of it.
+fn projectile_end(
+    game_state: &GameState,
+    objects: &[Object],
+    path: &[(i32, i32)],
+) -> (Option<usize>, (i32, i32)) {
+    let (x, y) = path[path.len() - 1];
+    let target_id = objects
+        .iter()
+        .position(|object| object.pos() == (x, y) && object.alive && object.fighter.is_some());
+    let spot = if !game_state.world.get(x, y).blocked {
+        (x, y)
+    } else if path.len() > 1 {
+        path[path.len() - 2]
+    } else {
+        objects[PLAYER].pos()
+    };
+    (target_id, spot)
+}
+
+/// Let a projectile of the player hurt the creature that it hit.
+fn hit_with_projectile(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    target_id: usize,
+    projectile: &str,
+    damage: i32,
+) {
+    if objects[target_id].faction == Faction::Neutral {
+        provoke(game_state, &mut objects[target_id]);
+    }
+    let damage = damage - objects[target_id].defense(game_state);
+    let name = objects[target_id].visible_name(game_state);
+    if damage <= 0 {
+        game_state.log.add(
+            format!("The {} bounces off the {}.", projectile, name),
+            Severity::Info,
+        );
+        return;
+    }
+    game_state.log.add(
+        format!(
+            "The {} hits the {} for {} hit points.",
+            projectile, name, damage
+        ),
+        Severity::Info,
+    );
+    if let Some(xp) = objects[target_id].take_damage(damage, game_state) {
+        if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+            fighter.xp += xp;
+        }
+        game_state
+            .log
+            .add(format!("You gain {} XP.", xp), Severity::Good);
+    }
+}
+
+/// Return the position of the arrows in the player's inventory if the player has a bow ready
+/// to fire them.
+pub fn ready_arrow(game_state: &mut GameState) -> Option<usize> {
+    let has_bow = game_state.inventory.iter().any(|item| {
+        item.item == Some(Item::Bow) && item.equipment.as_ref().is_some_and(|e| e.equipped)
+    });
+    if !has_bow {
+        game_state
+            .log
+            .add("You need to wield a bow to fire arrows.", Severity::Info);
+        return None;
+    }
+    let arrow_id = game_state
+        .inventory
+        .iter()
+        .position(|item| item.item == Some(Item::Arrow));
+    if arrow_id.is_none() {
+        game_state.log.add("Your quiver is empty.", Severity::Info);
+    }
+    arrow_id
+}
+
+/// Fire a single arrow from the stack at the given position in the player's inventory along
+/// the path of a projectile. Arrows that survive the shot can be picked up again where they
+/// came down.
+pub fn fire_arrow(
+    arrow_id: usize,
+    game_state: &mut GameState,
+    objects: &mut Vec<Object>,
+    path: &[(i32, i32)],
+) {
+    if path.is_empty() {
+        return;
+    }
+    let mut arrow = take_one(&mut game_state.inventory, arrow_id);
+    let (target_id, (x, y)) = projectile_end(game_state, objects, path);
+    if let Some(target_id) = target_id {
+        hit_with_projectile(game_state, objects, target_id, "arrow", ARROW_DAMAGE);
+    }
+    if rand::random::<f32>() < ARROW_RECOVERY_CHANCE {
+        arrow.set_pos(x, y);
+        drop_onto_floor(objects, arrow);
+    }
+}
+
+/// Heal the player or, if the player is unhurt, a hurt companion next to it.
+fn cast_heal(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    let companion_id = objects.iter().position(|object| {
+        object.ai == Some(Ai::Companion)
+            && object.distance_to(&objects[PLAYER]) < 2.0
+            && object
+                .fighter
+                .is_some_and(|f| f.hp < object.max_hp(game_state))
+    });
+    let player = &mut objects[PLAYER];
+    if let Some(fighter) = player.fighter {
+        if fighter.hp == player.max_hp(game_state) {
+            if let Some(companion_id) = companion_id {
+                let companion = &mut objects[companion_id];
+                game_state.log.add(
+                    format!("Your {} looks much better!", companion.name),
+                    Severity::Good,
+                );
+                companion.heal(game_state, HEAL_AMOUNT);
+                return UseResult::UsedUp;
+            }
+            game_state
+                .log
+                .add("You are already at full health.", Severity::Info);
+            return UseResult::Cancelled;
+        }
+        game_state
+            .log
+            .add("Your wounds start to feel better!", Severity::Good);
+        player.heal(game_state, HEAL_AMOUNT);
+        return UseResult::UsedUp;
+    }
+    UseResult::Cancelled
+}
+
+fn eat_ration(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    eat(game_state, RATION_NUTRITION);
+    game_state
+        .log
+        .add("That food really hit the spot!", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn drink_strength(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    if let Some(fighter) = lasting_form(&mut objects[PLAYER]).0 {
+        fighter.base_power += 1;
+    }
+    game_state.log.add("You feel strong!", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn drink_speed(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state.speed = SPEED_TURNS;
+    game_state
+        .log
+        .add("You are suddenly moving faster.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn drink_poison(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    apply_status(&mut objects[PLAYER], Effect::Poisoned, POISON_TURNS);
+    game_state
+        .log
+        .add("This burns! You have been poisoned.", Severity::Warning);
+    UseResult::UsedUp
+}
+
+fn drink_blindness(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state.blindness = BLINDNESS_TURNS;
+    game_state
+        .log
+        .add("A cloud of darkness falls upon you.", Severity::Warning);
+    UseResult::UsedUp
+}
+
+fn drink_paralysis(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    if apply_status(&mut objects[PLAYER], Effect::Stunned, PARALYSIS_TURNS) {
+        game_state.log.add("You can't move!", Severity::Danger);
+    } else {
+        game_state
+            .log
+            .add("Your limbs grow heavy for a moment.", Severity::Info);
+    }
+    UseResult::UsedUp
+}
+
+fn drink_confusion(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    apply_status(&mut objects[PLAYER], Effect::Confused, CONFUSION_TURNS);
+    game_state
+        .log
+        .add("The world starts spinning around you.", Severity::Warning);
+    UseResult::UsedUp
+}
+
+fn drink_regeneration(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    apply_status(
+        &mut objects[PLAYER],
+        Effect::Regenerating,
+        REGENERATION_TURNS,
+    );
+    game_state
+        .log
+        .add("A soothing warmth spreads through you.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn drink_restoration(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    let drained = game_state.drained;
+    if drained == 0 {
+        game_state
+            .log
+            .add("You feel refreshed for a moment.", Severity::Info);
+        return UseResult::UsedUp;
+    }
+    if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+        fighter.base_max_hp += drained;
+        fighter.hp += drained;
+    }
+    game_state.drained = 0;
+    game_state
+        .log
+        .add("Your lost vitality flows back into you.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn drink_polymorph(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    polymorph(game_state, &mut objects[PLAYER], true);
+    UseResult::UsedUp
+}
+
+fn drink_true_sight(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state.see_invisible = TRUE_SIGHT_TURNS;
+    game_state
+        .log
+        .add("Your eyes tingle and your sight sharpens.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn read_remove_curse(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    if remove_curses(&mut game_state.inventory) > 0 {
+        game_state
+            .log
+            .add("A malevolent aura lifts from your gear.", Severity::Good);
+    } else {
+        game_state.log.add(
+            "You feel as if someone is watching over you.",
+            Severity::Good,
+        );
+    }
+    UseResult::UsedUp
+}
+
+fn read_teleportation(game_state: &mut GameState, objects: &mut [Object]) -> UseResult {
+    let mut rng = rand::thread_rng();
+    // give up after some tries, in case the level is crammed
+    for _ in 0..1000 {
+        let x = rng.gen_range(0, WORLD_WIDTH);
+        let y = rng.gen_range(0, WORLD_HEIGHT);
+        if !is_blocked_for(&game_state.world, objects, x, y, MoveMode::Walk)
+            && !game_state.world.get(x, y).is_hazardous()
+        {
+            objects[PLAYER].set_pos(x, y);
+            game_state.travel.clear();
+            game_state
+                .log
+                .add("You feel yourself yanked away!", Severity::Info);
+            return UseResult::UsedUp;
+        }
+    }
+    game_state
+        .log
+        .add("You feel a wrenching sensation.", Severity::Info);
+    UseResult::UsedUp
+}
+
+fn read_magic_mapping(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    let world = &mut game_state.world;
+    let is_open = |x: i32, y: i32| world.in_bounds(x, y) && !world.get(x, y).blocked;
+    // only the walls around open tiles are revealed, not the solid rock behind them
+    let revealed: Vec<(i32, i32)> = (0..WORLD_HEIGHT)
+        .flat_map(|y| (0..WORLD_WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| (y - 1..=y + 1).any(|ny| (x - 1..=x + 1).any(|nx| is_open(nx, ny))))
+        .collect();
+    for (x, y) in revealed {
+        world.get_mut(x, y).explored = true;
+    }
+    game_state
+        .log
+        .add("A map coalesces in your mind!", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn read_enchant_weapon(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    if !enchant_weapon(game_state) {
+        game_state.log.add("Your hands twitch.", Severity::Info);
+    }
+    UseResult::UsedUp
+}
+
+/// Improve the weapon that the player wields. Return false if there is none.
+pub fn enchant_weapon(game_state: &mut GameState) -> bool {
+    let weapon = game_state.inventory.iter_mut().find(|item| {
+        item.equipment
+            .as_ref()
+            .is_some_and(|e| e.equipped && e.is_weapon())
+    });
+    match weapon {
+        Some(weapon) => {
+            enchant(weapon, Bonus::Power(1), &mut game_state.log);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Read the scroll of fireball at the given position in the inventory, which explodes at the
+/// target and burns everyone around it, including the player if it stands too close.
+pub fn read_fireball(
+    inventory_id: usize,
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    target: (i32, i32),
+) {
+    take_one(&mut game_state.inventory, inventory_id);
+    game_state
+        .log
+        .add("The scroll bursts into a ball of fire!", Severity::Info);
+    game_state
+        .noises
+        .push(Noise::new(target.0, target.1, COMBAT_NOISE));
+    let tiles = burst(&game_state.world, target, FIREBALL_RADIUS);
+    let burning = Some((Effect::Burning, FIREBALL_BURN_TURNS));
+    let xp = hit_area(
+        game_state,
+        objects,
+        &tiles,
+        FIREBALL_DAMAGE,
+        burning,
+        "burned",
+    );
+    if xp > 0 && objects[PLAYER].alive {
+        if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+            fighter.xp += xp;
+        }
+        game_state
+            .log
+            .add(format!("You gain {} XP.", xp), Severity::Good);
+    }
+}
+
+fn read_enchant_armor(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    if !enchant_armor(game_state) {
+        game_state
+            .log
+            .add("Your skin glows then fades.", Severity::Info);
+    }
+    UseResult::UsedUp
+}
+
+/// Improve a random piece of armor that the player wears. Return false if there is none.
+pub fn enchant_armor(game_state: &mut GameState) -> bool {
+    let armor_ids: Vec<usize> = game_state
+        .inventory
+        .iter()
+        .enumerate()
+        .filter(|&(_, item)| {
+            item.equipment
+                .as_ref()
+                .is_some_and(|e| e.equipped && e.is_armor())
+        })
+        .map(|(id, _)| id)
+        .collect();
+    if armor_ids.is_empty() {
+        return false;
+    }
+    let armor =
+        &mut game_state.inventory[armor_ids[rand::thread_rng().gen_range(0, armor_ids.len())]];
+    enchant(armor, Bonus::Defense(1), &mut game_state.log);
+    true
+}
+
+fn use_key(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state
+        .log
+        .add("Walk into a locked door to unlock it.", Severity::Info);
+    UseResult::Cancelled
+}
+
+fn use_pickaxe(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state
+        .log
+        .add("Press 'd' and a direction to dig.", Severity::Info);
+    UseResult::Cancelled
+}
+
+fn use_whetstone(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    if repair(&mut game_state.inventory, Equipment::is_weapon) == 0 {
+        game_state
+            .log
+            .add("Your weapons are sharp enough.", Severity::Info);
+        return UseResult::Cancelled;
+    }
+    game_state
+        .log
+        .add("You sharpen your weapons.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn use_repair_kit(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    if repair(&mut game_state.inventory, Equipment::is_armor) == 0 {
+        game_state
+            .log
+            .add("Your armor needs no mending.", Severity::Info);
+        return UseResult::Cancelled;
+    }
+    game_state.log.add("You mend your armor.", Severity::Good);
+    UseResult::UsedUp
+}
+
+fn use_material(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state
+        .log
+        .add("Press 'C' to craft something from it.", Severity::Info);
+    UseResult::Cancelled
+}
+
+fn use_arrow(game_state: &mut GameState, _objects: &mut [Object]) -> UseResult {
+    game_state
+        .log
+        .add("Wield a bow and press 'f' to fire arrows.", Severity::Info);
+    UseResult::Cancelled
+}
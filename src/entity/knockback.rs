@@ -0,0 +1,94 @@
+/// Module Knockback
+///
+/// Heavy blows and some spells push their target away from the attacker. An attack only sees
+/// the attacker and its target, so knockbacks are queued in the game state and resolved once
+/// the action is over, when the other objects can be looked at and moved safely.
+// internal modules
+use entity::object::Object;
+use game_io::{MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+
+// damage taken by a creature that is knocked into something solid
+const COLLISION_DAMAGE: i32 = 3;
+
+/// A push of the creature standing at the given position, by a number of tiles into the
+/// given direction.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Knockback {
+    pub x: i32,
+    pub y: i32,
+    pub dx: i32,
+    pub dy: i32,
+    pub distance: i32,
+}
+
+/// Queue a knockback that pushes the target away from the given position.
+pub fn knock_back(game_state: &mut GameState, from: (i32, i32), target: &Object, distance: i32) {
+    let (dx, dy) = ((target.x - from.0).signum(), (target.y - from.1).signum());
+    if (dx, dy) == (0, 0) || distance <= 0 {
+        return;
+    }
+    game_state.knockbacks.push(Knockback {
+        x: target.x,
+        y: target.y,
+        dx,
+        dy,
+        distance,
+    });
+}
+
+/// Push every creature that has been knocked back tile by tile, until it has flown the whole
+/// distance or hits an obstacle or another creature. Hitting something solid hurts.
+pub fn resolve_knockbacks(game_state: &mut GameState, objects: &mut [Object]) {
+    for knockback in game_state.knockbacks.split_off(0) {
+        let id = objects.iter().position(|object| {
+            object.alive && object.fighter.is_some() && object.pos() == (knockback.x, knockback.y)
+        });
+        let id = match id {
+            Some(id) => id,
+            None => continue,
+        };
+        for _ in 0..knockback.distance {
+            let (x, y) = (objects[id].x + knockback.dx, objects[id].y + knockback.dy);
+            let tile = game_state.world.get(x, y);
+            if tile.blocks(objects[id].move_mode) {
+                // only solid obstacles hurt, a creature simply stops at the edge of water
+                let message = match (id == PLAYER, tile.blocked) {
+                    (true, true) => format!("You slam into the {}!", tile.name()),
+                    (false, true) => {
+                        format!("The {} slams into the {}!", objects[id].name, tile.name())
+                    }
+                    (true, false) => format!("You stop at the edge of the {}.", tile.name()),
+                    (false, false) => format!(
+                        "The {} stops at the edge of the {}.",
+                        objects[id].name,
+                        tile.name()
+                    ),
+                };
+                game_state.log.add(message, Severity::Combat);
+                if tile.blocked {
+                    objects[id].take_damage(COLLISION_DAMAGE, game_state);
+                }
+                break;
+            }
+            let other_id = objects
+                .iter()
+                .position(|object| object.blocks && object.pos() == (x, y));
+            if let Some(other_id) = other_id {
+                let message = if id == PLAYER {
+                    format!("You are knocked against the {}.", objects[other_id].name)
+                } else if other_id == PLAYER {
+                    format!("The {} is knocked against you.", objects[id].name)
+                } else {
+                    format!(
+                        "The {} is knocked against the {}.",
+                        objects[id].name, objects[other_id].name
+                    )
+                };
+                game_state.log.add(message, Severity::Combat);
+                break;
+            }
+            objects[id].set_pos(x, y);
+        }
+    }
+}
@@ -3,7 +3,31 @@
 /// This Module collects all submodules that are related to entites, their
 /// attributes and functions.
 pub mod ai;
+pub mod attributes;
+pub mod behavior;
+pub mod boss;
+pub mod champion;
+pub mod container;
+pub mod corpse;
+pub mod crafting;
+pub mod dice;
+pub mod equipment;
+pub mod experience;
+pub mod faction;
 pub mod fighter;
+pub mod hunger;
+pub mod item;
+pub mod knockback;
+pub mod npc;
 pub mod object;
+pub mod perk;
+pub mod polymorph;
+pub mod race;
+pub mod spawner;
+pub mod spell;
+pub mod stamina;
+pub mod status;
+pub mod stealth;
+pub mod trap;
 
 mod dna;
@@ -0,0 +1,295 @@
+/// Module Npc
+///
+/// Non-player characters are peaceful inhabitants of the world that offer their services when
+/// the player talks to them. Those met in the dungeon can be attacked, but turn on the player
+/// and call for help when they are.
+// internal modules
+use backend::Root;
+use entity::ai::Ai;
+use entity::equipment::{can_let_go, repair, total_wear};
+use entity::item::{add_to_inventory, can_carry, has_room, item_value, take_one, INVENTORY_SIZE};
+use entity::object::Object;
+use game_io::{menu, MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+use world::make_item;
+use world::noise::{Noise, ALARM_NOISE};
+
+// width of the shop and dialogue menus
+const SHOP_WIDTH: i32 = 50;
+// shopkeepers buy items for this fraction of their value
+const SELL_DIVISOR: i32 = 2;
+// gold that the smith charges for each point of durability restored
+const REPAIR_PRICE: i32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Npc {
+    /// Sells the items in its stock and buys those of the player.
+    Shopkeeper { stock: Vec<Object> },
+    /// Restores the player's health.
+    Healer,
+    /// A captive of the monsters that knows its way around the level.
+    Prisoner,
+    /// Swaps items in its stock for items of the player.
+    Trader { stock: Vec<String> },
+    /// Repairs the player's equipment for gold.
+    Smith,
+}
+
+/// Let the player talk to the NPC `npc_id` and make use of its services.
+pub fn interact(
+    root: &mut Root,
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    npc_id: usize,
+) {
+    let mut npc = match objects[npc_id].npc.take() {
+        Some(npc) => npc,
+        None => return,
+    };
+    match npc {
+        Npc::Shopkeeper { ref mut stock } => shop(root, game_state, stock),
+        Npc::Healer => heal(game_state, objects),
+        Npc::Smith => smith(game_state),
+        Npc::Prisoner => talk_to_prisoner(root, game_state, objects),
+        Npc::Trader { ref mut stock } => trade(root, game_state, stock),
+    }
+    objects[npc_id].npc = Some(npc);
+}
+
+fn shop(root: &mut Root, game_state: &mut GameState, stock: &mut Vec<Object>) {
+    let header = format!(
+        "\"Welcome! Care to have a look at my wares?\" You have {} gold.\n",
+        game_state.gold
+    );
+    let options = ["Buy", "Sell", "Leave"];
+    match menu(&header, &options, SHOP_WIDTH, root) {
+        Some(0) => buy(root, game_state, stock),
+        Some(1) => sell(root, game_state, stock),
+        _ => {}
+    }
+}
+
+/// Return the price at which the shopkeeper buys the given item.
+fn sell_price(item: &Object) -> i32 {
+    (item_value(item) / SELL_DIVISOR).max(1)
+}
+
+/// Let the player buy a single item from the shopkeeper's stock.
+fn buy(root: &mut Root, game_state: &mut GameState, stock: &mut Vec<Object>) {
+    if stock.is_empty() {
+        game_state
+            .log
+            .add("\"Sorry, I'm sold out. Come back later!\"", Severity::Info);
+        return;
+    }
+    let wares: Vec<String> = stock
+        .iter()
+        .map(|item| format!("{} - {} gold", item.stack_name(), item_value(item)))
+        .collect();
+    let header = format!("Buy which item? You have {} gold.\n", game_state.gold);
+    let index = match menu(&header, &wares, SHOP_WIDTH, root) {
+        Some(index) => index,
+        None => return,
+    };
+    let price = item_value(&stock[index]);
+    if price > game_state.gold {
+        game_state
+            .log
+            .add("\"You can't afford that, I'm afraid.\"", Severity::Info);
+        return;
+    }
+    if !can_carry(game_state, &stock[index]) {
+        game_state
+            .log
+            .add("Your inventory is full.", Severity::Warning);
+        return;
+    }
+    let item = take_one(stock, index);
+    game_state.gold -= price;
+    game_state.log.add(
+        format!("You buy a {} for {} gold.", item.name, price),
+        Severity::Good,
+    );
+    add_to_inventory(&mut game_state.inventory, item);
+}
+
+/// Let the player sell a single item of its inventory to the shopkeeper.
+fn sell(root: &mut Root, game_state: &mut GameState, stock: &mut Vec<Object>) {
+    if game_state.inventory.is_empty() {
+        game_state
+            .log
+            .add("\"You've got nothing I'd want, friend.\"", Severity::Info);
+        return;
+    }
+    let offers: Vec<String> = game_state
+        .inventory
+        .iter()
+        .map(|item| format!("{} - {} gold", item.stack_name(), sell_price(item)))
+        .collect();
+    let index = match menu("Sell which item?\n", &offers, SHOP_WIDTH, root) {
+        Some(index) => index,
+        None => return,
+    };
+    if !can_let_go(&game_state.inventory[index]) {
+        game_state.log.add(
+            "\"That seems to be stuck to you. No, thank you.\"",
+            Severity::Info,
+        );
+        return;
+    }
+    if !has_room(stock, &game_state.inventory[index], INVENTORY_SIZE) {
+        game_state
+            .log
+            .add("\"I've no room left for that.\"", Severity::Info);
+        return;
+    }
+    let mut item = take_one(&mut game_state.inventory, index);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = false;
+    }
+    let price = sell_price(&item);
+    game_state.gold += price;
+    game_state.log.add(
+        format!("You sell your {} for {} gold.", item.name, price),
+        Severity::Good,
+    );
+    add_to_inventory(stock, item);
+}
+
+/// Let the NPC, which the player has just attacked, turn on the player. It no longer offers
+/// its services and its cries for help wake up the monsters around.
+pub fn provoke(game_state: &mut GameState, npc: &mut Object) {
+    if npc.npc.take().is_none() {
+        return;
+    }
+    npc.ai = Some(Ai::Basic);
+    game_state
+        .noises
+        .push(Noise::new(npc.x, npc.y, ALARM_NOISE));
+    game_state.log.add(
+        format!("The {} screams for help and turns on you!", npc.name),
+        Severity::Warning,
+    );
+}
+
+fn talk_to_prisoner(root: &mut Root, game_state: &mut GameState, objects: &mut [Object]) {
+    let header = "\"You're not one of them! They've kept me here for ages.\"\n";
+    let options = ["Who are you?", "Do you know the way down?", "Farewell."];
+    match menu(header, &options, SHOP_WIDTH, root) {
+        Some(0) => game_state.log.add(
+            "\"I was a lymphocyte once. Now look at me.\"",
+            Severity::Info,
+        ),
+        Some(1) => {
+            let stairs_pos = objects
+                .iter()
+                .find(|object| object.name == "stairs" || object.name == "sealed stairs")
+                .map(|object| object.pos());
+            match stairs_pos {
+                Some((x, y)) => {
+                    game_state.world.get_mut(x, y).explored = true;
+                    for object in objects.iter_mut().filter(|object| object.pos() == (x, y)) {
+                        object.always_visible = true;
+                    }
+                    game_state.log.add(
+                        "\"The stairs? I've seen them drag others down there.\" The prisoner \
+                         describes the way.",
+                        Severity::Info,
+                    );
+                }
+                None => game_state.log.add(
+                    "\"There's no way down from here, as far as I know.\"",
+                    Severity::Info,
+                ),
+            }
+        }
+        _ => game_state
+            .log
+            .add("\"Be careful out there.\"", Severity::Info),
+    }
+}
+
+/// Let the player pick an item from the trader's stock and give one of its own in return.
+fn trade(root: &mut Root, game_state: &mut GameState, stock: &mut [String]) {
+    if stock.is_empty() || game_state.inventory.is_empty() {
+        game_state.log.add(
+            "\"Nothing to trade, it seems. Maybe next time.\"",
+            Severity::Info,
+        );
+        return;
+    }
+    let header = "\"Fancy a trade? One of yours for one of mine.\"\n";
+    let wanted = match menu(header, stock, SHOP_WIDTH, root) {
+        Some(index) => index,
+        None => return,
+    };
+    let offers: Vec<String> = game_state
+        .inventory
+        .iter()
+        .map(|item| item.stack_name())
+        .collect();
+    let offered = match menu(
+        "\"And what do you give me for it?\"\n",
+        &offers,
+        SHOP_WIDTH,
+        root,
+    ) {
+        Some(index) => index,
+        None => return,
+    };
+    if !can_let_go(&game_state.inventory[offered]) {
+        game_state.log.add(
+            "\"That seems to be stuck to you. No, thank you.\"",
+            Severity::Info,
+        );
+        return;
+    }
+    // items in the inventory have no position
+    if let Some(item) = make_item(&stock[wanted], 0, 0) {
+        let given = take_one(&mut game_state.inventory, offered);
+        game_state.log.add(
+            format!("You trade your {} for a {}.", given.name, item.name),
+            Severity::Good,
+        );
+        add_to_inventory(&mut game_state.inventory, item);
+        stock[wanted] = given.name;
+    }
+}
+
+/// Let the smith repair all of the player's equipment, if the player can pay for it.
+fn smith(game_state: &mut GameState) {
+    let price = total_wear(&game_state.inventory) * REPAIR_PRICE;
+    if price == 0 {
+        game_state.log.add(
+            "\"Your gear is in fine shape. Come back when you've dented it.\"",
+            Severity::Info,
+        );
+    } else if price > game_state.gold {
+        game_state.log.add(
+            format!(
+                "\"Mending all that costs {} gold. Come back when you have it.\"",
+                price
+            ),
+            Severity::Info,
+        );
+    } else {
+        repair(&mut game_state.inventory, |_| true);
+        game_state.gold -= price;
+        game_state.log.add(
+            format!(
+                "\"Good as new!\" The smith repairs your gear for {} gold.",
+                price
+            ),
+            Severity::Good,
+        );
+    }
+}
+
+fn heal(game_state: &mut GameState, objects: &mut [Object]) {
+    let max_hp = objects[PLAYER].max_hp(game_state);
+    objects[PLAYER].heal(game_state, max_hp);
+    game_state.log.add(
+        "\"Let me tend to your wounds.\" You feel completely restored.",
+        Severity::Good,
+    );
+}
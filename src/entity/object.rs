@@ -1,16 +1,78 @@
 /// Module Object
 ///
 /// An Object represents the base structure for all entities in the game.
-use tcod::colors::{self, Color};
-use tcod::console::*;
+use rand::Rng;
 
 // internal modules
+use backend::colors::Color;
+use backend::console::{BackgroundFlag, Console};
 use entity::ai::Ai;
+use entity::attributes::Attribute;
+use entity::champion::{drain_max_hp, Affix, BLINDING_TURNS, VAMPIRIC_DIVISOR, VENOM_TURNS};
+use entity::container::Container;
+use entity::corpse::Corpse;
+use entity::dice::Dice;
+use entity::equipment::{wear_armor, wear_weapon, Element, Equipment, Proc};
+use entity::experience::monster_xp;
+use entity::faction::Faction;
 use entity::fighter::Fighter;
-use game_io::MessageLog;
+use entity::hunger::{Hunger, WEAK_POWER_PENALTY};
+use entity::item::Item;
+use entity::knockback::knock_back;
+use entity::npc::Npc;
+use entity::perk::{has_perk, Perk, IRON_SKIN_DEFENSE};
+use entity::polymorph::Polymorph;
+use entity::spawner::Spawner;
+use entity::spell::Caster;
+use entity::status::{apply_status, has_status, is_incapacitated, Effect, Status, SHIELD_DEFENSE};
+use entity::stealth::{backstab, Backstab};
+use entity::trap::Trap;
+use game_io::{MessageLog, Severity};
 use game_state::GameState;
+use world::noise::{Noise, COMBAT_NOISE};
 
-#[derive(Debug, Serialize, Deserialize)]
+// turns that a target of a burning blow keeps burning, and a frozen one cannot act
+const BURN_TURNS: i32 = 3;
+const FREEZE_TURNS: i32 = 3;
+// part of the damage dealt that a life-stealing blow heals
+const LIFESTEAL_DIVISOR: i32 = 2;
+// tiles that a target is pushed away by a knockback blow
+const KNOCKBACK_DISTANCE: i32 = 2;
+// chance in percent of a critical hit without any bonuses, and how much more damage it deals
+const CRIT_CHANCE: i32 = 5;
+const CRIT_MULTIPLIER: i32 = 2;
+// experience levels per percent of additional chance of a critical hit
+const LEVELS_PER_CRIT: i32 = 2;
+// chance in percent that an attack is fumbled, which costs the attacker a turn
+const FUMBLE_CHANCE: i32 = 3;
+// accuracy and evasion in percent without any modifiers, and how much they grow per level
+const BASE_ACCURACY: i32 = 90;
+const BASE_EVASION: i32 = 10;
+const ACCURACY_PER_LEVEL: i32 = 2;
+const EVASION_PER_LEVEL: i32 = 1;
+// accuracy and evasion gained for every point of the dexterity bonus
+const ACCURACY_PER_DEXTERITY: i32 = 3;
+const EVASION_PER_DEXTERITY: i32 = 2;
+// evasion lost for every point of defense of the armor worn, which makes heavy armor cumbersome
+const EVASION_PER_DEFENSE: i32 = 2;
+// accuracy lost while blind or weak from hunger
+const BLIND_ACCURACY_PENALTY: i32 = 30;
+const WEAK_ACCURACY_PENALTY: i32 = 10;
+// bounds of the chance in percent that an attack hits
+const MIN_HIT_CHANCE: i32 = 5;
+const MAX_HIT_CHANCE: i32 = 95;
+
+/// The way in which an object moves around, which determines the terrain it can enter.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MoveMode {
+    Walk,
+    /// Walks and swims through deep water.
+    Swim,
+    /// Floats through walls, doors and water, but not through the bedrock at the world's border.
+    Phase,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Object {
     pub x: i32,
     pub y: i32,
@@ -24,6 +86,46 @@ pub struct Object {
     pub level: i32,           // could be changed into some pseudo-progress like allowed DNA length
     pub fighter: Option<Fighter>,
     pub ai: Option<Ai>,
+    pub item: Option<Item>,
+    pub trap: Option<Trap>,
+    pub npc: Option<Npc>,
+    pub container: Option<Container>,
+    pub move_mode: MoveMode,
+    // radius of the light this object gives off, 0 if it is no light source
+    pub light_radius: i32,
+    // turns this object still has to wait until it has entered its tile, e.g. when wading
+    pub delay: i32,
+    // the remaining steps of the path this object is following, ending at its destination
+    pub path: Vec<(i32, i32)>,
+    pub faction: Faction,
+    // where this monster has last seen the player, while it is still looking for it
+    pub last_seen: Option<(i32, i32)>,
+    // whether this monster has noticed the player
+    pub aware: bool,
+    // follows the scent of the player when it cannot see it
+    pub tracks_scent: bool,
+    // cannot be seen by the player without the ability to see invisible creatures
+    pub invisible: bool,
+    // special abilities of champions
+    pub affixes: Vec<Affix>,
+    // temporary conditions like poison, each with the turns it still lasts
+    pub statuses: Vec<Status>,
+    // status effects that cannot be put on this object
+    pub resistances: Vec<Effect>,
+    // items carried by creatures that are clever enough to pick them up, dropped on death
+    pub inventory: Option<Vec<Object>>,
+    pub caster: Option<Caster>,
+    pub spawner: Option<Spawner>,
+    // turns during which this object acts twice per turn
+    pub haste: i32,
+    // the form this creature returns to once it is no longer polymorphed
+    pub polymorph: Option<Polymorph>,
+    pub corpse: Option<Corpse>,
+    // name of the drop table that is rolled when this monster dies
+    pub drops: Option<String>,
+    pub equipment: Option<Equipment>,
+    // number of equal items on this stack
+    pub count: i32,
 }
 
 impl Object {
@@ -49,6 +151,40 @@ impl Object {
             level: 1,
             fighter: None,
             ai: None,
+            item: None,
+            trap: None,
+            npc: None,
+            container: None,
+            move_mode: MoveMode::Walk,
+            light_radius: 0,
+            delay: 0,
+            path: vec![],
+            faction: Faction::Neutral,
+            last_seen: None,
+            aware: false,
+            tracks_scent: false,
+            invisible: false,
+            affixes: vec![],
+            statuses: vec![],
+            resistances: vec![],
+            inventory: None,
+            caster: None,
+            spawner: None,
+            haste: 0,
+            polymorph: None,
+            corpse: None,
+            drops: None,
+            equipment: None,
+            count: 1,
+        }
+    }
+
+    /// Return the name of this object together with the size of its stack, if it is a stack.
+    pub fn stack_name(&self) -> String {
+        if self.count > 1 {
+            format!("{} (x{})", self.name, self.count)
+        } else {
+            self.name.clone()
         }
     }
 
@@ -61,6 +197,11 @@ impl Object {
         self.y = y;
     }
 
+    /// Return true if the player cannot perceive this object, even when it is in view.
+    pub fn is_hidden(&self) -> bool {
+        self.trap.is_some_and(|trap| trap.hidden)
+    }
+
     /// Set the color and then draw the char that represents this object at its position.
     pub fn draw(&self, con: &mut Console) {
         con.set_default_foreground(self.color);
@@ -91,50 +232,384 @@ impl Object {
             if fighter.hp <= 0 {
                 self.alive = false;
                 fighter.on_death.callback(self, &mut game_state.log);
-                return Some(fighter.xp);
+                return Some(monster_xp(fighter.xp, game_state.level.depth));
             }
         }
         None
     }
 
-    pub fn power(&self, _game_state: &GameState) -> i32 {
-        self.fighter.map_or(0, |f| f.base_power)
+    pub fn power(&self, game_state: &GameState) -> i32 {
+        let base_power = self.fighter.map_or(0, |f| f.base_power);
+        let bonus: i32 = self
+            .get_all_equipped(game_state)
+            .iter()
+            .map(|e| e.power_bonus())
+            .sum();
+        let penalty = match Hunger::from_nutrition(game_state.nutrition) {
+            Hunger::Weak | Hunger::Starving if self.faction == Faction::Player => {
+                WEAK_POWER_PENALTY
+            }
+            _ => 0,
+        };
+        base_power + bonus + self.attribute_bonus(game_state, Attribute::Strength) - penalty
+    }
+
+    /// Return the dice rolled for the damage of this object's attacks: those of the weapon it
+    /// wields, or those of its bare hands, claws or teeth. Polymorphed creatures can't wield
+    /// weapons and fight with what their new form has.
+    pub fn damage_dice(&self, game_state: &GameState) -> Dice {
+        if self.polymorph.is_some() {
+            return self
+                .fighter
+                .map_or(Dice::new(0, 0, 0), |fighter| fighter.damage);
+        }
+        self.get_all_equipped(game_state)
+            .iter()
+            .filter_map(|equipment| equipment.damage)
+            .next()
+            .or_else(|| self.fighter.map(|fighter| fighter.damage))
+            .unwrap_or_else(|| Dice::new(0, 0, 0))
     }
 
     pub fn attack(&mut self, target: &mut Object, game_state: &mut GameState) {
-        // simple formula for attack damage
-        let damage = self.power(game_state) - target.defense(game_state);
+        self.attack_with(target, game_state, "attacks", 0);
+    }
+
+    /// Attack the target with all might, rolling the damage dice once more.
+    pub fn power_attack(&mut self, target: &mut Object, game_state: &mut GameState) {
+        self.attack_with(target, game_state, "smashes", 1);
+    }
+
+    /// Attack the target from afar, with a projectile that is known to reach it.
+    pub fn ranged_attack(&mut self, target: &mut Object, game_state: &mut GameState) {
+        self.attack_with(target, game_state, "spits at", 0);
+    }
+
+    /// Attack the target, describing the attack with the given verb. The damage dice are rolled
+    /// once, plus the given number of extra times.
+    fn attack_with(
+        &mut self,
+        target: &mut Object,
+        game_state: &mut GameState,
+        verb: &str,
+        extra_rolls: i32,
+    ) {
+        game_state
+            .noises
+            .push(Noise::new(target.x, target.y, COMBAT_NOISE));
+        // monsters caught off guard by the player are hit harder
+        let backstab = if self.faction == Faction::Player {
+            backstab(target)
+        } else {
+            None
+        };
+        target.aware = true;
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0, 100) < FUMBLE_CHANCE {
+            self.delay = self.delay.max(1);
+            let message = format!(
+                "{} fumbles the attack and stumbles!",
+                self.visible_name(game_state)
+            );
+            game_state.log.add(message, Severity::Combat);
+            return;
+        }
+        let hit_chance = (self.accuracy(game_state) - target.evasion(game_state))
+            .clamp(MIN_HIT_CHANCE, MAX_HIT_CHANCE);
+        let always_hits = backstab.is_some_and(Backstab::always_hits);
+        if !always_hits && rng.gen_range(0, 100) >= hit_chance {
+            let message = format!(
+                "{} {} {} but misses.",
+                self.visible_name(game_state),
+                verb,
+                target.visible_name(game_state)
+            );
+            game_state.log.add(message, Severity::Combat);
+            return;
+        }
+        // blows that would have hit may still be caught by a shield or turned aside by a weapon
+        let defender_equipment = target.get_all_equipped(game_state);
+        let block_chance: i32 = defender_equipment.iter().map(|e| e.block_bonus()).sum();
+        let parry_chance: i32 = defender_equipment.iter().map(|e| e.parry_bonus()).sum();
+        let defense_verb = if rng.gen_range(0, 100) < block_chance {
+            Some("blocks")
+        } else if rng.gen_range(0, 100) < parry_chance {
+            Some("parries")
+        } else {
+            None
+        };
+        if let Some(defense_verb) = defense_verb {
+            let message = format!(
+                "{} {} the attack of {}.",
+                target.visible_name(game_state),
+                defense_verb,
+                self.visible_name(game_state)
+            );
+            game_state.log.add(message, Severity::Combat);
+            return;
+        }
+        // simple formula for attack damage, elemental damage ignores defense
+        let elements: Vec<(Element, i32)> = self
+            .get_all_equipped(game_state)
+            .iter()
+            .flat_map(|equipment| equipment.elemental_damage())
+            .filter(|&(_, amount)| amount > 0)
+            .collect();
+        let elemental_damage: i32 = elements.iter().map(|&(_, amount)| amount).sum();
+        let dice = self.damage_dice(game_state);
+        let roll: i32 = (0..=extra_rolls).map(|_| dice.roll(&mut rng)).sum();
+        let mut damage =
+            (roll + self.power(game_state) - target.defense(game_state)).max(0) + elemental_damage;
+        let critical = damage > 0 && rng.gen_range(0, 100) < self.critical_chance(game_state);
+        if critical {
+            damage *= CRIT_MULTIPLIER;
+        }
+        if let Some(backstab) = backstab {
+            damage *= backstab.multiplier();
+        }
+        // weapon effects can only be triggered by blows that hurt
+        let procs: Vec<Proc> = if damage > 0 {
+            self.get_all_equipped(game_state)
+                .iter()
+                .flat_map(|equipment| equipment.procs())
+                .filter(|&(_, chance)| rng.gen_range(0, 100) < chance)
+                .map(|(effect, _)| effect)
+                .collect()
+        } else {
+            vec![]
+        };
+        let attacker_name = self.visible_name(game_state);
+        let target_name = target.visible_name(game_state);
+        // weapons wear down with every blow and armor with every hit taken
+        if self.faction == Faction::Player {
+            wear_weapon(game_state);
+        }
+        if target.faction == Faction::Player && damage > 0 {
+            wear_armor(game_state);
+        }
         if damage > 0 {
             // make the target take some damage
+            let mut prefix = String::new();
+            if let Some(backstab) = backstab {
+                prefix += backstab.exclamation();
+                prefix += " ";
+            }
+            if critical {
+                prefix += "Critical hit! ";
+            }
+            let severity = if prefix.is_empty() {
+                Severity::Combat
+            } else {
+                Severity::Critical
+            };
             game_state.log.add(
                 format!(
-                    "{} attacks {} for {} hit points.",
-                    self.name, target.name, damage
+                    "{}{} {} {} for {} hit points.",
+                    prefix, attacker_name, verb, target_name, damage
                 ),
-                colors::WHITE,
+                severity,
             );
-            // target.take_damage(damage, messages);
+            if self.affixes.contains(&Affix::Venomous) && target.fighter.is_some() {
+                apply_status(target, Effect::Poisoned, VENOM_TURNS);
+                game_state
+                    .log
+                    .add(format!("{} is poisoned!", target_name), Severity::Combat);
+            }
+            if self.affixes.contains(&Affix::Blinding) && target.faction == Faction::Player {
+                if game_state.blindness == 0 {
+                    game_state.log.add("You are blinded!", Severity::Warning);
+                }
+                game_state.blindness = game_state.blindness.max(BLINDING_TURNS);
+            }
+            // vampires feed on the damage they deal
+            let vampiric: i32 = self
+                .get_all_equipped(game_state)
+                .iter()
+                .map(|e| e.vampiric_bonus())
+                .sum();
+            let mut life = damage * vampiric / 100;
+            if self.affixes.contains(&Affix::Vampiric) {
+                life += damage / VAMPIRIC_DIVISOR;
+            }
+            if life > 0 {
+                self.heal(game_state, life);
+                game_state.log.add(
+                    format!("{} feeds on the wounds of {}.", attacker_name, target_name),
+                    Severity::Combat,
+                );
+            }
+            if self.affixes.contains(&Affix::Draining) && target.faction == Faction::Player {
+                drain_max_hp(game_state, target);
+            }
+            for &(element, _) in &elements {
+                match element {
+                    Element::Frost => {
+                        apply_status(target, Effect::Frozen, 1);
+                    }
+                    Element::Poison => {
+                        apply_status(target, Effect::Poisoned, VENOM_TURNS);
+                    }
+                    Element::Fire => {}
+                }
+                game_state.log.add(
+                    format!("{} is {}!", target_name, element.hurt_adjective()),
+                    Severity::Combat,
+                );
+            }
+            for &effect in &procs {
+                let message = match effect {
+                    Proc::Burn => {
+                        apply_status(target, Effect::Burning, BURN_TURNS);
+                        format!("{} bursts into flames!", target_name)
+                    }
+                    Proc::Freeze => {
+                        apply_status(target, Effect::Frozen, FREEZE_TURNS);
+                        format!("{} is frozen solid!", target_name)
+                    }
+                    Proc::Lifesteal => {
+                        self.heal(game_state, damage / LIFESTEAL_DIVISOR);
+                        format!("{} drains the life of {}!", attacker_name, target_name)
+                    }
+                    Proc::Knockback => {
+                        knock_back(game_state, self.pos(), target, KNOCKBACK_DISTANCE);
+                        format!("{} is knocked back!", target_name)
+                    }
+                };
+                game_state.log.add(message, Severity::Combat);
+            }
+            // only the player gains experience from kills
             if let Some(xp) = target.take_damage(damage, game_state) {
-                // yield experience to the player
-                self.fighter.as_mut().unwrap().xp += xp;
+                if self.faction == Faction::Player {
+                    self.fighter.as_mut().unwrap().xp += xp;
+                    game_state
+                        .log
+                        .add(format!("You gain {} XP.", xp), Severity::Good);
+                }
             }
         } else {
             game_state.log.add(
                 format!(
-                    "{} attacks {} but it has no effect!",
-                    self.name, target.name
+                    "{} {} {} but it has no effect!",
+                    attacker_name, verb, target_name
                 ),
-                colors::WHITE,
+                Severity::Combat,
             );
         }
     }
 
-    pub fn defense(&self, _game_state: &GameState) -> i32 {
-        self.fighter.map_or(0, |f| f.base_defense)
+    /// Return true if the player can see this object whenever it is in view.
+    pub fn is_perceptible(&self, game_state: &GameState) -> bool {
+        !self.invisible || game_state.see_invisible > 0
     }
 
-    pub fn max_hp(&self, _game_state: &GameState) -> i32 {
-        self.fighter.map_or(0, |f| f.base_max_hp)
+    /// Return the name of this object as the player perceives it. Invisible objects are only
+    /// known as "something", unless the player can see them.
+    pub fn visible_name(&self, game_state: &GameState) -> String {
+        if !self.is_perceptible(game_state) {
+            "something".into()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    pub fn defense(&self, game_state: &GameState) -> i32 {
+        let base_defense = self.fighter.map_or(0, |f| f.base_defense);
+        let bonus: i32 = self
+            .get_all_equipped(game_state)
+            .iter()
+            .map(|e| e.defense_bonus())
+            .sum();
+        let shield = if has_status(self, Effect::Shielded) {
+            SHIELD_DEFENSE
+        } else {
+            0
+        };
+        let perk = if self.faction == Faction::Player && has_perk(game_state, Perk::IronSkin) {
+            IRON_SKIN_DEFENSE
+        } else {
+            0
+        };
+        base_defense + bonus + shield + perk
+    }
+
+    /// Return the chance in percent that an attack of this object hits, before the evasion of
+    /// the target is subtracted.
+    pub fn accuracy(&self, game_state: &GameState) -> i32 {
+        let mut accuracy = BASE_ACCURACY
+            + self.level * ACCURACY_PER_LEVEL
+            + self.attribute_bonus(game_state, Attribute::Dexterity) * ACCURACY_PER_DEXTERITY;
+        if self.faction == Faction::Player {
+            if game_state.blindness > 0 {
+                accuracy -= BLIND_ACCURACY_PENALTY;
+            }
+            if let Hunger::Weak | Hunger::Starving = Hunger::from_nutrition(game_state.nutrition) {
+                accuracy -= WEAK_ACCURACY_PENALTY;
+            }
+        }
+        accuracy
+    }
+
+    /// Return the chance in percent by which this object evades attacks. Armor makes it
+    /// harder to dodge, and creatures that cannot move freely cannot dodge at all.
+    pub fn evasion(&self, game_state: &GameState) -> i32 {
+        if self.delay > 0 || is_incapacitated(self) || has_status(self, Effect::Confused) {
+            return 0;
+        }
+        let burden: i32 = self
+            .get_all_equipped(game_state)
+            .iter()
+            .filter(|e| e.is_armor())
+            .map(|e| e.defense_bonus().max(0))
+            .sum();
+        let dexterity = self.attribute_bonus(game_state, Attribute::Dexterity);
+        (BASE_EVASION + self.level * EVASION_PER_LEVEL + dexterity * EVASION_PER_DEXTERITY
+            - burden * EVASION_PER_DEFENSE)
+            .max(0)
+    }
+
+    /// Return the bonus that the given attribute grants. Only the player has attributes.
+    fn attribute_bonus(&self, game_state: &GameState, attribute: Attribute) -> i32 {
+        if self.faction == Faction::Player {
+            game_state.attributes.bonus(attribute)
+        } else {
+            0
+        }
+    }
+
+    /// Return the chance in percent that an attack of this object is a critical hit, which
+    /// grows with its level and the bonuses of its equipment.
+    pub fn critical_chance(&self, game_state: &GameState) -> i32 {
+        let bonus: i32 = self
+            .get_all_equipped(game_state)
+            .iter()
+            .map(|e| e.critical_bonus())
+            .sum();
+        CRIT_CHANCE + self.level / LEVELS_PER_CRIT + bonus
+    }
+
+    pub fn max_hp(&self, game_state: &GameState) -> i32 {
+        let base_max_hp = self.fighter.map_or(0, |f| f.base_max_hp);
+        let bonus: i32 = self
+            .get_all_equipped(game_state)
+            .iter()
+            .map(|e| e.max_hp_bonus())
+            .sum();
+        base_max_hp + bonus
+    }
+
+    /// Return the equipment that this object wears. The player's equipment is in the inventory
+    /// of the game state, monsters wear items from their own inventory.
+    pub fn get_all_equipped(&self, game_state: &GameState) -> Vec<Equipment> {
+        let inventory: &[Object] = if self.faction == Faction::Player {
+            &game_state.inventory
+        } else {
+            self.inventory.as_ref().map_or(&[], |inventory| inventory)
+        };
+        inventory
+            .iter()
+            .filter_map(|item| item.equipment.clone())
+            .filter(|equipment| equipment.equipped)
+            .collect()
     }
 
     /// heal by the given amount, without going over the maxmimum
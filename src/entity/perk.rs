@@ -0,0 +1,112 @@
+/// Module Perk
+///
+/// Perks are special abilities that the player picks as it gains levels. Every level grants a
+/// perk point, which can be spent right away or saved for later. The perks form a small tree:
+/// some of them can only be picked once the perk they build upon has been learned.
+// internal modules
+use backend::Root;
+use game_io::{menu, MessageLog, Severity};
+use game_state::GameState;
+
+/// Defense added by iron skin.
+pub const IRON_SKIN_DEFENSE: i32 = 2;
+// width of the perk menu
+const PERK_WIDTH: i32 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Perk {
+    /// Raises the defense of the player.
+    IronSkin,
+    /// A killing blow carries on into another enemy next to the player.
+    Cleave,
+    /// Putting on and taking off equipment takes no time.
+    QuickDraw,
+    /// Difficult terrain doesn't slow the player down.
+    SureFooted,
+}
+
+/// All perks, in the order they are offered to the player.
+pub const PERKS: [Perk; 4] = [
+    Perk::IronSkin,
+    Perk::Cleave,
+    Perk::QuickDraw,
+    Perk::SureFooted,
+];
+
+impl Perk {
+    pub fn name(self) -> &'static str {
+        match self {
+            Perk::IronSkin => "Iron Skin",
+            Perk::Cleave => "Cleave",
+            Perk::QuickDraw => "Quick Draw",
+            Perk::SureFooted => "Sure Footed",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Perk::IronSkin => "+2 defense",
+            Perk::Cleave => "killing blows strike another adjacent enemy",
+            Perk::QuickDraw => "changing equipment takes no time",
+            Perk::SureFooted => "difficult terrain doesn't slow you down",
+        }
+    }
+
+    /// Return the perk that has to be learned before this one can be picked, if any.
+    pub fn requires(self) -> Option<Perk> {
+        match self {
+            Perk::Cleave => Some(Perk::IronSkin),
+            Perk::SureFooted => Some(Perk::QuickDraw),
+            Perk::IronSkin | Perk::QuickDraw => None,
+        }
+    }
+}
+
+pub fn has_perk(game_state: &GameState, perk: Perk) -> bool {
+    game_state.perks.contains(&perk)
+}
+
+/// Let the player spend a perk point on one of the perks it can pick. Return true if a perk has
+/// been picked.
+pub fn spend_perk_point(root: &mut Root, game_state: &mut GameState) -> bool {
+    if game_state.perk_points <= 0 {
+        game_state
+            .log
+            .add("You have no perk points to spend.", Severity::Info);
+        return false;
+    }
+    let available: Vec<Perk> = PERKS
+        .iter()
+        .cloned()
+        .filter(|&perk| {
+            !has_perk(game_state, perk)
+                && perk
+                    .requires()
+                    .is_none_or(|required| has_perk(game_state, required))
+        })
+        .collect();
+    if available.is_empty() {
+        game_state
+            .log
+            .add("There are no perks left to learn.", Severity::Info);
+        return false;
+    }
+    let options: Vec<String> = available
+        .iter()
+        .map(|perk| format!("{}: {}", perk.name(), perk.description()))
+        .collect();
+    let header = format!(
+        "Choose a perk ({} points left), or press any other key to save the point.\n",
+        game_state.perk_points
+    );
+    let perk = match menu(&header, &options, PERK_WIDTH, root) {
+        Some(index) => available[index],
+        None => return false,
+    };
+    game_state.perk_points -= 1;
+    game_state.perks.push(perk);
+    game_state
+        .log
+        .add(format!("You have learned {}.", perk.name()), Severity::Good);
+    true
+}
@@ -0,0 +1,164 @@
+/// Module Polymorph
+///
+/// Polymorph transforms a creature into another one for a while. It takes on the looks, hit
+/// points, attacks and spells of its new form, but keeps its mind, its allegiance and the way it
+/// moves. Once the polymorph wears off, the creature returns to its original form with the same
+/// share of its hit points that it had left in the new one. Lasting gains, like those of a level
+/// up, go to the original form, so that they are not lost.
+use rand::Rng;
+
+// internal modules
+use backend::colors::Color;
+use entity::faction::Faction;
+use entity::fighter::Fighter;
+use entity::object::Object;
+use entity::spell::Caster;
+use game_io::{MessageLog, Severity};
+use game_state::GameState;
+use world::make_monster;
+
+// turns that a polymorph lasts
+const POLYMORPH_TURNS: i32 = 20;
+// kinds of monsters that creatures can be turned into
+const FORMS: [&str; 7] = [
+    "virus",
+    "bacteria",
+    "phage",
+    "slime mold",
+    "stalker",
+    "lymphocyte",
+    "prion",
+];
+
+/// The looks, combat stats and spells of a creature, which change with its form.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Form {
+    name: String,
+    chr: char,
+    color: Color,
+    fighter: Fighter,
+    caster: Option<Caster>,
+}
+
+impl Form {
+    /// Return the current form of the creature, or `None` if it can't fight.
+    fn of(object: &Object) -> Option<Form> {
+        object.fighter.map(|fighter| Form {
+            name: object.name.clone(),
+            chr: object.chr,
+            color: object.color,
+            fighter,
+            caster: object.caster.clone(),
+        })
+    }
+
+    /// Give the creature this form. The player stays the player, whatever it looks like.
+    fn apply(&self, object: &mut Object) {
+        if object.faction != Faction::Player {
+            object.name = self.name.clone();
+        }
+        object.chr = self.chr;
+        object.color = self.color;
+        object.caster = self.caster.clone();
+        if let Some(fighter) = object.fighter.as_mut() {
+            let share = fighter.hp as f32 / fighter.base_max_hp.max(1) as f32;
+            fighter.base_max_hp = self.fighter.base_max_hp;
+            fighter.base_defense = self.fighter.base_defense;
+            fighter.base_power = self.fighter.base_power;
+            fighter.damage = self.fighter.damage;
+            fighter.hp = ((share * fighter.base_max_hp as f32).ceil() as i32)
+                .max(1)
+                .min(fighter.base_max_hp);
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Polymorph {
+    pub turns: i32,
+    /// The form to return to once the polymorph wears off.
+    original: Form,
+}
+
+/// Return the fighter and caster that lasting changes to the creature have to be made to: those
+/// of its original form while it is polymorphed, otherwise its own.
+pub fn lasting_form(object: &mut Object) -> (Option<&mut Fighter>, Option<&mut Caster>) {
+    match object.polymorph {
+        Some(ref mut polymorph) => (
+            Some(&mut polymorph.original.fighter),
+            polymorph.original.caster.as_mut(),
+        ),
+        None => (object.fighter.as_mut(), object.caster.as_mut()),
+    }
+}
+
+/// Transform the creature into a random other one. A creature that is polymorphed already
+/// changes its form again, but still returns to its original one in the end. Messages are only
+/// shown if the player can see what happens.
+pub fn polymorph(game_state: &mut GameState, target: &mut Object, visible: bool) {
+    let current = match Form::of(target) {
+        Some(form) => form,
+        None => return,
+    };
+    let choices: Vec<&str> = FORMS
+        .iter()
+        .cloned()
+        .filter(|&name| name != target.name)
+        .collect();
+    let name = choices[rand::thread_rng().gen_range(0, choices.len())];
+    let form = match make_monster(name, 0, 0).and_then(|monster| Form::of(&monster)) {
+        Some(form) => form,
+        None => return,
+    };
+    let message = if target.faction == Faction::Player {
+        format!("You turn into a {}!", form.name)
+    } else {
+        format!(
+            "The {} turns into a {}!",
+            target.visible_name(game_state),
+            form.name
+        )
+    };
+    let original = target
+        .polymorph
+        .take()
+        .map_or(current, |polymorph| polymorph.original);
+    form.apply(target);
+    target.polymorph = Some(Polymorph {
+        turns: POLYMORPH_TURNS,
+        original,
+    });
+    if visible || target.faction == Faction::Player {
+        game_state.log.add(message, Severity::Warning);
+    }
+}
+
+/// Let the polymorph of the creature run its course, and give it back its original form once it
+/// wears off. Needs to be called once per turn.
+pub fn update_polymorph(game_state: &mut GameState, object: &mut Object, visible: bool) {
+    let worn_off = match object.polymorph.as_mut() {
+        Some(polymorph) => {
+            polymorph.turns -= 1;
+            polymorph.turns <= 0
+        }
+        None => return,
+    };
+    if !worn_off {
+        return;
+    }
+    if let Some(polymorph) = object.polymorph.take() {
+        let message = if object.faction == Faction::Player {
+            "You return to your own form.".to_string()
+        } else {
+            format!(
+                "The {} turns back into a {}.",
+                object.visible_name(game_state),
+                polymorph.original.name
+            )
+        };
+        polymorph.original.apply(object);
+        if visible || object.faction == Faction::Player {
+            game_state.log.add(message, Severity::Info);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+/// Module Race
+///
+/// The player chooses a race when starting a new game. Each race changes the attributes the
+/// player starts out with and comes with an innate trait: dwarves make out creatures in the dark
+/// from further away, and elves cannot be paralyzed.
+// internal modules
+use entity::attributes::{raise_attribute, Attribute, Attributes};
+use entity::object::Object;
+use entity::status::Effect;
+
+// distance up to which creatures can be made out in the dark, without and with darkvision
+const NIGHT_SIGHT: f32 = 2.0;
+const DARKVISION: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Race {
+    Human,
+    Dwarf,
+    Elf,
+}
+
+/// All races, in the order they are offered to the player.
+pub const RACES: [Race; 3] = [Race::Human, Race::Dwarf, Race::Elf];
+
+impl Race {
+    pub fn name(self) -> &'static str {
+        match self {
+            Race::Human => "human",
+            Race::Dwarf => "dwarf",
+            Race::Elf => "elf",
+        }
+    }
+
+    /// Return a short description of the race for the menu it is chosen in.
+    pub fn description(self) -> &'static str {
+        match self {
+            Race::Human => "Human: +2 str, +2 con",
+            Race::Dwarf => "Dwarf: +2 str, +4 con, -2 dex, -2 int, sees in the dark",
+            Race::Elf => "Elf: +4 dex, +4 int, -4 con, cannot be paralyzed",
+        }
+    }
+
+    /// Return the amounts that the race adds to each attribute.
+    fn modifiers(self) -> Vec<(Attribute, i32)> {
+        use entity::attributes::Attribute::*;
+        match self {
+            Race::Human => vec![(Strength, 2), (Constitution, 2)],
+            Race::Dwarf => vec![
+                (Strength, 2),
+                (Constitution, 4),
+                (Dexterity, -2),
+                (Intelligence, -2),
+            ],
+            Race::Elf => vec![(Dexterity, 4), (Intelligence, 4), (Constitution, -4)],
+        }
+    }
+
+    /// Return the distance up to which creatures standing in the dark can be made out.
+    pub fn night_sight(self) -> f32 {
+        match self {
+            Race::Dwarf => DARKVISION,
+            Race::Human | Race::Elf => NIGHT_SIGHT,
+        }
+    }
+
+    /// Change the attributes of the newly created player and give it the traits of the race.
+    pub fn apply(self, player: &mut Object, attributes: &mut Attributes) {
+        for (attribute, amount) in self.modifiers() {
+            raise_attribute(player, attributes, attribute, amount);
+        }
+        if self == Race::Elf {
+            player.resistances.push(Effect::Stunned);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+/// Module Spawner
+///
+/// Nests bring forth a new monster every few turns, until the player destroys them.
+// internal modules
+use entity::object::Object;
+use game_io::{FovMap, MessageLog, Severity};
+use game_state::GameState;
+use world::{is_blocked_for, spawn_monster};
+
+// a spawner rests while this many of its monsters are within the radius around it
+const SPAWN_LIMIT: usize = 3;
+const SPAWN_RADIUS: f32 = 6.0;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Spawner {
+    /// Spawn table entry of the monsters that are spawned.
+    pub monster: String,
+    /// Number of turns between two spawned monsters.
+    pub interval: i32,
+    // turns until the next monster is spawned
+    pub countdown: i32,
+}
+
+impl Spawner {
+    pub fn new(monster: &str, interval: i32) -> Self {
+        Spawner {
+            monster: monster.into(),
+            interval,
+            countdown: interval,
+        }
+    }
+}
+
+/// Let every spawner count down and, when it is time, put a new monster on a free tile next to
+/// it. Needs to be called once per turn.
+pub fn update_spawners(game_state: &mut GameState, objects: &mut Vec<Object>, fov_map: &FovMap) {
+    for id in 0..objects.len() {
+        let (x, y) = objects[id].pos();
+        let entry = match objects[id].spawner.as_mut() {
+            Some(spawner) if spawner.countdown > 0 => {
+                spawner.countdown -= 1;
+                continue;
+            }
+            Some(spawner) => {
+                spawner.countdown = spawner.interval;
+                spawner.monster.clone()
+            }
+            None => continue,
+        };
+        let mut monster = match spawn_monster(&entry, x, y) {
+            Some(monster) => monster,
+            None => continue,
+        };
+        let nearby = objects
+            .iter()
+            .filter(|object| {
+                object.alive && object.name == monster.name && object.distance(x, y) <= SPAWN_RADIUS
+            })
+            .count();
+        let spot = (y - 1..=y + 1)
+            .flat_map(|spot_y| (x - 1..=x + 1).map(move |spot_x| (spot_x, spot_y)))
+            .find(|&(spot_x, spot_y)| {
+                game_state.world.in_bounds(spot_x, spot_y)
+                    && !is_blocked_for(
+                        &game_state.world,
+                        objects,
+                        spot_x,
+                        spot_y,
+                        monster.move_mode,
+                    )
+                    && !game_state.world.get(spot_x, spot_y).is_hazardous()
+            });
+        if let (Some((spot_x, spot_y)), true) = (spot, nearby < SPAWN_LIMIT) {
+            monster.set_pos(spot_x, spot_y);
+            if fov_map.is_in_fov(spot_x, spot_y) {
+                game_state.log.add(
+                    format!("A {} crawls out of the {}!", monster.name, objects[id].name),
+                    Severity::Warning,
+                );
+            }
+            objects.push(monster);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+/// Module Spell
+///
+/// Spells cast by monsters and the player. Every spell costs mana, which casters slowly regain
+/// over time, and after each cast a monster needs a few turns before it can cast again. Breath
+/// attacks hit every creature in their area, friend or foe. The player starts out knowing only
+/// the magic missile and learns further spells when leveling up.
+use std::cmp;
+
+// internal modules
+use entity::dice::Dice;
+use entity::knockback::knock_back;
+use entity::object::Object;
+use entity::status::{apply_status, Effect, SHIELD_TURNS};
+use game_io::{FovMap, MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+use util::mut_two;
+use world::area::{cone, ray};
+use world::is_blocked;
+use world::noise::{Noise, COMBAT_NOISE};
+
+/// Maximum distance at which a bolt can hit its target.
+pub const BOLT_RANGE: i32 = 6;
+const BOLT_DAMAGE: i32 = 5;
+const HEAL_AMOUNT: i32 = 8;
+// number of turns during which a hastened creature acts twice per turn
+const HASTE_TURNS: i32 = 5;
+// tiles that a repelled target is pushed away
+const REPEL_DISTANCE: i32 = 3;
+/// Reach of breath attacks, the damage they deal and how long they keep burning or freezing.
+pub const BREATH_RANGE: i32 = 5;
+const BREATH_DAMAGE: i32 = 6;
+const BREATH_EFFECT_TURNS: i32 = 2;
+// turns a caster has to wait after casting a spell
+const SPELL_COOLDOWN: i32 = 2;
+/// Mana that the player starts out with.
+pub const PLAYER_MANA: i32 = 10;
+/// Maximum distance of the target of a magic missile, and of the tile the player blinks to.
+pub const MISSILE_RANGE: f32 = 8.0;
+pub const BLINK_RANGE: f32 = 5.0;
+const MISSILE_DAMAGE: Dice = Dice {
+    count: 2,
+    sides: 4,
+    bonus: 0,
+};
+/// The spells that the player can learn, in the order they are offered.
+pub const PLAYER_SPELLS: [Spell; 3] = [Spell::MagicMissile, Spell::Blink, Spell::Shield];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Spell {
+    /// Hurts a target that is in the line of fire.
+    Bolt,
+    /// Heals the caster.
+    Heal,
+    /// Lets an ally act twice per turn for a while.
+    Haste,
+    /// Pushes a target next to the caster away.
+    Repel,
+    /// Burns everything in a cone towards the target.
+    FireBreath,
+    /// Freezes everything on a line through the target.
+    FrostBreath,
+    /// Hurts a target that the player can see.
+    MagicMissile,
+    /// Moves the player to a free tile nearby in an instant.
+    Blink,
+    /// Raises the defense of the player for a while.
+    Shield,
+}
+
+impl Spell {
+    pub fn name(self) -> &'static str {
+        match self {
+            Spell::Bolt => "bolt",
+            Spell::Heal => "heal",
+            Spell::Haste => "haste",
+            Spell::Repel => "repel",
+            Spell::FireBreath => "fire breath",
+            Spell::FrostBreath => "frost breath",
+            Spell::MagicMissile => "magic missile",
+            Spell::Blink => "blink",
+            Spell::Shield => "shield",
+        }
+    }
+
+    /// Return the amount of mana that casting the spell costs.
+    pub fn cost(self) -> i32 {
+        match self {
+            Spell::Bolt => 3,
+            Spell::Heal => 5,
+            Spell::Haste => 4,
+            Spell::Repel => 3,
+            Spell::FireBreath | Spell::FrostBreath => 6,
+            Spell::MagicMissile => 3,
+            Spell::Blink => 5,
+            Spell::Shield => 6,
+        }
+    }
+}
+
+/// The spells a creature knows and the mana it has left to cast them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Caster {
+    pub spells: Vec<Spell>,
+    pub mana: i32,
+    pub max_mana: i32,
+    // turns until the next spell can be cast
+    pub cooldown: i32,
+}
+
+impl Caster {
+    pub fn new(spells: Vec<Spell>, max_mana: i32) -> Self {
+        Caster {
+            spells,
+            mana: max_mana,
+            max_mana,
+            cooldown: 0,
+        }
+    }
+
+    /// Return true if the caster knows the spell and is able to cast it right now.
+    pub fn can_cast(&self, spell: Spell) -> bool {
+        self.cooldown == 0 && self.mana >= spell.cost() && self.spells.contains(&spell)
+    }
+
+    /// Regain one point of mana and let the cooldown run down, once per turn.
+    pub fn recover(&mut self) {
+        self.mana = cmp::min(self.mana + 1, self.max_mana);
+        self.cooldown = cmp::max(self.cooldown - 1, 0);
+    }
+}
+
+/// Hurt every creature standing on one of the given tiles, and put the given effect on those
+/// that survive. Return the experience that the creatures killed are worth.
+pub fn hit_area(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    tiles: &[(i32, i32)],
+    damage: i32,
+    effect: Option<(Effect, i32)>,
+    verb: &str,
+) -> i32 {
+    let mut xp = 0;
+    for object in objects
+        .iter_mut()
+        .filter(|object| object.alive && object.fighter.is_some() && tiles.contains(&object.pos()))
+    {
+        game_state.log.add(
+            format!("The {} is {} for {} hit points.", object.name, verb, damage),
+            Severity::Combat,
+        );
+        match object.take_damage(damage, game_state) {
+            Some(gained) => xp += gained,
+            None => {
+                if let Some((effect, turns)) = effect {
+                    apply_status(object, effect, turns);
+                }
+            }
+        }
+    }
+    xp
+}
+
+/// Let the creature `caster_id` cast the spell on the creature `target_id`, which is the caster
+/// itself for spells like healing. The caster must be able to cast the spell.
+pub fn cast_spell(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    caster_id: usize,
+    target_id: usize,
+    spell: Spell,
+) {
+    if let Some(caster) = objects[caster_id].caster.as_mut() {
+        caster.mana -= spell.cost();
+        caster.cooldown = SPELL_COOLDOWN;
+    }
+    let visible = fov_map.is_in_fov(objects[caster_id].x, objects[caster_id].y);
+    match spell {
+        Spell::Heal => {
+            objects[caster_id].heal(game_state, HEAL_AMOUNT);
+            if visible {
+                game_state.log.add(
+                    format!("The {} looks healthier.", objects[caster_id].name),
+                    Severity::Info,
+                );
+            }
+        }
+        Spell::Haste => {
+            let (caster, target) = mut_two(objects, caster_id, target_id);
+            target.haste = HASTE_TURNS;
+            if visible {
+                game_state.log.add(
+                    format!("The {} hastens the {}!", caster.name, target.name),
+                    Severity::Warning,
+                );
+            }
+        }
+        Spell::Bolt => {
+            let (caster, target) = mut_two(objects, caster_id, target_id);
+            game_state
+                .noises
+                .push(Noise::new(target.x, target.y, COMBAT_NOISE));
+            game_state.log.add(
+                format!(
+                    "{} hurls a bolt at {} for {} hit points.",
+                    caster.name, target.name, BOLT_DAMAGE
+                ),
+                Severity::Combat,
+            );
+            target.take_damage(BOLT_DAMAGE, game_state);
+        }
+        Spell::Repel => {
+            let (caster, target) = mut_two(objects, caster_id, target_id);
+            if visible {
+                game_state.log.add(
+                    format!("The {} blasts {} away!", caster.name, target.name),
+                    Severity::Combat,
+                );
+            }
+            knock_back(game_state, caster.pos(), target, REPEL_DISTANCE);
+        }
+        Spell::FireBreath | Spell::FrostBreath => {
+            let (origin, target) = (objects[caster_id].pos(), objects[target_id].pos());
+            let (tiles, breath, verb, effect) = if spell == Spell::FireBreath {
+                let tiles = cone(&game_state.world, origin, target, BREATH_RANGE);
+                (tiles, "fire", "scorched", Effect::Burning)
+            } else {
+                let tiles = ray(&game_state.world, origin, target, BREATH_RANGE);
+                (tiles, "frost", "frozen", Effect::Frozen)
+            };
+            game_state
+                .noises
+                .push(Noise::new(origin.0, origin.1, COMBAT_NOISE));
+            if visible {
+                game_state.log.add(
+                    format!("The {} breathes {}!", objects[caster_id].name, breath),
+                    Severity::Warning,
+                );
+            }
+            let effect = Some((effect, BREATH_EFFECT_TURNS));
+            hit_area(game_state, objects, &tiles, BREATH_DAMAGE, effect, verb);
+        }
+        Spell::MagicMissile | Spell::Blink | Spell::Shield => {
+            unreachable!("only the player casts {}", spell.name())
+        }
+    }
+}
+
+/// Let the player cast the spell, aimed at the given tile if it needs a target. The player must
+/// have enough mana left. Return true if the spell has been cast.
+pub fn cast_player_spell(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    spell: Spell,
+    target: Option<(i32, i32)>,
+) -> bool {
+    match spell {
+        Spell::MagicMissile => {
+            let target_id = target.and_then(|target| {
+                objects.iter().position(|object| {
+                    object.alive && object.fighter.is_some() && object.pos() == target
+                })
+            });
+            let target_id = match target_id {
+                Some(target_id) => target_id,
+                None => return false,
+            };
+            let damage =
+                MISSILE_DAMAGE.roll(&mut rand::thread_rng()) + game_state.attributes.spell_power();
+            let damage = damage.max(1);
+            let (x, y) = objects[target_id].pos();
+            game_state.noises.push(Noise::new(x, y, COMBAT_NOISE));
+            game_state.log.add(
+                format!(
+                    "A magic missile strikes the {} for {} hit points.",
+                    objects[target_id].visible_name(game_state),
+                    damage
+                ),
+                Severity::Combat,
+            );
+            if let Some(xp) = objects[target_id].take_damage(damage, game_state) {
+                if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+                    fighter.xp += xp;
+                }
+                game_state
+                    .log
+                    .add(format!("You gain {} XP.", xp), Severity::Good);
+            }
+        }
+        Spell::Blink => {
+            let (x, y) = match target {
+                Some(target) if !is_blocked(&game_state.world, objects, target.0, target.1) => {
+                    target
+                }
+                _ => {
+                    game_state
+                        .log
+                        .add("There is no room to blink to there.", Severity::Info);
+                    return false;
+                }
+            };
+            objects[PLAYER].set_pos(x, y);
+            game_state
+                .log
+                .add("You blink and reappear elsewhere.", Severity::Info);
+        }
+        Spell::Shield => {
+            apply_status(&mut objects[PLAYER], Effect::Shielded, SHIELD_TURNS);
+            game_state
+                .log
+                .add("A shimmering shield surrounds you.", Severity::Good);
+        }
+        _ => return false,
+    }
+    if let Some(caster) = objects[PLAYER].caster.as_mut() {
+        caster.mana -= spell.cost();
+    }
+    true
+}
@@ -0,0 +1,173 @@
+/// Module Stamina
+///
+/// Stamina is to martial feats what mana is to spells. Sprinting, power attacks and kicks use it
+/// up, and it comes back little by little in every turn in which the player doesn't exert
+/// itself. The higher the constitution of the player, the more stamina it has.
+// internal modules
+use entity::ai::Ai;
+use entity::attributes::Attribute;
+use entity::faction::Faction;
+use entity::knockback::knock_back;
+use entity::npc::provoke;
+use entity::object::Object;
+use game_io::{MessageLog, Severity};
+use game_state::{move_by, GameState, PLAYER};
+use util::mut_two;
+use world::noise::{Noise, COMBAT_NOISE};
+use world::{is_blocked_for, Door};
+
+// stamina without any constitution bonus, and how much every point of the bonus adds
+const BASE_STAMINA: i32 = 10;
+const STAMINA_PER_CONSTITUTION: i32 = 2;
+// stamina regained in every turn without exertion
+const STAMINA_REGEN: i32 = 1;
+// stamina used up by each feat
+const SPRINT_COST: i32 = 3;
+const POWER_ATTACK_COST: i32 = 4;
+const KICK_COST: i32 = 2;
+// tiles covered by a single sprint
+const SPRINT_DISTANCE: i32 = 3;
+// damage dealt by a kick, regardless of defense, and the tiles it pushes its target away
+const KICK_DAMAGE: i32 = 2;
+const KICK_DISTANCE: i32 = 2;
+
+pub fn max_stamina(game_state: &GameState) -> i32 {
+    BASE_STAMINA + game_state.attributes.bonus(Attribute::Constitution) * STAMINA_PER_CONSTITUTION
+}
+
+/// Use up the given amount of stamina. Return false if the player is too exhausted.
+fn exert(game_state: &mut GameState, cost: i32) -> bool {
+    if game_state.stamina < cost {
+        game_state
+            .log
+            .add("You are too exhausted for that.", Severity::Info);
+        return false;
+    }
+    game_state.stamina -= cost;
+    game_state.exerted = true;
+    true
+}
+
+/// Regain some stamina at the end of a turn, unless the player has exerted itself during it.
+pub fn recover_stamina(game_state: &mut GameState) {
+    if !game_state.exerted {
+        game_state.stamina = (game_state.stamina + STAMINA_REGEN).min(max_stamina(game_state));
+    }
+    game_state.exerted = false;
+}
+
+/// Return the creature standing next to the player in the given direction, if there is one.
+/// Companions are never the target of feats.
+fn adjacent_target(objects: &[Object], dx: i32, dy: i32) -> Option<usize> {
+    let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+    objects.iter().position(|object| {
+        object.alive
+            && object.fighter.is_some()
+            && object.pos() == (x, y)
+            && object.ai != Some(Ai::Companion)
+    })
+}
+
+/// Dash up to a few tiles into the given direction within a single turn, stopping early at
+/// obstacles and dangerous or difficult terrain. Return true if the player has moved.
+pub fn sprint(game_state: &mut GameState, objects: &mut [Object], dx: i32, dy: i32) -> bool {
+    let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+    if is_blocked_for(&game_state.world, objects, x, y, objects[PLAYER].move_mode) {
+        game_state
+            .log
+            .add("There is no room to sprint.", Severity::Info);
+        return false;
+    }
+    if !exert(game_state, SPRINT_COST) {
+        return false;
+    }
+    for _ in 0..SPRINT_DISTANCE {
+        let position = objects[PLAYER].pos();
+        move_by(&mut game_state.world, objects, PLAYER, dx, dy);
+        let (x, y) = objects[PLAYER].pos();
+        if (x, y) == position
+            || game_state.world.get(x, y).is_hazardous()
+            || objects[PLAYER].delay > 0
+        {
+            break;
+        }
+    }
+    game_state.log.add("You sprint ahead.", Severity::Info);
+    true
+}
+
+/// Put all strength into an attack on the creature in the given direction. Return true if there
+/// was something to attack.
+pub fn power_attack(game_state: &mut GameState, objects: &mut [Object], dx: i32, dy: i32) -> bool {
+    let target_id = match adjacent_target(objects, dx, dy) {
+        Some(target_id) => target_id,
+        None => {
+            game_state
+                .log
+                .add("There is nothing to strike there.", Severity::Info);
+            return false;
+        }
+    };
+    if !exert(game_state, POWER_ATTACK_COST) {
+        return false;
+    }
+    let (player, target) = mut_two(objects, PLAYER, target_id);
+    if target.faction == Faction::Neutral {
+        provoke(game_state, target);
+    }
+    player.power_attack(target, game_state);
+    true
+}
+
+/// Kick the creature in the given direction away, or kick a door open. Return true if there was
+/// something to kick.
+pub fn kick(game_state: &mut GameState, objects: &mut [Object], dx: i32, dy: i32) -> bool {
+    let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+    if let Some(target_id) = adjacent_target(objects, dx, dy) {
+        if !exert(game_state, KICK_COST) {
+            return false;
+        }
+        game_state.noises.push(Noise::new(x, y, COMBAT_NOISE));
+        let (player, target) = mut_two(objects, PLAYER, target_id);
+        if target.faction == Faction::Neutral {
+            provoke(game_state, target);
+        }
+        game_state.log.add(
+            format!(
+                "You kick the {} for {} hit points.",
+                target.visible_name(game_state),
+                KICK_DAMAGE
+            ),
+            Severity::Info,
+        );
+        knock_back(game_state, player.pos(), target, KICK_DISTANCE);
+        if let Some(xp) = target.take_damage(KICK_DAMAGE, game_state) {
+            if let Some(fighter) = player.fighter.as_mut() {
+                fighter.xp += xp;
+            }
+            game_state
+                .log
+                .add(format!("You gain {} XP.", xp), Severity::Good);
+        }
+        return true;
+    }
+    match game_state.world.get(x, y).door {
+        Some(Door::Closed) | Some(Door::Locked) => {
+            if !exert(game_state, KICK_COST) {
+                return false;
+            }
+            game_state.noises.push(Noise::new(x, y, COMBAT_NOISE));
+            game_state.world.get_mut(x, y).set_door(Door::Open);
+            game_state
+                .log
+                .add("You kick the door open.", Severity::Info);
+            true
+        }
+        _ => {
+            game_state
+                .log
+                .add("There is nothing to kick there.", Severity::Info);
+            false
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/// Module Status
+///
+/// Status effects are temporary conditions of creatures, like being poisoned or frozen. Each of
+/// them lasts for a number of turns and takes effect once per turn. Applying an effect again
+/// either prolongs it or only renews it, and some effects put out each other.
+// internal modules
+use backend::colors::{self, Color};
+use entity::faction::Faction;
+use entity::object::Object;
+use game_io::{MessageLog, Severity};
+use game_state::GameState;
+
+// hit points lost to poison and fire, and regained by regeneration, every turn
+const POISON_DAMAGE: i32 = 1;
+const BURN_DAMAGE: i32 = 2;
+const REGENERATION: i32 = 1;
+// longest that an effect can be prolonged to by applying it again
+const MAX_TURNS: i32 = 20;
+/// How long the shield spell lasts, and the defense it adds meanwhile.
+pub const SHIELD_TURNS: i32 = 10;
+pub const SHIELD_DEFENSE: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    Poisoned,
+    Burning,
+    /// Cannot act, and ends burning.
+    Frozen,
+    /// Cannot act.
+    Stunned,
+    Regenerating,
+    /// Staggers about and attacks whoever is in its way.
+    Confused,
+    /// Has a higher defense.
+    Shielded,
+}
+
+impl Effect {
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Poisoned => "poisoned",
+            Effect::Burning => "burning",
+            Effect::Frozen => "frozen",
+            Effect::Stunned => "stunned",
+            Effect::Regenerating => "regenerating",
+            Effect::Confused => "confused",
+            Effect::Shielded => "shielded",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Effect::Poisoned => colors::GREEN,
+            Effect::Burning => colors::FLAME,
+            Effect::Frozen => colors::LIGHT_CYAN,
+            Effect::Stunned => colors::LIGHT_BLUE,
+            Effect::Regenerating => colors::LIGHT_RED,
+            Effect::Confused => colors::LIGHT_VIOLET,
+            Effect::Shielded => colors::LIGHT_SKY,
+        }
+    }
+
+    /// Return true if applying the effect again adds to its duration, instead of only renewing
+    /// it to the longer of both durations.
+    fn stacks(self) -> bool {
+        match self {
+            Effect::Poisoned | Effect::Burning | Effect::Regenerating => true,
+            Effect::Frozen | Effect::Stunned | Effect::Confused | Effect::Shielded => false,
+        }
+    }
+
+    /// Return the effect that this one puts out, if any.
+    fn cancels(self) -> Option<Effect> {
+        match self {
+            Effect::Burning => Some(Effect::Frozen),
+            Effect::Frozen => Some(Effect::Burning),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub effect: Effect,
+    pub turns: i32,
+}
+
+/// Put the given effect on the object for the given number of turns. Return false if the
+/// object resists the effect.
+pub fn apply_status(object: &mut Object, effect: Effect, turns: i32) -> bool {
+    if object.resistances.contains(&effect) {
+        return false;
+    }
+    if let Some(cancelled) = effect.cancels() {
+        object.statuses.retain(|status| status.effect != cancelled);
+    }
+    match object
+        .statuses
+        .iter_mut()
+        .find(|status| status.effect == effect)
+    {
+        Some(status) if effect.stacks() => status.turns = (status.turns + turns).min(MAX_TURNS),
+        Some(status) => status.turns = status.turns.max(turns),
+        None => object.statuses.push(Status { effect, turns }),
+    }
+    true
+}
+
+pub fn has_status(object: &Object, effect: Effect) -> bool {
+    object.statuses.iter().any(|status| status.effect == effect)
+}
+
+/// Return true if the object is kept from acting by its status effects.
+pub fn is_incapacitated(object: &Object) -> bool {
+    has_status(object, Effect::Frozen) || has_status(object, Effect::Stunned)
+}
+
+/// Let the status effects of the object take effect for one turn and count down their
+/// durations. Messages are only shown if the player can see the object. Return the effect that
+/// killed the object, if any.
+pub fn update_statuses(
+    object: &mut Object,
+    game_state: &mut GameState,
+    visible: bool,
+) -> Option<Effect> {
+    let mut killer = None;
+    for status in object.statuses.clone() {
+        let killed = match status.effect {
+            Effect::Poisoned => object.take_damage(POISON_DAMAGE, game_state).is_some(),
+            Effect::Burning => object.take_damage(BURN_DAMAGE, game_state).is_some(),
+            Effect::Regenerating => {
+                object.heal(game_state, REGENERATION);
+                false
+            }
+            Effect::Frozen | Effect::Stunned | Effect::Confused | Effect::Shielded => false,
+        };
+        if killed && killer.is_none() {
+            killer = Some(status.effect);
+        }
+    }
+    if killer.is_some() {
+        return killer;
+    }
+
+    for status in object.statuses.iter_mut() {
+        status.turns -= 1;
+    }
+    let subject = if object.faction == Faction::Player {
+        "You are".to_string()
+    } else {
+        format!("The {} is", object.visible_name(game_state))
+    };
+    for status in object.statuses.iter().filter(|status| status.turns <= 0) {
+        if visible {
+            let message = format!("{} no longer {}.", subject, status.effect.name());
+            game_state.log.add(message, Severity::Info);
+        }
+    }
+    object.statuses.retain(|status| status.turns > 0);
+    None
+}
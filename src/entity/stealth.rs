@@ -0,0 +1,114 @@
+/// Module Stealth
+///
+/// Monsters don't notice the player the moment it comes into view. Every turn, each monster
+/// that could see the player tries to make it out, and the better lit the player is, the closer
+/// it stands and the more noise it has made lately, the likelier it is to be noticed. Sleeping
+/// monsters only notice the player close by. Once noticed, the player stays noticed until it
+/// leaves the monster's sight. Monsters that are asleep, confused or haven't noticed the player
+/// can be backstabbed for extra damage.
+use rand::Rng;
+
+// internal modules
+use entity::ai::Ai;
+use entity::object::Object;
+use entity::status::{has_status, Effect};
+use game_io::FovMap;
+use game_state::{GameState, PLAYER};
+
+// distance from which a monster can't make out even a fully lit player
+const SIGHT_RANGE: f32 = 10.0;
+// even in the dark, a creature can be made out this easily
+const MIN_VISIBILITY: f32 = 0.15;
+// added to the chance of being noticed while the player is making noise
+const NOISE_DETECTION: f32 = 0.5;
+/// Turns during which the player still counts as noisy after making noise.
+pub const NOISE_MEMORY: i32 = 3;
+// sleeping monsters only notice the player within this distance, and less easily
+const WAKE_DISTANCE: f32 = 4.0;
+const SLEEP_FACTOR: f32 = 0.5;
+
+/// The ways in which a monster can be caught off guard by an attack of the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backstab {
+    Asleep,
+    /// The monster hasn't noticed the player.
+    Unaware,
+    Confused,
+}
+
+impl Backstab {
+    /// Return how much more damage the attack deals.
+    pub fn multiplier(self) -> i32 {
+        match self {
+            Backstab::Asleep => 3,
+            Backstab::Unaware | Backstab::Confused => 2,
+        }
+    }
+
+    /// Return true if the monster cannot dodge the attack at all.
+    pub fn always_hits(self) -> bool {
+        self != Backstab::Confused
+    }
+
+    /// Return what the attack is announced with.
+    pub fn exclamation(self) -> &'static str {
+        match self {
+            Backstab::Asleep => "You strike in its sleep!",
+            Backstab::Unaware => "Sneak attack!",
+            Backstab::Confused => "You exploit its confusion!",
+        }
+    }
+}
+
+/// Return how the monster is caught off guard by an attack of the player, if it is at all.
+pub fn backstab(target: &Object) -> Option<Backstab> {
+    if target.ai.is_none() {
+        None
+    } else if let Some(Ai::Asleep { .. }) = target.ai {
+        Some(Backstab::Asleep)
+    } else if !target.aware {
+        Some(Backstab::Unaware)
+    } else if has_status(target, Effect::Confused) {
+        Some(Backstab::Confused)
+    } else {
+        None
+    }
+}
+
+/// Return the chance that the monster notices the player during this turn, if it can see it.
+pub fn detection_chance(game_state: &GameState, monster: &Object, player: &Object) -> f32 {
+    let light = game_state
+        .world
+        .get(player.x, player.y)
+        .light
+        .max(MIN_VISIBILITY);
+    let distance = monster.distance_to(player);
+    let mut chance = light * (1.0 - distance / SIGHT_RANGE).max(0.0);
+    if game_state.noisy > 0 {
+        chance += NOISE_DETECTION;
+    }
+    if let Some(Ai::Asleep { .. }) = monster.ai {
+        if distance > WAKE_DISTANCE {
+            return 0.0;
+        }
+        chance *= SLEEP_FACTOR;
+    }
+    chance.min(1.0)
+}
+
+/// Let the monster try to notice the player, if it can see it. Monsters that lose sight of the
+/// player forget about it, until they notice it again.
+pub fn update_awareness(
+    game_state: &GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    monster_id: usize,
+) {
+    let (x, y) = objects[monster_id].pos();
+    if !fov_map.is_in_fov(x, y) || !objects[PLAYER].alive {
+        objects[monster_id].aware = false;
+    } else if !objects[monster_id].aware {
+        let chance = detection_chance(game_state, &objects[monster_id], &objects[PLAYER]);
+        objects[monster_id].aware = rand::thread_rng().gen::<f32>() < chance;
+    }
+}
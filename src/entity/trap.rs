@@ -0,0 +1,99 @@
+/// Module Trap
+///
+/// Traps are hidden objects that spring when a creature steps onto them. Some of them hurt their
+/// victim, darts also poison it, gas confuses it and polymorph traps turn it into another
+/// creature for a while.
+// internal modules
+use entity::object::Object;
+use entity::polymorph::polymorph;
+use entity::status::{apply_status, Effect};
+use game_io::{MessageLog, Severity};
+use game_state::{GameState, PLAYER};
+use world::noise::{Noise, ALARM_NOISE};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TrapKind {
+    SpikePit,
+    Dart,
+    Alarm,
+    Gas,
+    Polymorph,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub hidden: bool,
+}
+
+// damage dealt by the different kinds of traps
+const SPIKE_PIT_DAMAGE: i32 = 6;
+const DART_DAMAGE: i32 = 3;
+// turns that the victims of darts stay poisoned, and of gas confused
+const DART_POISON_TURNS: i32 = 4;
+const GAS_CONFUSION_TURNS: i32 = 6;
+
+/// Make the trap `trap` visible to the player.
+pub fn reveal_trap(trap: &mut Object) {
+    if let Some(trap) = trap.trap.as_mut() {
+        trap.hidden = false;
+    }
+    trap.always_visible = true;
+}
+
+/// Spring the trap `trap` on the creature `victim`. The trap becomes visible afterwards.
+/// Messages are only shown if the player can see what happens.
+pub fn trigger_trap(
+    game_state: &mut GameState,
+    trap: &mut Object,
+    victim: &mut Object,
+    victim_id: usize,
+    visible: bool,
+) {
+    let kind = match trap.trap {
+        Some(trap) => trap.kind,
+        None => return,
+    };
+    reveal_trap(trap);
+
+    let (player_verb, monster_verb, damage) = match kind {
+        TrapKind::SpikePit => ("fall", "falls", SPIKE_PIT_DAMAGE),
+        TrapKind::Dart => ("are", "is", DART_DAMAGE),
+        TrapKind::Alarm => ("step", "steps", 0),
+        TrapKind::Gas => ("are", "is", 0),
+        TrapKind::Polymorph => ("are", "is", 0),
+    };
+    let subject = if victim_id == PLAYER {
+        format!("You {}", player_verb)
+    } else {
+        format!("The {} {}", victim.name, monster_verb)
+    };
+    let message = match kind {
+        TrapKind::SpikePit => format!("{} into a spike pit!", subject),
+        TrapKind::Dart => format!("{} hit by a dart from a hidden trap!", subject),
+        TrapKind::Alarm => format!("{} on a pressure plate. An alarm blares!", subject),
+        TrapKind::Gas => format!("{} engulfed by a cloud of sickly gas!", subject),
+        TrapKind::Polymorph => format!("{} bathed in a flash of swirling light!", subject),
+    };
+    if visible || victim_id == PLAYER {
+        game_state.log.add(message, Severity::Warning);
+    }
+    if kind == TrapKind::Alarm {
+        game_state
+            .noises
+            .push(Noise::new(trap.x, trap.y, ALARM_NOISE));
+    }
+    match kind {
+        TrapKind::Dart => {
+            apply_status(victim, Effect::Poisoned, DART_POISON_TURNS);
+        }
+        TrapKind::Gas => {
+            apply_status(victim, Effect::Confused, GAS_CONFUSION_TURNS);
+        }
+        TrapKind::Polymorph => polymorph(game_state, victim, visible),
+        TrapKind::SpikePit | TrapKind::Alarm => {}
+    }
+    if damage > 0 {
+        victim.take_damage(damage, game_state);
+    }
+}
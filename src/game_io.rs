@@ -4,19 +4,37 @@
 use std::error::Error;
 use std::fs::File;
 use std::io::{Read, Write};
-use tcod::colors::{self, Color};
-use tcod::console::*;
-use tcod::input::{self, Event, Key, Mouse};
-use tcod::map::FovAlgorithm;
 
 // internal modules
+use backend::colors::{self, Color};
+use backend::console::{blit, wrap, BackgroundFlag, Console, TextAlignment};
+use backend::input::{Event, Key, KeyCode, Mouse};
+use backend::{Backend, Root};
 use color_palette::*;
+use entity::ai::Ai;
+use entity::container::open_bag;
+use entity::crafting::craft;
+use entity::equipment::compare;
+use entity::experience::level_up_xp;
+use entity::faction::Faction;
+use entity::hunger::{eat_corpse, Hunger};
+use entity::item::{
+    drop_item, fire_arrow, read_fireball, ready_arrow, throw_item, use_item, Item, BOW_RANGE,
+    FIREBALL_RANGE, THROW_RANGE,
+};
+use entity::npc::interact;
 use entity::object::Object;
+use entity::perk::{has_perk, spend_perk_point, Perk};
+use entity::race::RACES;
+use entity::spell::{cast_player_spell, Spell, BLINK_RANGE, MISSILE_RANGE};
+use entity::stamina::{kick, max_stamina, power_attack, sprint};
 use game_state::{
-    game_loop, new_game, next_level, player_move_or_attack, GameState, LEVEL_UP_BASE,
-    LEVEL_UP_FACTOR, PLAYER, TORCH_RADIUS,
+    enter_branch, game_loop, new_game, next_level, pick_item_up, player_dig, player_move_or_attack,
+    player_rest, player_run, player_search, player_toggle_doors, player_travel_to,
+    player_travel_to_stairs, previous_level, GameState, PLAYER, TORCH_LIGHT_RADIUS,
 };
-use world::{World, WORLD_HEIGHT, WORLD_WIDTH};
+use world::light::{player_light_radius, sight_radius, update_light};
+use world::{trace_projectile, Door, Terrain, World, BRANCHES, WORLD_HEIGHT, WORLD_WIDTH};
 
 // GUI constraints
 // window size
@@ -25,8 +43,10 @@ pub const SCREEN_HEIGHT: i32 = 50;
 // target fps
 pub const LIMIT_FPS: i32 = 20;
 // constraints for field of view computing and rendering
-const FOV_ALG: FovAlgorithm = FovAlgorithm::Shadow;
 const FOV_LIGHT_WALLS: bool = true;
+// tiles in view are drawn at least this bright, and creatures in darker places can only be seen
+// from right next to them
+const MIN_LIGHT: f32 = 0.15;
 
 // Menu constraints
 const BAR_WIDTH: i32 = 20;
@@ -35,70 +55,132 @@ const PANEL_Y: i32 = SCREEN_HEIGHT - PANEL_HEIGHT;
 const MSG_X: i32 = BAR_WIDTH + 2;
 const MSG_WIDTH: i32 = SCREEN_WIDTH - BAR_WIDTH - 2;
 const MSG_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
+// lines of the message log screen that show messages, below its title
+const LOG_HEIGHT: i32 = SCREEN_HEIGHT - 2;
 
 const CHARACTER_SCREEN_WIDTH: i32 = 30;
+const INVENTORY_WIDTH: i32 = 50;
 
 /// Field of view mapping
-pub use tcod::map::Map as FovMap;
+pub use world::fov::FovMap;
 
 /// GameIO holds he core components for game's input and output processing.
 pub struct GameIO {
     pub root: Root,
-    pub con: Offscreen,
-    pub panel: Offscreen,
+    pub con: Console,
+    pub panel: Console,
     pub fov: FovMap,
     pub mouse: Mouse,
+    /// Sight radius and light radius of the player the last time the fov was computed.
+    pub sight_radius: i32,
+    pub player_light: i32,
 }
 
-pub type Messages = Vec<(String, Color)>;
+/// How much a message matters to the player. Each kind of message has a color of its own, so
+/// that warnings stand out from routine combat.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Blows exchanged in a fight, which pile up quickly.
+    Combat,
+    /// Critical hits and backstabs, which stand out from the other blows.
+    Critical,
+    /// Everything else the player should know about.
+    Info,
+    /// Good news, like healing or gaining experience.
+    Good,
+    /// Something the player should keep an eye on.
+    Warning,
+    /// Immediate danger to the player's life.
+    Danger,
+}
 
-pub trait MessageLog {
-    fn add<T: Into<String>>(&mut self, message: T, color: Color);
+impl From<Severity> for Color {
+    fn from(severity: Severity) -> Color {
+        match severity {
+            Severity::Combat => colors::LIGHT_GREY,
+            Severity::Critical => colors::LIGHT_YELLOW,
+            Severity::Info => colors::WHITE,
+            Severity::Good => colors::LIGHT_GREEN,
+            Severity::Warning => colors::ORANGE,
+            Severity::Danger => colors::RED,
+        }
+    }
+}
+
+/// A message in the log. A message that repeats the one before it only raises its count.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub text: String,
+    pub color: Color,
+    pub count: u32,
 }
 
-impl MessageLog for Vec<(String, Color)> {
-    fn add<T: Into<String>>(&mut self, message: T, color: Color) {
-        self.push((message.into(), color));
+impl Message {
+    /// Return the text of the message, together with the number of repeats if there are any.
+    pub fn display(&self) -> String {
+        if self.count > 1 {
+            format!("{} (x{})", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
     }
 }
 
-pub fn initialize_io() -> GameIO {
-    let root = Root::initializer()
-        .font("assets/terminal16x16_gs_ro.png", FontLayout::AsciiInRow)
-        .font_type(FontType::Greyscale)
-        .size(SCREEN_WIDTH, SCREEN_HEIGHT)
-        .title("innit alpha v0.0.1")
-        .init();
+pub type Messages = Vec<Message>;
 
-    tcod::system::set_fps(LIMIT_FPS);
+pub trait MessageLog {
+    /// Add a message in the color of the given severity.
+    fn add<T: Into<String>>(&mut self, message: T, severity: Severity);
+}
 
+impl MessageLog for Vec<Message> {
+    fn add<T: Into<String>>(&mut self, message: T, severity: Severity) {
+        let (text, color) = (message.into(), Color::from(severity));
+        if let Some(last) = self.last_mut() {
+            if last.text == text && last.color == color {
+                last.count += 1;
+                return;
+            }
+        }
+        self.push(Message {
+            text,
+            color,
+            count: 1,
+        });
+    }
+}
+
+/// Set up the consoles of the game on top of the given backend.
+pub fn initialize_io(backend: Box<dyn Backend>) -> GameIO {
     GameIO {
-        root,
-        con: Offscreen::new(SCREEN_WIDTH, SCREEN_HEIGHT),
-        panel: Offscreen::new(SCREEN_WIDTH, PANEL_HEIGHT),
+        root: Root::new(backend, SCREEN_WIDTH, SCREEN_HEIGHT),
+        con: Console::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        panel: Console::new(SCREEN_WIDTH, PANEL_HEIGHT),
         fov: FovMap::new(WORLD_WIDTH, WORLD_HEIGHT),
         mouse: Default::default(),
+        sight_radius: 0,
+        player_light: 0,
     }
 }
 
 pub fn initialize_fov(world: &World, game_io: &mut GameIO) {
     // init fov map
+    update_fov_map(world, &mut game_io.fov);
+    game_io.con.clear(); // unexplored areas start black (which is the default background color)
+}
+
+/// Copy the transparency and walkability of all tiles into the fov map.
+fn update_fov_map(world: &World, fov: &mut FovMap) {
     for y in 0..WORLD_HEIGHT {
         for x in 0..WORLD_WIDTH {
-            game_io.fov.set(
-                x,
-                y,
-                !world[x as usize][y as usize].block_sight,
-                !world[x as usize][y as usize].blocked,
-            );
+            fov.set(x, y, !world.get(x, y).block_sight);
         }
     }
-    game_io.con.clear(); // unexplored areas start black (which is the default background color)
 }
 
 #[allow(clippy::too_many_arguments)]
 fn render_bar(
-    panel: &mut Offscreen,
+    panel: &mut Console,
     x: i32,
     y: i32,
     total_width: i32,
@@ -139,39 +221,45 @@ pub fn render_all(
     objects: &[Object],
     fov_recompute: bool,
 ) {
-    if fov_recompute {
+    // sight and light change with equipment and effects, even without taking a turn
+    let player = &objects[PLAYER];
+    let radius = sight_radius(game_state, player);
+    let player_light = player_light_radius(game_state, player);
+    if fov_recompute || radius != game_io.sight_radius || player_light != game_io.player_light {
         // recompute fov if needed (the player moved or something)
-        let player = &objects[PLAYER];
+        update_fov_map(&game_state.world, &mut game_io.fov);
+        update_light(&mut game_state.world, objects, player_light);
         game_io
             .fov
-            .compute_fov(player.x, player.y, TORCH_RADIUS, FOV_LIGHT_WALLS, FOV_ALG);
+            .compute_fov(player.x, player.y, radius, FOV_LIGHT_WALLS);
+        game_io.sight_radius = radius;
+        game_io.player_light = player_light;
     }
 
     // go through all tiles and set their background color
     for y in 0..WORLD_HEIGHT {
         for x in 0..WORLD_WIDTH {
-            let visible = game_io.fov.is_in_fov(x, y);
-            let wall = game_state.world[x as usize][y as usize].block_sight;
-            let tile_color = match (visible, wall) {
+            let visible = in_view(&game_io.fov, game_state, &objects[PLAYER], x, y);
+            let tile = game_state.world.get(x, y);
+            let (light_color, dark_color) = match tile.terrain {
+                _ if tile.block_sight => get_col_walls(game_state.world.theme),
+                Terrain::DeepWater => (get_col_light_water(), get_col_dark_water()),
+                Terrain::ShallowWater => {
+                    (get_col_light_shallow_water(), get_col_dark_shallow_water())
+                }
+                Terrain::Lava => (get_col_light_lava(), get_col_dark_lava()),
+                Terrain::Chasm => (get_col_light_chasm(), get_col_dark_chasm()),
+                _ => get_col_grounds(game_state.world.theme),
+            };
+            let tile_color = if visible {
+                // inside fov: the darker the tile, the dimmer it is drawn
+                colors::lerp(dark_color, light_color, tile.light.max(MIN_LIGHT))
+            } else {
                 // outside field of view:
-                (false, true) => get_col_dark_wall(),
-                (false, false) => get_col_dark_ground(),
-                // inside fov:
-                // (true, true) => COLOR_LIGHT_WALL,
-                (true, true) => colors::lerp(
-                    get_col_light_wall(),
-                    get_col_dark_wall(),
-                    objects[PLAYER].distance(x, y) / TORCH_RADIUS as f32,
-                ),
-                // (true, false) => COLOR_LIGHT_GROUND,
-                (true, false) => colors::lerp(
-                    get_col_light_ground(),
-                    get_col_dark_ground(),
-                    objects[PLAYER].distance(x, y) / TORCH_RADIUS as f32,
-                ),
+                dark_color
             };
 
-            let explored = &mut game_state.world[x as usize][y as usize].explored;
+            let explored = &mut game_state.world.get_mut(x, y).explored;
             if visible {
                 *explored = true;
             }
@@ -180,6 +268,19 @@ pub fn render_all(
                 game_io
                     .con
                     .set_char_background(x, y, tile_color, BackgroundFlag::Set);
+
+                // draw doors on top of the tile background
+                let door = match game_state.world.get(x, y).door {
+                    Some(Door::Open) => Some(('\'', get_col_door())),
+                    Some(Door::Closed) => Some(('+', get_col_door())),
+                    Some(Door::Locked) => Some(('+', get_col_locked_door())),
+                    // secret doors look just like walls
+                    Some(Door::Secret) | None => None,
+                };
+                if let Some((chr, color)) = door {
+                    game_io.con.set_default_foreground(color);
+                    game_io.con.put_char(x, y, chr, BackgroundFlag::None);
+                }
             }
         }
     }
@@ -187,8 +288,8 @@ pub fn render_all(
     let mut to_draw: Vec<&Object> = objects
         .iter()
         .filter(|o| {
-            game_io.fov.is_in_fov(o.x, o.y)
-                || (o.always_visible && game_state.world[o.x as usize][o.y as usize].explored)
+            can_see(&game_io.fov, game_state, &objects[PLAYER], o)
+                || (o.always_visible && !o.is_hidden() && game_state.world.get(o.x, o.y).explored)
         })
         .collect();
     // sort, so that non-blocking objects com first
@@ -204,7 +305,7 @@ pub fn render_all(
 
     // show player's stats
     let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
-    let max_hp = objects[PLAYER].fighter.map_or(0, |f| f.base_max_hp);
+    let max_hp = objects[PLAYER].max_hp(game_state);
     render_bar(
         &mut game_io.panel,
         1,
@@ -216,12 +317,78 @@ pub fn render_all(
         colors::LIGHT_RED,
         colors::DARKER_RED,
     );
+    // mana and stamina share a line, each with half of the width
+    if let Some(caster) = objects[PLAYER].caster.as_ref() {
+        render_bar(
+            &mut game_io.panel,
+            1,
+            2,
+            BAR_WIDTH / 2 - 1,
+            "MP",
+            caster.mana,
+            caster.max_mana,
+            colors::LIGHT_BLUE,
+            colors::DARKER_BLUE,
+        );
+    }
+    render_bar(
+        &mut game_io.panel,
+        BAR_WIDTH / 2 + 1,
+        2,
+        BAR_WIDTH / 2,
+        "SP",
+        game_state.stamina,
+        max_stamina(game_state),
+        colors::LIGHT_GREEN,
+        colors::DARKER_GREEN,
+    );
     game_io.panel.print_ex(
         1,
-        2,
+        3,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        format!("Dungeon level: {}", game_state.level.depth),
+    );
+    let location = if game_state.level.depth == 0 {
+        "Overworld"
+    } else {
+        BRANCHES[game_state.level.branch].name
+    };
+    game_io.panel.print_ex(
+        1,
+        4,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        format!("{} ({})", location, game_state.world.theme.name()),
+    );
+
+    // hunger and status effects share a line, one after the other
+    let mut x = 1;
+    if let Some((hunger, color)) = Hunger::from_nutrition(game_state.nutrition).indicator() {
+        game_io.panel.set_default_foreground(color);
+        game_io
+            .panel
+            .print_ex(x, 5, BackgroundFlag::None, TextAlignment::Left, hunger);
+        x += hunger.len() as i32 + 1;
+    }
+    for status in &objects[PLAYER].statuses {
+        game_io.panel.set_default_foreground(status.effect.color());
+        game_io.panel.print_ex(
+            x,
+            5,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            status.effect.name(),
+        );
+        x += status.effect.name().len() as i32 + 1;
+    }
+    game_io.panel.set_default_foreground(colors::GOLD);
+    game_io.panel.print_ex(
+        1,
+        6,
         BackgroundFlag::None,
         TextAlignment::Left,
-        format!("Dungeon level: {}", game_state.dungeon_level),
+        format!("Gold: {}", game_state.gold),
     );
 
     // show names of objects under the mouse
@@ -231,18 +398,19 @@ pub fn render_all(
         0,
         BackgroundFlag::None,
         TextAlignment::Left,
-        get_names_under_mouse(game_io.mouse, objects, &game_io.fov),
+        describe_under_mouse(game_io.mouse, game_state, objects, &game_io.fov),
     );
 
     // print game messages, one line at a time
     let mut y = MSG_HEIGHT as i32;
-    for &(ref msg, color) in &mut game_state.log.iter().rev() {
-        let msg_height = game_io.panel.get_height_rect(MSG_X, y, MSG_WIDTH, 0, msg);
+    for message in game_state.log.iter().rev() {
+        let msg = message.display();
+        let msg_height = game_io.panel.get_height_rect(MSG_X, y, MSG_WIDTH, 0, &msg);
         y -= msg_height;
         if y < 0 {
             break;
         }
-        game_io.panel.set_default_foreground(color);
+        game_io.panel.set_default_foreground(message.color);
         game_io.panel.print_rect(MSG_X, y, MSG_WIDTH, 0, msg);
     }
 
@@ -283,9 +451,8 @@ pub fn handle_keys(
     objects: &mut Vec<Object>,
     key: Key,
 ) -> PlayerAction {
+    use backend::input::KeyCode::*;
     use game_io::PlayerAction::*;
-    use tcod::input::Key;
-    use tcod::input::KeyCode::*;
 
     let player_alive = objects[PLAYER].alive;
     match (key, player_alive) {
@@ -306,60 +473,355 @@ pub fn handle_keys(
         // exit game
         (Key { code: Escape, .. }, _) => Exit,
 
-        // handle movement
-        (Key { code: Up, .. }, true) => {
-            player_move_or_attack(game_state, objects, 0, -1);
-            TookTurn
+        // run with shift and a direction key
+        (key, true) if run_direction(key).is_some() => {
+            let (dx, dy) = run_direction(key).unwrap_or((0, 0));
+            run(game_io, game_state, objects, dx, dy)
         }
-        (Key { code: Down, .. }, true) => {
-            player_move_or_attack(game_state, objects, 0, 1);
+
+        // handle movement with the arrow keys, the vi-keys or the numpad
+        (key, true) if key_direction(key).is_some() => {
+            let (dx, dy) = key_direction(key).unwrap_or((0, 0));
+            player_move_or_attack(game_state, objects, dx, dy);
             TookTurn
         }
-        (Key { code: Left, .. }, true) => {
-            player_move_or_attack(game_state, objects, -1, 0);
+        (Key { printable: '.', .. }, true) | (Key { code: NumPad5, .. }, true) => {
+            // do nothing, i.e. wait for the monster to come to you
             TookTurn
         }
-        (Key { code: Right, .. }, true) => {
-            player_move_or_attack(game_state, objects, 1, 0);
-            TookTurn
+        (Key { printable: 'x', .. }, _) => {
+            // examine the surroundings, and travel to the chosen tile if there is one
+            if let Some(target) = look(game_io, game_state, objects) {
+                player_travel_to(game_state, objects, target);
+            }
+            DidntTakeTurn
         }
-        (Key { printable: 'x', .. }, true) => {
-            // do nothing, i.e. wait for the monster to come to you
+        (Key { printable: 'R', .. }, true) => {
+            // rest until recovered or interrupted, one turn per frame
+            player_rest(game_state, objects, &game_io.fov);
+            DidntTakeTurn
+        }
+        (Key { printable: 'g', .. }, true) => {
+            // pick up an item
+            let item_id = objects
+                .iter()
+                .position(|object| object.pos() == objects[PLAYER].pos() && object.item.is_some());
+            if let Some(item_id) = item_id {
+                pick_item_up(game_state, objects, item_id);
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 'i', .. }, true) => {
+            // show the inventory, and use an item if one is chosen
+            let inventory_index = inventory_menu(
+                &game_state.inventory,
+                "Press the key next to an item to use it, or any other to cancel.\n",
+                &mut game_io.root,
+            );
+            match inventory_index {
+                // bags are opened to show what they hold
+                Some(inventory_index)
+                    if game_state.inventory[inventory_index].item == Some(Item::Bag) =>
+                {
+                    if open_bag(&mut game_io.root, game_state, inventory_index) {
+                        TookTurn
+                    } else {
+                        DidntTakeTurn
+                    }
+                }
+                // fireballs are aimed at a tile first
+                Some(inventory_index)
+                    if game_state.inventory[inventory_index].item == Some(Item::Fireball) =>
+                {
+                    game_state.log.add(
+                        "Left-click a target tile, or right-click to cancel.",
+                        Severity::Info,
+                    );
+                    match target_tile(game_io, game_state, objects, Some(FIREBALL_RANGE)) {
+                        Some(target) => {
+                            read_fireball(inventory_index, game_state, objects, target);
+                            TookTurn
+                        }
+                        None => DidntTakeTurn,
+                    }
+                }
+                Some(inventory_index) => {
+                    let equipment = game_state.inventory[inventory_index].equipment.is_some();
+                    use_item(inventory_index, game_state, objects);
+                    if equipment && has_perk(game_state, Perk::QuickDraw) {
+                        DidntTakeTurn
+                    } else {
+                        TookTurn
+                    }
+                }
+                None => DidntTakeTurn,
+            }
+        }
+        (Key { printable: 'D', .. }, true) => {
+            // show the inventory, and drop an item if one is chosen
+            let inventory_index = inventory_menu(
+                &game_state.inventory,
+                "Press the key next to an item to drop it, or any other to cancel.\n",
+                &mut game_io.root,
+            );
+            if let Some(inventory_index) = inventory_index {
+                drop_item(inventory_index, game_state, objects);
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 'T', .. }, true) => {
+            // throw an item at a tile
+            let inventory_index = inventory_menu(
+                &game_state.inventory,
+                "Press the key next to an item to throw it, or any other to cancel.\n",
+                &mut game_io.root,
+            );
+            let inventory_index = match inventory_index {
+                Some(inventory_index) => inventory_index,
+                None => return DidntTakeTurn,
+            };
+            match aim_projectile(game_io, game_state, objects, inventory_index, THROW_RANGE) {
+                Some(path) => {
+                    throw_item(inventory_index, game_state, objects, &path);
+                    TookTurn
+                }
+                None => DidntTakeTurn,
+            }
+        }
+        (Key { printable: 'f', .. }, true) => {
+            // fire an arrow from the bow at a tile
+            let arrow_index = match ready_arrow(game_state) {
+                Some(arrow_index) => arrow_index,
+                None => return DidntTakeTurn,
+            };
+            match aim_projectile(game_io, game_state, objects, arrow_index, BOW_RANGE) {
+                Some(path) => {
+                    fire_arrow(arrow_index, game_state, objects, &path);
+                    TookTurn
+                }
+                None => DidntTakeTurn,
+            }
+        }
+        (Key { printable: 'o', .. }, true) => {
+            // open or close adjacent doors
+            if player_toggle_doors(game_state, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 's', .. }, true) => {
+            // search for secret doors and hidden traps
+            player_search(game_state, objects);
             TookTurn
         }
+        (Key { printable: 'd', .. }, true) => {
+            // dig into an adjacent wall
+            let direction = ask_direction("Dig in which direction?", &mut game_io.root);
+            match direction {
+                Some((dx, dy)) if player_dig(game_state, objects, dx, dy) => TookTurn,
+                _ => DidntTakeTurn,
+            }
+        }
+        (Key { printable: '>', .. }, true) => {
+            // travel to the stairs down
+            player_travel_to_stairs(game_state, objects);
+            DidntTakeTurn
+        }
+        (Key { printable: 't', .. }, true) => {
+            // talk to an adjacent NPC
+            let (player_x, player_y) = objects[PLAYER].pos();
+            let npc_id = objects.iter().position(|object| {
+                object.npc.is_some()
+                    && (object.x - player_x).abs() <= 1
+                    && (object.y - player_y).abs() <= 1
+            });
+            if let Some(npc_id) = npc_id {
+                interact(&mut game_io.root, game_state, objects, npc_id);
+                TookTurn
+            } else {
+                game_state
+                    .log
+                    .add("There is nobody here to talk to.", Severity::Info);
+                DidntTakeTurn
+            }
+        }
         (Key { printable: 'e', .. }, true) => {
-            // go down the stairs, if the player is on them
-            println!("trying to go down stairs");
-            let player_on_stairs = objects
-                .iter()
-                .any(|object| object.pos() == objects[PLAYER].pos() && object.name == "stairs");
-            if player_on_stairs {
+            // go up or down the stairs, if the player is on them
+            let player_on = |name: &str| {
+                objects
+                    .iter()
+                    .any(|object| object.pos() == objects[PLAYER].pos() && object.name == name)
+            };
+            if player_on("stairs") {
                 next_level(game_io, objects, game_state);
+            } else if player_on("up stairs") {
+                previous_level(game_io, objects, game_state);
+            } else if let Some(branch) = BRANCHES
+                .iter()
+                .position(|branch| player_on(&branch.entrance_name()))
+            {
+                enter_branch(game_io, objects, game_state, branch);
+            } else if player_on("sealed stairs") {
+                game_state.log.add(
+                    "The stairs are sealed while the boss lives.",
+                    Severity::Info,
+                );
+            }
+            DidntTakeTurn
+        }
+        (Key { printable: 'z', .. }, true) => {
+            // cast one of the spells the player knows, aimed at a monster or tile if needed
+            let spell = match spell_menu(game_state, objects, &mut game_io.root) {
+                Some(spell) => spell,
+                None => return DidntTakeTurn,
+            };
+            let target = match spell {
+                Spell::MagicMissile => {
+                    game_state.log.add(
+                        "Left-click an enemy to target it, or right-click to cancel.",
+                        Severity::Info,
+                    );
+                    match target_monster(game_io, game_state, objects, Some(MISSILE_RANGE)) {
+                        Some(target_id) => Some(objects[target_id].pos()),
+                        None => return DidntTakeTurn,
+                    }
+                }
+                Spell::Blink => {
+                    game_state.log.add(
+                        "Left-click a target tile, or right-click to cancel.",
+                        Severity::Info,
+                    );
+                    match target_tile(game_io, game_state, objects, Some(BLINK_RANGE)) {
+                        Some(target) => Some(target),
+                        None => return DidntTakeTurn,
+                    }
+                }
+                _ => None,
+            };
+            if cast_player_spell(game_state, objects, spell, target) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 'S', .. }, true) => {
+            // sprint a few tiles in one go
+            let direction = ask_direction("Sprint in which direction?", &mut game_io.root);
+            match direction {
+                Some((dx, dy)) if sprint(game_state, objects, dx, dy) => TookTurn,
+                _ => DidntTakeTurn,
+            }
+        }
+        (Key { printable: 'P', .. }, true) => {
+            // attack an adjacent creature with all might
+            let direction = ask_direction("Strike in which direction?", &mut game_io.root);
+            match direction {
+                Some((dx, dy)) if power_attack(game_state, objects, dx, dy) => TookTurn,
+                _ => DidntTakeTurn,
             }
+        }
+        (Key { printable: 'F', .. }, true) => {
+            // kick an adjacent creature away or a door open
+            let direction = ask_direction("Kick in which direction?", &mut game_io.root);
+            match direction {
+                Some((dx, dy)) if kick(game_state, objects, dx, dy) => TookTurn,
+                _ => DidntTakeTurn,
+            }
+        }
+        (Key { printable: 'I', .. }, true) => {
+            // douse the torch to hide in the dark, or light it again
+            let player = &mut objects[PLAYER];
+            if player.light_radius > 0 {
+                player.light_radius = 0;
+                game_state.log.add("You douse your torch.", Severity::Info);
+            } else {
+                player.light_radius = TORCH_LIGHT_RADIUS;
+                game_state.log.add("You light your torch.", Severity::Info);
+            }
+            TookTurn
+        }
+        (Key { printable: 'p', .. }, true) => {
+            // spend perk points saved from earlier level-ups
+            spend_perk_point(&mut game_io.root, game_state);
+            DidntTakeTurn
+        }
+        (Key { printable: 'E', .. }, true) => {
+            // eat a corpse lying under the player
+            if eat_corpse(game_state, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 'C', .. }, true) => {
+            // craft something from the materials in the inventory
+            if craft(&mut game_io.root, game_state, objects) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        }
+        (Key { printable: 'M', .. }, _) => {
+            // show all messages so far
+            message_log(&game_state.log, &mut game_io.root);
             DidntTakeTurn
         }
         (Key { printable: 'c', .. }, true) => {
             // show character information
             let player = &objects[PLAYER];
             let level = player.level;
-            let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
+            let level_up_xp = level_up_xp(player.level);
+            let perks: Vec<&str> = game_state.perks.iter().map(|perk| perk.name()).collect();
+            let perks = if perks.is_empty() {
+                "none".to_string()
+            } else {
+                perks.join(", ")
+            };
             if let Some(fighter) = player.fighter.as_ref() {
                 let msg = format!(
                     "Character information
 
+                Race: {}
                 Level: {}
                 Experience: {}
                 Experience to level up: {}
 
+                Strength: {}
+                Dexterity: {}
+                Intelligence: {}
+                Constitution: {}
+
                 Maximum HP: {}
+                Damage: {}
                 Attack: {}
-                Defense: {}",
+                Defense: {}
+                Sight: {}
+
+                Perks: {}
+                Perk points: {}
+
+                World seed: {}",
+                    game_state.race.name(),
                     level,
                     fighter.xp,
                     level_up_xp,
+                    game_state.attributes.strength,
+                    game_state.attributes.dexterity,
+                    game_state.attributes.intelligence,
+                    game_state.attributes.constitution,
                     player.max_hp(game_state),
+                    player.damage_dice(game_state),
                     player.power(game_state),
                     player.defense(game_state),
+                    sight_radius(game_state, player),
+                    perks,
+                    game_state.perk_points,
+                    game_state.seed,
                 );
                 msgbox(&msg, CHARACTER_SCREEN_WIDTH, &mut game_io.root);
             }
@@ -371,17 +833,261 @@ pub fn handle_keys(
     }
 }
 
-fn get_names_under_mouse(mouse: Mouse, objects: &[Object], fov_map: &FovMap) -> String {
-    let (x, y) = (mouse.cx as i32, mouse.cy as i32);
+/// Return the direction that the key stands for, if any. Directions are given by the arrow
+/// keys, the vi-keys `hjklyubn` and the numpad.
+fn key_direction(key: Key) -> Option<(i32, i32)> {
+    match key.code {
+        KeyCode::Up | KeyCode::NumPad8 => Some((0, -1)),
+        KeyCode::Down | KeyCode::NumPad2 => Some((0, 1)),
+        KeyCode::Left | KeyCode::NumPad4 => Some((-1, 0)),
+        KeyCode::Right | KeyCode::NumPad6 => Some((1, 0)),
+        KeyCode::NumPad7 => Some((-1, -1)),
+        KeyCode::NumPad9 => Some((1, -1)),
+        KeyCode::NumPad1 => Some((-1, 1)),
+        KeyCode::NumPad3 => Some((1, 1)),
+        KeyCode::Char => match key.printable {
+            'k' => Some((0, -1)),
+            'j' => Some((0, 1)),
+            'h' => Some((-1, 0)),
+            'l' => Some((1, 0)),
+            'y' => Some((-1, -1)),
+            'u' => Some((1, -1)),
+            'b' => Some((-1, 1)),
+            'n' => Some((1, 1)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Return the direction to run in that the key stands for, if any. Shift turns the vi-keys into
+/// upper case, so `HJKLYUBN` run just like shift and the arrow keys or the numpad.
+fn run_direction(key: Key) -> Option<(i32, i32)> {
+    match key.code {
+        KeyCode::Char if key.printable.is_ascii_uppercase() => key_direction(Key {
+            printable: key.printable.to_ascii_lowercase(),
+            ..key
+        }),
+        _ if key.shift => key_direction(key),
+        _ => None,
+    }
+}
+
+/// Start running in the given direction.
+fn run(
+    game_io: &mut GameIO,
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    dx: i32,
+    dy: i32,
+) -> PlayerAction {
+    if player_run(game_state, objects, &game_io.fov, dx, dy) {
+        PlayerAction::TookTurn
+    } else {
+        PlayerAction::DidntTakeTurn
+    }
+}
+
+/// Let the player pick a target tile for the item at the given position in the inventory and
+/// show the item flying there. Return the path of the item, or None if the player cancelled.
+fn aim_projectile(
+    game_io: &mut GameIO,
+    game_state: &mut GameState,
+    objects: &[Object],
+    inventory_id: usize,
+    range: f32,
+) -> Option<Vec<(i32, i32)>> {
+    game_state.log.add(
+        "Left-click a target tile, or right-click to cancel.",
+        Severity::Info,
+    );
+    let from = objects[PLAYER].pos();
+    let target = target_tile(game_io, game_state, objects, Some(range))?;
+    if target == from {
+        return None;
+    }
+    let path = trace_projectile(&game_state.world, objects, from, target);
+    let (chr, color) = {
+        let item = &game_state.inventory[inventory_id];
+        (item.chr, item.color)
+    };
+    animate_projectile(game_io, game_state, objects, &path, chr, color);
+    Some(path)
+}
+
+/// Show a projectile with the given looks flying along its path, one tile per frame.
+fn animate_projectile(
+    game_io: &mut GameIO,
+    game_state: &mut GameState,
+    objects: &[Object],
+    path: &[(i32, i32)],
+    chr: char,
+    color: Color,
+) {
+    for &(x, y) in path {
+        render_all(game_io, game_state, objects, false);
+        if in_view(&game_io.fov, game_state, &objects[PLAYER], x, y) {
+            game_io.root.set_default_foreground(color);
+            game_io.root.put_char(x, y, chr, BackgroundFlag::None);
+        }
+        game_io.root.flush();
+    }
+}
+
+/// Return true if the player can see the object: it lies in view and is not hidden, and if it is
+/// a creature, it stands in light or right next to the player.
+fn can_see(fov_map: &FovMap, game_state: &GameState, player: &Object, object: &Object) -> bool {
+    let in_light = object.fighter.is_none()
+        || game_state.world.get(object.x, object.y).light >= MIN_LIGHT
+        || player.distance_to(object) < game_state.race.night_sight();
+    let visible = !object.invisible || game_state.see_invisible > 0;
+    in_view(fov_map, game_state, player, object.x, object.y)
+        && !object.is_hidden()
+        && in_light
+        && visible
+}
+
+/// Return true if the player perceives the given tile. Blind players perceive nothing but the
+/// tile they stand on, and only remember the rest of the map.
+fn in_view(fov_map: &FovMap, game_state: &GameState, player: &Object, x: i32, y: i32) -> bool {
+    if game_state.blindness > 0 {
+        (x, y) == player.pos()
+    } else {
+        fov_map.is_in_fov(x, y)
+    }
+}
+
+/// Describe the tile under the mouse, if the player can see it.
+fn describe_under_mouse(
+    mouse: Mouse,
+    game_state: &GameState,
+    objects: &[Object],
+    fov_map: &FovMap,
+) -> String {
+    let (x, y) = (mouse.cx, mouse.cy);
+    let on_map = x >= 0 && y >= 0 && x < WORLD_WIDTH && y < WORLD_HEIGHT;
+    if on_map && in_view(fov_map, game_state, &objects[PLAYER], x, y) {
+        describe_tile(game_state, objects, fov_map, x, y)
+    } else {
+        String::new()
+    }
+}
 
-    // create a list with the names of all objects at the mouse's coordinates and in FOV
-    let names = objects
+/// Return a description of what the player knows about the tile: the creatures and items on it
+/// that the player can see, and the ground they lie on. Tiles out of view are described as they
+/// are remembered, without anything on them.
+fn describe_tile(
+    game_state: &GameState,
+    objects: &[Object],
+    fov_map: &FovMap,
+    x: i32,
+    y: i32,
+) -> String {
+    if x < 0 || y < 0 || x >= WORLD_WIDTH || y >= WORLD_HEIGHT {
+        return String::new();
+    }
+    let tile = game_state.world.get(x, y);
+    let player = &objects[PLAYER];
+    if !in_view(fov_map, game_state, player, x, y) {
+        return if tile.explored {
+            format!("You remember {} here.", tile.name())
+        } else {
+            "You don't know what is there.".to_string()
+        };
+    }
+    let mut names: Vec<String> = objects
         .iter()
-        .filter(|obj| obj.pos() == (x, y) && fov_map.is_in_fov(obj.x, obj.y))
-        .map(|obj| obj.name.clone())
-        .collect::<Vec<_>>();
+        .filter(|obj| obj.pos() == (x, y) && can_see(fov_map, game_state, player, obj))
+        .map(|obj| describe_object(game_state, obj))
+        .collect();
+    names.push(tile.name().to_string());
+    names.join(", ")
+}
+
+/// Return the name of the object, followed by the state of its health and everything else that
+/// can be told about it by looking, if it is a creature.
+fn describe_object(game_state: &GameState, object: &Object) -> String {
+    let name = if object.faction == Faction::Player {
+        "you".to_string()
+    } else {
+        object.stack_name()
+    };
+    let fighter = match object.fighter {
+        Some(fighter) if object.alive => fighter,
+        _ => return name,
+    };
+    let health = fighter.hp as f32 / object.max_hp(game_state).max(1) as f32;
+    let mut notes = vec![if health >= 1.0 {
+        "unhurt"
+    } else if health >= 0.66 {
+        "lightly wounded"
+    } else if health >= 0.33 {
+        "wounded"
+    } else {
+        "badly wounded"
+    }];
+    notes.extend(object.statuses.iter().map(|status| status.effect.name()));
+    match object.ai {
+        Some(Ai::Asleep { .. }) => notes.push("asleep"),
+        Some(_) if !object.aware => notes.push("unaware"),
+        _ => {}
+    }
+    format!("{} ({})", name, notes.join(", "))
+}
+
+/// Let the player move a cursor over the map with the arrow keys and describe whatever lies
+/// under it. Return the tile under the cursor if the player presses Enter to travel there, or
+/// `None` once Escape or 'x' is pressed.
+fn look(
+    game_io: &mut GameIO,
+    game_state: &mut GameState,
+    objects: &[Object],
+) -> Option<(i32, i32)> {
+    game_state.log.add(
+        "Move the cursor with the direction keys. Press Enter to travel there, or Escape to stop.",
+        Severity::Info,
+    );
+    let (mut x, mut y) = objects[PLAYER].pos();
+    loop {
+        render_all(game_io, game_state, objects, false);
+        game_io
+            .root
+            .set_char_background(x, y, colors::LIGHT_GREY, BackgroundFlag::Set);
+
+        // show the description at the top of the screen, above the map
+        let description = describe_tile(game_state, objects, &game_io.fov, x, y);
+        let height = game_io
+            .root
+            .get_height_rect(0, 0, SCREEN_WIDTH, 0, &description);
+        let mut window = Console::new(SCREEN_WIDTH, height);
+        window.set_default_foreground(colors::WHITE);
+        window.print_rect(0, 0, SCREEN_WIDTH, 0, description);
+        let window_y = if y < height {
+            SCREEN_HEIGHT - PANEL_HEIGHT - height
+        } else {
+            0
+        };
+        blit(
+            &window,
+            (0, 0),
+            (SCREEN_WIDTH, height),
+            &mut game_io.root,
+            (0, window_y),
+            1.0,
+            0.7,
+        );
+        game_io.root.flush();
 
-    names.join(", ") // return names separated by commas
+        let key = game_io.root.wait_for_keypress();
+        let (dx, dy) = match key.code {
+            KeyCode::Enter => return Some((x, y)),
+            KeyCode::Escape => return None,
+            KeyCode::Char if key.printable == 'x' => return None,
+            _ => key_direction(key).unwrap_or((0, 0)),
+        };
+        x = (x + dx).clamp(0, WORLD_WIDTH - 1);
+        y = (y + dy).clamp(0, WORLD_HEIGHT - 1);
+    }
 }
 
 /// return the position of a tile left-clicked in player's FOV (optionally in a range),
@@ -392,20 +1098,24 @@ pub fn target_tile(
     objects: &[Object],
     max_range: Option<f32>,
 ) -> Option<(i32, i32)> {
-    use tcod::input::KeyCode::Escape;
+    if game_state.blindness > 0 {
+        game_state
+            .log
+            .add("You cannot see where to aim.", Severity::Info);
+        return None;
+    }
     loop {
         // render the screen. this erases the inventory and shows the names of objects under the mouse
         game_io.root.flush();
-        let event = input::check_for_event(input::KEY_PRESS | input::MOUSE).map(|e| e.1);
         let mut key = None;
-        match event {
+        match game_io.root.check_for_event() {
             Some(Event::Mouse(m)) => game_io.mouse = m,
             Some(Event::Key(k)) => key = Some(k),
             None => {}
         }
         render_all(game_io, game_state, objects, false);
 
-        let (x, y) = (game_io.mouse.cx as i32, game_io.mouse.cy as i32);
+        let (x, y) = (game_io.mouse.cx, game_io.mouse.cy);
 
         // accept the target if the player clicked in FOV, and in case a range is specified, if it's in that range
         let in_fov = (x < WORLD_WIDTH) && (y < WORLD_HEIGHT) && game_io.fov.is_in_fov(x, y);
@@ -415,7 +1125,7 @@ pub fn target_tile(
             return Some((x, y));
         }
 
-        let escape = key.map_or(false, |k| k.code == Escape);
+        let escape = key.is_some_and(|k| k.code == KeyCode::Escape);
         if game_io.mouse.rbutton_pressed || escape {
             return None; // cancel if the player right-clicked or pressed Escape
         }
@@ -464,7 +1174,7 @@ pub fn menu<T: AsRef<str>>(
     let height = options.len() as i32 + header_height;
 
     // create an off-screen console that represents the menu's window
-    let mut window = Offscreen::new(width, height);
+    let mut window = Console::new(width, height);
 
     // print the header, with auto-wrap
     window.set_default_foreground(colors::WHITE);
@@ -494,11 +1204,11 @@ pub fn menu<T: AsRef<str>>(
     // blit contents of "window" to the root console
     let x = SCREEN_WIDTH / 2 - width / 2;
     let y = SCREEN_HEIGHT / 2 - height / 2;
-    tcod::console::blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
+    blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
 
     // present the root console to the player and wait for a key-press
     root.flush();
-    let key = root.wait_for_keypress(true);
+    let key = root.wait_for_keypress();
 
     // convert the ASCII code to and index; if if corresponds to an option, return it
     if key.printable.is_alphabetic() {
@@ -513,18 +1223,158 @@ pub fn menu<T: AsRef<str>>(
     }
 }
 
+/// Show a menu with all items of the inventory as options. Return the index of the chosen item,
+/// if any.
+fn inventory_menu(inventory: &[Object], header: &str, root: &mut Root) -> Option<usize> {
+    // show a menu with each item of the inventory as an option
+    let options = if inventory.is_empty() {
+        vec!["Inventory is empty.".into()]
+    } else {
+        inventory
+            .iter()
+            .map(|item| {
+                let mut name = item.stack_name();
+                if let Some(equipment) = item.equipment.as_ref() {
+                    if equipment.enchantment > 0 {
+                        name = format!("{} +{}", name, equipment.enchantment);
+                    }
+                    if let Some(damage) = equipment.damage {
+                        name = format!("{} ({})", name, damage);
+                    }
+                    if let Some(condition) = equipment.condition() {
+                        name = format!("{} ({})", name, condition);
+                    }
+                    if equipment.equipped {
+                        name = format!("{} (on {})", name, equipment.slot.name());
+                    }
+                }
+                if let Some(comparison) = compare(inventory, item) {
+                    name = format!("{} [{}]", name, comparison);
+                }
+                name
+            })
+            .collect()
+    };
+
+    let inventory_index = menu(header, &options, INVENTORY_WIDTH, root);
+
+    // if an item was chosen, return it
+    if inventory.is_empty() {
+        None
+    } else {
+        inventory_index
+    }
+}
+
+/// Show a menu of the spells the player knows and return the one chosen, if the player has
+/// enough mana to cast it.
+fn spell_menu(game_state: &mut GameState, objects: &[Object], root: &mut Root) -> Option<Spell> {
+    let caster = objects[PLAYER].caster.as_ref()?;
+    if caster.spells.is_empty() {
+        game_state
+            .log
+            .add("You don't know any spells.", Severity::Info);
+        return None;
+    }
+    let options: Vec<String> = caster
+        .spells
+        .iter()
+        .map(|spell| format!("{} ({} mana)", spell.name(), spell.cost()))
+        .collect();
+    let header = "Press the key next to a spell to cast it, or any other to cancel.\n";
+    let spell = caster.spells[menu(header, &options, INVENTORY_WIDTH, root)?];
+    if caster.mana < spell.cost() {
+        game_state
+            .log
+            .add("You don't have enough mana.", Severity::Info);
+        return None;
+    }
+    Some(spell)
+}
+
 fn msgbox(text: &str, width: i32, root: &mut Root) {
     let options: &[&str] = &[];
     menu(text, options, width, root);
 }
 
-pub fn main_menu(game_io: &mut GameIO) {
-    let img = tcod::image::Image::from_file("assets/menu_background.png")
-        .expect("Background image not found");
+/// Show all messages of the game on the whole screen, newest at the bottom. The player scrolls
+/// with the arrow keys, page up and down, and home and end. Any other key closes the log.
+fn message_log(messages: &Messages, root: &mut Root) {
+    let lines: Vec<(String, Color)> = messages
+        .iter()
+        .flat_map(|message| {
+            let color = message.color;
+            wrap(&message.display(), SCREEN_WIDTH - 2)
+                .into_iter()
+                .map(move |line| (line, color))
+        })
+        .collect();
+    let max_scroll = (lines.len() as i32 - LOG_HEIGHT).max(0);
+    // number of lines that the view is scrolled up from the newest message
+    let mut scroll = 0;
+    loop {
+        root.set_default_background(colors::BLACK);
+        root.clear();
+        root.set_default_foreground(colors::WHITE);
+        root.print_ex(
+            1,
+            0,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            "Message log (arrows, page up/down, home/end to scroll, any other key to close)",
+        );
+        let first = (lines.len() as i32 - LOG_HEIGHT - scroll).max(0);
+        for (y, &(ref line, color)) in lines
+            .iter()
+            .skip(first as usize)
+            .take(LOG_HEIGHT as usize)
+            .enumerate()
+        {
+            root.set_default_foreground(color);
+            root.print_ex(
+                1,
+                y as i32 + 2,
+                BackgroundFlag::None,
+                TextAlignment::Left,
+                line,
+            );
+        }
+        root.flush();
+
+        scroll = match root.wait_for_keypress().code {
+            KeyCode::Up => scroll + 1,
+            KeyCode::Down => scroll - 1,
+            KeyCode::PageUp => scroll + LOG_HEIGHT,
+            KeyCode::PageDown => scroll - LOG_HEIGHT,
+            KeyCode::Home => max_scroll,
+            KeyCode::End => 0,
+            _ => return,
+        }
+        .max(0)
+        .min(max_scroll);
+    }
+}
+
+/// Show the given prompt and wait for the player to choose a direction with one of the
+/// direction keys. Return `None` if any other key is pressed.
+fn ask_direction(prompt: &str, root: &mut Root) -> Option<(i32, i32)> {
+    let width = prompt.len() as i32;
+    let mut window = Console::new(width, 1);
+    window.set_default_foreground(colors::WHITE);
+    window.print_ex(0, 0, BackgroundFlag::None, TextAlignment::Left, prompt);
+    let x = SCREEN_WIDTH / 2 - width / 2;
+    blit(&window, (0, 0), (width, 1), root, (x, 0), 1.0, 0.7);
+    root.flush();
+
+    key_direction(root.wait_for_keypress())
+}
 
+/// Show the main menu. New games use the given world seed, if any.
+pub fn main_menu(game_io: &mut GameIO, seed: Option<u64>) {
     while !game_io.root.window_closed() {
         // show the background image, at twice the regular console resolution
-        tcod::image::blit_2x(&img, (0, 0), (-1, -1), &mut game_io.root, (0, 0));
+        game_io.root.clear();
+        game_io.root.draw_image("assets/menu_background.png");
 
         game_io.root.set_default_foreground(colors::LIGHT_YELLOW);
         game_io.root.print_ex(
@@ -548,8 +1398,18 @@ pub fn main_menu(game_io: &mut GameIO) {
 
         match choice {
             Some(0) => {
-                // start new game
-                let (mut objects, mut game_state) = new_game(game_io);
+                // choose a race and start new game
+                let races: Vec<&str> = RACES.iter().map(|race| race.description()).collect();
+                let race = match menu(
+                    "Choose your race:\n",
+                    &races,
+                    INVENTORY_WIDTH,
+                    &mut game_io.root,
+                ) {
+                    Some(race) => RACES[race],
+                    None => continue,
+                };
+                let (mut objects, mut game_state) = new_game(game_io, seed, race);
                 game_loop(&mut objects, &mut game_state, game_io);
             }
             Some(1) => {
@@ -3,70 +3,194 @@
 /// This module contains the struct that encompasses all parts of the game state:
 ///
 /// TODO: Try to move as many dependecies to game_io as possible out of here.
-use tcod::input::{self, Event, Key};
-use tcod::{colors, Console};
+use rand::Rng;
+use std::mem;
 
 // internal modules
-use entity::ai::ai_take_turn;
+use backend::colors;
+use backend::input::{Event, Key, KeyCode};
+use entity::ai::{ai_take_turn, wake_up, Ai};
+use entity::attributes::{raise_attribute, Attributes, ATTRIBUTES, ATTRIBUTE_PER_LEVEL};
+use entity::champion::Affix;
+use entity::container::open_container;
+use entity::corpse::decay_corpses;
+use entity::dice::Dice;
+use entity::equipment::wield_best_weapon;
+use entity::experience::level_up_xp;
+use entity::faction::Faction;
 use entity::fighter::{DeathCallback, Fighter};
-use entity::object::Object;
+use entity::hunger::{update_hunger, Hunger, MAX_NUTRITION};
+use entity::item::{add_to_inventory, can_carry, drop_onto_floor, take_one, Item};
+use entity::knockback::{resolve_knockbacks, Knockback};
+use entity::npc::provoke;
+use entity::object::{MoveMode, Object};
+use entity::perk::{has_perk, spend_perk_point, Perk};
+use entity::polymorph::{lasting_form, update_polymorph};
+use entity::race::Race;
+use entity::spawner::update_spawners;
+use entity::spell::{Caster, Spell, PLAYER_MANA, PLAYER_SPELLS};
+use entity::stamina::{max_stamina, recover_stamina};
+use entity::status::{has_status, is_incapacitated, update_statuses, Effect};
+use entity::stealth::NOISE_MEMORY;
+use entity::trap::{reveal_trap, trigger_trap};
 use game_io::{
-    handle_keys, initialize_fov, menu, render_all, save_game, GameIO, MessageLog, Messages,
-    PlayerAction,
+    handle_keys, initialize_fov, menu, render_all, save_game, FovMap, GameIO, MessageLog, Messages,
+    PlayerAction, Severity,
 };
 use util::mut_two;
-use world::{is_blocked, make_world, World};
+use world::artifact::claim_artifacts;
+use world::dijkstra::DijkstraMap;
+use world::noise::{spread_noise, Noise, DIG_NOISE, DOOR_NOISE};
+use world::{
+    find_known_path, is_blocked, is_blocked_for, make_item, make_monster, make_world, Door,
+    LevelId, Terrain, World, BRANCHES, FALL_DAMAGE, LAVA_DAMAGE,
+};
 
 // player object reference, index of the object vector
 pub const PLAYER: usize = 0;
-pub const TORCH_RADIUS: i32 = 10;
-// experience and level-ups
-pub const LEVEL_UP_BASE: i32 = 200;
-pub const LEVEL_UP_FACTOR: i32 = 150;
+// radius of the light of the torch the player carries
+pub const TORCH_LIGHT_RADIUS: i32 = 6;
 pub const LEVEL_SCREEN_WIDTH: i32 = 40;
+// chance per turn of searching to find each adjacent secret
+pub const SEARCH_CHANCE: f32 = 0.3;
+// how far a single stroke of a pickaxe digs into a wall
+pub const PICKAXE_STRENGTH: i32 = 1;
+// chance of finding ore in a wall that has been dug through
+const ORE_CHANCE: f32 = 0.3;
+// chance that a confused player staggers in a random direction instead of the intended one
+const STAGGER_CHANCE: f32 = 0.5;
+// noise made this close to the player makes it easier to notice
+const NOISY_DISTANCE: f32 = 2.0;
+// the kind of monster that the player starts out with as a companion
+const PET: &str = "lymphocyte";
+// turns of rest it takes to regain a hit point, and the most turns a single rest lasts
+const REST_HEAL_INTERVAL: i32 = 3;
+const MAX_REST_TURNS: i32 = 300;
 
 #[derive(Serialize, Deserialize)]
 pub struct GameState {
     pub world: World,
     pub log: Messages,
     pub inventory: Vec<Object>,
-    pub dungeon_level: u32,
+    // the level the player is on
+    pub level: LevelId,
+    pub seed: u64,
+    // levels the player has left
+    pub levels: Vec<Level>,
+    // the remaining path the player is travelling along automatically
+    pub travel: Vec<(i32, i32)>,
+    // noise made since sleeping monsters last listened
+    pub noises: Vec<Noise>,
+    // pushes that attacks have dealt out but that have not been carried out yet
+    pub knockbacks: Vec<Knockback>,
+    // turns during which the player can see invisible creatures
+    pub see_invisible: i32,
+    // how long the player can go on without eating
+    pub nutrition: i32,
+    // turns during which the player acts twice per turn
+    pub speed: i32,
+    // turns during which the player perceives nothing but the tile it stands on
+    pub blindness: i32,
+    // names of the artifacts that have been generated so far
+    pub artifacts: Vec<String>,
+    // coins the player has collected, which take up no room in the inventory
+    pub gold: i32,
+    pub race: Race,
+    pub attributes: Attributes,
+    pub perks: Vec<Perk>,
+    // perk points gained on leveling up that have not been spent yet
+    pub perk_points: i32,
+    pub stamina: i32,
+    // turns during which monsters notice the player more easily, because it has made noise
+    pub noisy: i32,
+    // turns the player has spent resting so far, if it is resting
+    pub resting: Option<i32>,
+    // direction the player is running in, if it is running
+    pub running: Option<(i32, i32)>,
+    // maximum hit points that draining monsters have taken from the player
+    pub drained: i32,
+    // whether the player has used up stamina during this turn, which keeps it from recovering
+    pub exerted: bool,
 }
 
-pub fn new_game(game_io: &mut GameIO) -> (Vec<Object>, GameState) {
+/// A level that the player has left, stored so that it can be revisited exactly as it was.
+#[derive(Serialize, Deserialize)]
+pub struct Level {
+    pub id: LevelId,
+    pub world: World,
+    pub objects: Vec<Object>, // all objects of the level, except for the player
+}
+
+/// Start a new game with a player of the given race. Without a given seed, a random one is used
+/// to generate the world.
+pub fn new_game(game_io: &mut GameIO, seed: Option<u64>, race: Race) -> (Vec<Object>, GameState) {
     // create object representing the player
     let mut player = Object::new(0, 0, "player", true, '@', colors::WHITE);
     player.alive = true;
+    player.faction = Faction::Player;
+    player.light_radius = TORCH_LIGHT_RADIUS;
     player.fighter = Some(Fighter {
         base_max_hp: 100,
         hp: 100,
         base_defense: 1,
-        base_power: 2,
+        base_power: 0,
+        damage: Dice::new(1, 3, 0),
         on_death: DeathCallback::Player,
         xp: 0,
     });
+    player.caster = Some(Caster::new(vec![Spell::MagicMissile], PLAYER_MANA));
+    let mut attributes = Attributes::default();
+    race.apply(&mut player, &mut attributes);
 
     // create array holding all objects
     let mut objects = vec![player];
-    let level = 1;
+    let level = LevelId::new(0, 1);
+    let seed = seed.unwrap_or_else(rand::random);
 
     // create game state holding most game-relevant information
     //  - also creates map and player starting position
     let mut game_state = GameState {
         // generate map (at this point it's not drawn on screen)
-        world: make_world(&mut objects, level),
+        world: make_world(&mut objects, seed, level),
         // create the list of game messages and their colors, starts empty
         log: vec![],
         inventory: vec![],
-        dungeon_level: 1,
+        level,
+        seed,
+        levels: vec![],
+        travel: vec![],
+        noises: vec![],
+        knockbacks: vec![],
+        see_invisible: 0,
+        nutrition: MAX_NUTRITION,
+        speed: 0,
+        blindness: 0,
+        artifacts: vec![],
+        gold: 0,
+        race,
+        attributes,
+        perks: vec![],
+        perk_points: 0,
+        stamina: 0,
+        noisy: 0,
+        resting: None,
+        running: None,
+        drained: 0,
+        exerted: false,
     };
+    game_state.stamina = max_stamina(&game_state);
+
+    // a loyal companion accompanies the player from the start
+    if let Some(pet) = make_monster(PET, 0, 0) {
+        place_near_player(&game_state.world, &mut objects, pet);
+    }
 
     initialize_fov(&game_state.world, game_io);
 
     // a warm welcoming message
     game_state.log.add(
         "Welcome microbe! You're innit now. Beware of bacteria and viruses",
-        colors::RED,
+        Severity::Info,
     );
 
     (objects, game_state)
@@ -79,23 +203,29 @@ pub fn new_game(game_io: &mut GameIO) -> (Vec<Object>, GameState) {
 pub fn game_loop(objects: &mut Vec<Object>, game_state: &mut GameState, game_io: &mut GameIO) {
     // force FOV "recompute" first time through the game loop
     let mut previous_player_position = (-1, -1);
-
-    // input processing
-    let mut key: Key = Default::default();
+    let mut world_changed = true;
 
     while !game_io.root.window_closed() {
         // clear the screen of the previous frame
         game_io.con.clear();
 
         // check for input events
-        match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
-            Some((_, Event::Mouse(m))) => game_io.mouse = m,
-            Some((_, Event::Key(k))) => key = k,
-            _ => key = Default::default(),
-        }
+        let key: Key = match game_io.root.check_for_event() {
+            Some(Event::Mouse(m)) => {
+                game_io.mouse = m;
+                // travel to the explored tile the player clicked on
+                if m.lbutton_pressed && objects[PLAYER].alive {
+                    player_travel_to(game_state, objects, (m.cx, m.cy));
+                }
+                Default::default()
+            }
+            Some(Event::Key(k)) => k,
+            None => Default::default(),
+        };
 
         // render objects and map
-        let fov_recompute = previous_player_position != (objects[PLAYER].x, objects[PLAYER].y);
+        let fov_recompute =
+            world_changed || previous_player_position != (objects[PLAYER].x, objects[PLAYER].y);
         render_all(game_io, game_state, &objects, fov_recompute);
 
         // draw everything on the window at once
@@ -106,32 +236,336 @@ pub fn game_loop(objects: &mut Vec<Object>, game_state: &mut GameState, game_io:
 
         // handle keys and exit game if needed
         previous_player_position = objects[PLAYER].pos();
-        let player_action = handle_keys(game_io, game_state, objects, key);
+        // keep travelling until the player presses a key
+        let player_action = if key.code == KeyCode::NoKey && !game_state.travel.is_empty() {
+            player_travel_step(game_state, objects, &game_io.fov)
+        } else if key.code == KeyCode::NoKey && game_state.resting.is_some() {
+            player_rest_step(game_state, objects, &game_io.fov)
+        } else if key.code == KeyCode::NoKey && game_state.running.is_some() {
+            player_run_step(game_state, objects, &game_io.fov)
+        } else {
+            game_state.travel.clear();
+            game_state.resting = None;
+            game_state.running = None;
+            handle_keys(game_io, game_state, objects, key)
+        };
         if player_action == PlayerAction::Exit {
             save_game(objects, game_state).unwrap();
             break;
         }
+        resolve_knockbacks(game_state, objects);
+        // any turn may change the world, e.g. by opening or closing doors
+        world_changed = player_action != PlayerAction::DidntTakeTurn;
+        if world_changed {
+            let (x, y) = objects[PLAYER].pos();
+            game_state.world.scent.update(x, y);
+        }
+        if world_changed && objects[PLAYER].pos() != previous_player_position {
+            spring_traps(game_state, objects, &game_io.fov, PLAYER);
+            let (x, y) = objects[PLAYER].pos();
+            if objects[PLAYER].alive && game_state.world.get(x, y).terrain == Terrain::Chasm {
+                fall_into_chasm(game_io, objects, game_state);
+            }
+        }
 
-        // let monsters take their turn
-        if objects[PLAYER].alive && player_action != PlayerAction::DidntTakeTurn {
-            for id in 0..objects.len() {
-                if objects[id].ai.is_some() {
-                    ai_take_turn(game_state, objects, &game_io.fov, id);
+        // a fast player gets every other turn for free
+        let free_turn = player_action != PlayerAction::DidntTakeTurn && game_state.speed % 2 == 1;
+        if player_action != PlayerAction::DidntTakeTurn && game_state.speed > 0 {
+            game_state.speed -= 1;
+            if game_state.speed == 0 {
+                game_state
+                    .log
+                    .add("You feel yourself slow down.", Severity::Info);
+            }
+        }
+
+        // let monsters take their turn, more than once while the player is slowed by terrain
+        if objects[PLAYER].alive && player_action != PlayerAction::DidntTakeTurn && !free_turn {
+            drop_inventories(objects);
+            // the way to the player, shared by all monsters that walk
+            let player_map =
+                DijkstraMap::new(&game_state.world, &[objects[PLAYER].pos()], MoveMode::Walk);
+            loop {
+                wake_monsters(game_state, objects, &game_io.fov);
+                for id in 0..objects.len() {
+                    // hastened and fast monsters act twice per turn
+                    let fast = objects[id].affixes.contains(&Affix::Fast);
+                    let actions = if objects[id].haste > 0 || fast { 2 } else { 1 };
+                    if objects[id].haste > 0 {
+                        objects[id].haste -= 1;
+                    }
+                    for _ in 0..actions {
+                        if objects[id].ai.is_some() {
+                            let previous_position = objects[id].pos();
+                            ai_take_turn(game_state, objects, &game_io.fov, &player_map, id);
+                            resolve_knockbacks(game_state, objects);
+                            if objects[id].pos() != previous_position {
+                                spring_traps(game_state, objects, &game_io.fov, id);
+                            }
+                        }
+                    }
+                }
+                update_world(game_state, objects, &game_io.fov);
+                // frozen or stunned players let the monsters act until they recover
+                let helpless = is_incapacitated(&objects[PLAYER]);
+                if (objects[PLAYER].delay == 0 && !helpless) || !objects[PLAYER].alive {
+                    break;
                 }
+                if objects[PLAYER].delay > 0 {
+                    objects[PLAYER].delay -= 1;
+                }
+            }
+        }
+        if world_changed {
+            unseal_stairs(objects);
+        }
+    }
+}
+
+/// Let a turn pass in the world, independent of what the player and monsters did.
+fn update_world(game_state: &mut GameState, objects: &mut Vec<Object>, fov_map: &FovMap) {
+    apply_terrain_effects(game_state, objects, fov_map);
+    apply_affixes_and_statuses(game_state, objects, fov_map);
+    if objects[PLAYER].alive {
+        update_hunger(game_state, &mut objects[PLAYER]);
+        if let Some(caster) = objects[PLAYER].caster.as_mut() {
+            caster.recover();
+        }
+        recover_stamina(game_state);
+    }
+    if game_state.noisy > 0 {
+        game_state.noisy -= 1;
+    }
+    if game_state.blindness > 0 {
+        game_state.blindness -= 1;
+        if game_state.blindness == 0 {
+            game_state.log.add("You can see again.", Severity::Good);
+        }
+    }
+    if game_state.see_invisible > 0 {
+        game_state.see_invisible -= 1;
+        if game_state.see_invisible == 0 {
+            game_state
+                .log
+                .add("Your sight returns to normal.", Severity::Good);
+        }
+    }
+    drop_inventories(objects);
+    monsters_pick_up_items(game_state, objects, fov_map);
+    update_spawners(game_state, objects, fov_map);
+    decay_corpses(objects);
+}
+
+/// Let regenerating creatures regain a hit point and status effects take their course.
+/// Equipment that regenerates heals its wearer, or drains it if it is cursed.
+fn apply_affixes_and_statuses(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+) {
+    for object in objects.iter_mut().filter(|object| object.fighter.is_some()) {
+        if object.affixes.contains(&Affix::Regenerating) {
+            object.heal(game_state, 1);
+        }
+        let regeneration: i32 = object
+            .get_all_equipped(game_state)
+            .iter()
+            .map(|e| e.regeneration())
+            .sum();
+        if regeneration > 0 {
+            object.heal(game_state, regeneration);
+        } else if regeneration < 0 {
+            object.take_damage(-regeneration, game_state);
+        }
+        let visible = fov_map.is_in_fov(object.x, object.y);
+        update_polymorph(game_state, object, visible);
+        let name = object.visible_name(game_state);
+        let killer = match update_statuses(object, game_state, visible) {
+            Some(Effect::Poisoned) => "poison",
+            Some(Effect::Burning) => "fire",
+            _ => continue,
+        };
+        if visible {
+            game_state.log.add(
+                format!("The {} has killed {}.", killer, name),
+                Severity::Combat,
+            );
+        }
+    }
+}
+
+/// Drop everything that dead monsters carried onto the tile they died on.
+fn drop_inventories(objects: &mut Vec<Object>) {
+    let mut dropped = vec![];
+    for object in objects.iter_mut().filter(|object| !object.alive) {
+        let (x, y) = object.pos();
+        if let Some(inventory) = object.inventory.as_mut() {
+            for mut item in inventory.drain(..) {
+                item.set_pos(x, y);
+                dropped.push(item);
+            }
+        }
+    }
+    for item in dropped {
+        drop_onto_floor(objects, item);
+    }
+}
+
+/// Let living monsters that carry items pick up all items they are standing on.
+fn monsters_pick_up_items(game_state: &mut GameState, objects: &mut Vec<Object>, fov_map: &FovMap) {
+    let mut id = 0;
+    while id < objects.len() {
+        let (x, y) = objects[id].pos();
+        let item_id = objects
+            .iter()
+            .position(|object| object.item.is_some() && object.pos() == (x, y));
+        match item_id {
+            Some(item_id) if objects[id].alive && objects[id].inventory.is_some() => {
+                let item = objects.remove(item_id);
+                // removing the item moves all objects after it one index down
+                if item_id < id {
+                    id -= 1;
+                }
+                if fov_map.is_in_fov(x, y) {
+                    game_state.log.add(
+                        format!("The {} picks up a {}.", objects[id].name, item.stack_name()),
+                        Severity::Info,
+                    );
+                }
+                let wielded = objects[id].inventory.as_mut().and_then(|inventory| {
+                    add_to_inventory(inventory, item);
+                    wield_best_weapon(inventory)
+                });
+                if let Some(weapon) = wielded.filter(|_| fov_map.is_in_fov(x, y)) {
+                    game_state.log.add(
+                        format!("The {} wields the {}.", objects[id].name, weapon),
+                        Severity::Warning,
+                    );
+                }
+            }
+            _ => id += 1,
+        }
+    }
+}
+
+/// Wake up all sleeping monsters that hear any of the noise made since the last time.
+fn wake_monsters(game_state: &mut GameState, objects: &mut [Object], fov_map: &FovMap) {
+    for noise in mem::take(&mut game_state.noises) {
+        if objects[PLAYER].distance(noise.x, noise.y) <= NOISY_DISTANCE {
+            game_state.noisy = NOISE_MEMORY;
+        }
+        let loudness = spread_noise(&game_state.world, noise);
+        for monster in objects.iter_mut() {
+            let (x, y) = monster.pos();
+            let index = (y * game_state.world.width + x) as usize;
+            if loudness[index] > 0
+                && wake_up(monster)
+                && fov_map.is_in_fov(x, y)
+                && monster.is_perceptible(game_state)
+            {
+                game_state
+                    .log
+                    .add(format!("The {} wakes up!", monster.name), Severity::Warning);
+            }
+        }
+    }
+}
+
+/// Turn sealed stairs into regular ones once no boss is left alive on the level.
+fn unseal_stairs(objects: &mut [Object]) {
+    let boss_alive = objects.iter().any(|object| {
+        object
+            .fighter
+            .is_some_and(|fighter| fighter.on_death == DeathCallback::Boss)
+    });
+    if !boss_alive {
+        for object in objects
+            .iter_mut()
+            .filter(|object| object.name == "sealed stairs")
+        {
+            object.name = "stairs".into();
+            object.color = colors::WHITE;
+        }
+    }
+}
+
+/// Burn every creature that stands in lava.
+fn apply_terrain_effects(game_state: &mut GameState, objects: &mut [Object], fov_map: &FovMap) {
+    for (id, object) in objects.iter_mut().enumerate() {
+        let (x, y) = object.pos();
+        if object.alive
+            && object.fighter.is_some()
+            && game_state.world.get(x, y).terrain == Terrain::Lava
+        {
+            if id == PLAYER {
+                game_state.log.add("The lava burns you!", Severity::Danger);
+            } else if fov_map.is_in_fov(x, y) {
+                game_state.log.add(
+                    format!("The {} is burned by the lava!", object.name),
+                    Severity::Combat,
+                );
             }
+            object.take_damage(LAVA_DAMAGE, game_state);
+        }
+    }
+}
+
+/// Let the player fall down into the next level. If possible, the player lands at the same
+/// position one level below, otherwise at the up stairs.
+fn fall_into_chasm(game_io: &mut GameIO, objects: &mut Vec<Object>, game_state: &mut GameState) {
+    game_state
+        .log
+        .add("You fall down into the chasm!", Severity::Danger);
+    let (x, y) = objects[PLAYER].pos();
+    let next = game_state.level.below();
+    change_level(game_io, objects, game_state, next, "up stairs");
+    if !is_blocked(&game_state.world, objects, x, y) && !game_state.world.get(x, y).is_hazardous() {
+        objects[PLAYER].set_pos(x, y);
+    }
+    objects[PLAYER].take_damage(FALL_DAMAGE, game_state);
+}
+
+/// Trigger all traps at the position of the object with the given id.
+fn spring_traps(game_state: &mut GameState, objects: &mut [Object], fov_map: &FovMap, id: usize) {
+    let (x, y) = objects[id].pos();
+    let visible = fov_map.is_in_fov(x, y);
+    for trap_id in 0..objects.len() {
+        if trap_id != id && objects[trap_id].trap.is_some() && objects[trap_id].pos() == (x, y) {
+            let (trap, victim) = mut_two(objects, trap_id, id);
+            trigger_trap(game_state, trap, victim, id, visible);
         }
     }
 }
 
-pub fn move_by(world: &World, objects: &mut [Object], id: usize, dx: i32, dy: i32) {
+pub fn move_by(world: &mut World, objects: &mut [Object], id: usize, dx: i32, dy: i32) {
     // move by the given amount
     let (x, y) = objects[id].pos();
-    if !is_blocked(world, objects, x + dx, y + dy) {
+    let phasing = objects[id].move_mode == MoveMode::Phase;
+    if !world.in_bounds(x + dx, y + dy) {
+        // nobody leaves the world
+    } else if world.get(x + dx, y + dy).door == Some(Door::Closed) && !phasing {
+        // bumping into a closed door opens it
+        world.get_mut(x + dx, y + dy).set_door(Door::Open);
+    } else if id != PLAYER && world.get(x + dx, y + dy).is_hazardous() {
+        // monsters don't walk into lava or chasms on purpose
+    } else if !is_blocked_for(world, objects, x + dx, y + dy, objects[id].move_mode) {
         objects[id].set_pos(x + dx, y + dy);
+        objects[id].delay = world.get(x + dx, y + dy).move_cost - 1;
     }
 }
 
 pub fn player_move_or_attack(game_state: &mut GameState, objects: &mut [Object], dx: i32, dy: i32) {
+    let mut rng = rand::thread_rng();
+    let stagger = (rng.gen_range(-1, 2), rng.gen_range(-1, 2));
+    let (dx, dy) = if has_status(&objects[PLAYER], Effect::Confused)
+        && rng.gen::<f32>() < STAGGER_CHANCE
+        && stagger != (0, 0)
+    {
+        game_state.log.add("You stagger about.", Severity::Warning);
+        stagger
+    } else {
+        (dx, dy)
+    };
     // the coordinate the player is moving to/attacking
     let x = objects[PLAYER].x + dx;
     let y = objects[PLAYER].y + dy;
@@ -140,21 +574,445 @@ pub fn player_move_or_attack(game_state: &mut GameState, objects: &mut [Object],
     let target_id = objects
         .iter()
         .position(|object| object.fighter.is_some() && object.pos() == (x, y));
+    let container_id = objects.iter().position(|object| {
+        object.container.is_some() && object.item.is_none() && object.pos() == (x, y)
+    });
 
-    // attack if target found, move otherwise
-    match target_id {
-        Some(target_id) => {
+    // attack if target found, open containers, move otherwise
+    match (target_id, container_id) {
+        // companions are not attacked, but make way for the player
+        (Some(target_id), _) if objects[target_id].ai == Some(Ai::Companion) => {
+            let (player_x, player_y) = objects[PLAYER].pos();
+            objects[target_id].set_pos(player_x, player_y);
+            objects[PLAYER].set_pos(x, y);
+            game_state.log.add(
+                format!("You swap places with your {}.", objects[target_id].name),
+                Severity::Info,
+            );
+        }
+        (Some(target_id), _) => {
             let (player, target) = mut_two(objects, PLAYER, target_id);
+            if target.faction == Faction::Neutral {
+                provoke(game_state, target);
+            }
             player.attack(target, game_state);
+            if !target.alive && has_perk(game_state, Perk::Cleave) {
+                cleave(game_state, objects);
+            }
+        }
+        (None, Some(container_id)) => {
+            open_container(game_state, &mut objects[container_id]);
+        }
+        (None, None) => match game_state.world.get(x, y).door {
+            Some(Door::Locked) => {
+                unlock_door(game_state, x, y);
+            }
+            Some(Door::Closed) => {
+                game_state.log.add("You open the door.", Severity::Info);
+                move_by(&mut game_state.world, objects, PLAYER, dx, dy);
+            }
+            _ => {
+                move_by(&mut game_state.world, objects, PLAYER, dx, dy);
+            }
+        },
+    }
+    if has_perk(game_state, Perk::SureFooted) {
+        objects[PLAYER].delay = 0;
+    }
+}
+
+/// Follow up a killing blow by striking another hostile creature next to the player, if there
+/// is one.
+fn cleave(game_state: &mut GameState, objects: &mut [Object]) {
+    let (x, y) = objects[PLAYER].pos();
+    let target_id = objects.iter().position(|object| {
+        object.alive
+            && object.fighter.is_some()
+            && object.faction.is_hostile_to(Faction::Player)
+            && (object.x - x).abs() <= 1
+            && (object.y - y).abs() <= 1
+    });
+    if let Some(target_id) = target_id {
+        game_state.log.add(
+            format!("You cleave into the {}!", objects[target_id].name),
+            Severity::Combat,
+        );
+        let (player, target) = mut_two(objects, PLAYER, target_id);
+        player.attack(target, game_state);
+    }
+}
+
+/// Try to unlock and open the locked door at the given position, using up a key from the
+/// player's inventory. Return true if the door was unlocked.
+fn unlock_door(game_state: &mut GameState, x: i32, y: i32) -> bool {
+    let key_id = game_state
+        .inventory
+        .iter()
+        .position(|object| object.item == Some(Item::Key));
+    match key_id {
+        Some(key_id) => {
+            take_one(&mut game_state.inventory, key_id);
+            game_state.world.get_mut(x, y).set_door(Door::Open);
+            game_state
+                .log
+                .add("You unlock the door. The key breaks.", Severity::Info);
+            true
         }
         None => {
-            move_by(&game_state.world, objects, PLAYER, dx, dy);
+            game_state
+                .log
+                .add("The door is locked. You need a key.", Severity::Info);
+            false
+        }
+    }
+}
+
+/// Dig into the tile next to the player in the given direction, using a pickaxe from the
+/// player's inventory. Return true if the player took a swing.
+pub fn player_dig(game_state: &mut GameState, objects: &[Object], dx: i32, dy: i32) -> bool {
+    let has_pickaxe = game_state
+        .inventory
+        .iter()
+        .any(|object| object.item == Some(Item::Pickaxe));
+    if !has_pickaxe {
+        game_state
+            .log
+            .add("You need a pickaxe to dig.", Severity::Info);
+        return false;
+    }
+
+    let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+    let tile = game_state.world.get(x, y);
+    let solid =
+        (tile.terrain == Terrain::Wall || tile.terrain == Terrain::Pillar) && tile.door.is_none();
+    if !solid {
+        game_state
+            .log
+            .add("There is nothing to dig there.", Severity::Info);
+        false
+    } else if !tile.is_diggable() {
+        game_state
+            .log
+            .add("The rock here is too hard to dig through.", Severity::Info);
+        false
+    } else {
+        game_state.noises.push(Noise::new(x, y, DIG_NOISE));
+        if game_state.world.get_mut(x, y).dig(PICKAXE_STRENGTH) {
+            game_state.log.add("You dig through!", Severity::Info);
+            if rand::random::<f32>() < ORE_CHANCE {
+                find_ore(game_state);
+            }
+        } else {
+            game_state.log.add("You dig into the rock.", Severity::Info);
+        }
+        true
+    }
+}
+
+/// Put a lump of ore that the player has dug out into its inventory, if there is room for it.
+fn find_ore(game_state: &mut GameState) {
+    let ore = match make_item("mineral ore", 0, 0) {
+        Some(ore) => ore,
+        None => return,
+    };
+    if can_carry(game_state, &ore) {
+        game_state
+            .log
+            .add("You find a lump of mineral ore.", Severity::Good);
+        add_to_inventory(&mut game_state.inventory, ore);
+    }
+}
+
+/// Start travelling to the stairs down, if the player has already found them.
+pub fn player_travel_to_stairs(game_state: &mut GameState, objects: &[Object]) {
+    let stairs = objects
+        .iter()
+        .find(|object| object.name == "stairs" && game_state.world.get(object.x, object.y).explored)
+        .map(|object| object.pos());
+    match stairs {
+        Some(stairs) => player_travel_to(game_state, objects, stairs),
+        None => game_state
+            .log
+            .add("You have not found the stairs yet.", Severity::Info),
+    }
+}
+
+/// Start travelling to the given tile along a path across explored tiles. The tile must have
+/// been explored as well.
+pub fn player_travel_to(game_state: &mut GameState, objects: &[Object], target: (i32, i32)) {
+    let (x, y) = target;
+    if !game_state.world.in_bounds(x, y) || target == objects[PLAYER].pos() {
+        return;
+    }
+    if !game_state.world.get(x, y).explored {
+        game_state
+            .log
+            .add("You don't know the way there.", Severity::Info);
+        return;
+    }
+    game_state.travel = find_known_path(
+        &game_state.world,
+        objects,
+        objects[PLAYER].pos(),
+        target,
+        objects[PLAYER].move_mode,
+    );
+    if game_state.travel.is_empty() {
+        game_state.log.add("You see no way there.", Severity::Info);
+    }
+}
+
+/// Take the next step along the path the player is travelling. Travelling stops when a monster
+/// comes into view or the way is blocked.
+fn player_travel_step(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+) -> PlayerAction {
+    let monster_in_view = enemy_in_view(objects, fov_map);
+    let (x, y) = game_state.travel[0];
+    let door_ahead = game_state.world.get(x, y).door == Some(Door::Closed);
+    if monster_in_view || (is_blocked(&game_state.world, objects, x, y) && !door_ahead) {
+        game_state.travel.clear();
+        return PlayerAction::DidntTakeTurn;
+    }
+
+    let (player_x, player_y) = objects[PLAYER].pos();
+    player_move_or_attack(game_state, objects, x - player_x, y - player_y);
+    // the first step towards a closed door only opens it
+    if objects[PLAYER].pos() == (x, y) {
+        game_state.travel.remove(0);
+    }
+    PlayerAction::TookTurn
+}
+
+/// Return true if a creature hostile to the player is in view.
+fn enemy_in_view(objects: &[Object], fov_map: &FovMap) -> bool {
+    objects.iter().any(|object| {
+        object.ai.is_some()
+            && object.faction.is_hostile_to(Faction::Player)
+            && fov_map.is_in_fov(object.x, object.y)
+    })
+}
+
+/// Return true if the player has fully recovered its hit points, mana and stamina.
+fn is_rested(game_state: &GameState, player: &Object) -> bool {
+    let hp = player.fighter.map_or(0, |f| f.hp);
+    let mana = player.caster.as_ref().is_none_or(|c| c.mana >= c.max_mana);
+    hp >= player.max_hp(game_state) && mana && game_state.stamina >= max_stamina(game_state)
+}
+
+/// Start resting, which passes turn after turn until the player has recovered or is
+/// interrupted. Return true if the player has started resting.
+pub fn player_rest(game_state: &mut GameState, objects: &[Object], fov_map: &FovMap) -> bool {
+    if enemy_in_view(objects, fov_map) {
+        game_state
+            .log
+            .add("You cannot rest with enemies nearby.", Severity::Info);
+        return false;
+    }
+    if is_rested(game_state, &objects[PLAYER]) {
+        game_state
+            .log
+            .add("You are already fully rested.", Severity::Info);
+        return false;
+    }
+    game_state.log.add("You sit down to rest.", Severity::Info);
+    game_state.resting = Some(0);
+    true
+}
+
+/// Rest for a single turn. Resting stops when an enemy comes into view, the player grows weak
+/// with hunger or has recovered, which is checked before every turn.
+fn player_rest_step(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+) -> PlayerAction {
+    let turns = game_state.resting.unwrap_or(0);
+    let hunger = Hunger::from_nutrition(game_state.nutrition);
+    let interruption = if enemy_in_view(objects, fov_map) {
+        Some("You stop resting, an enemy comes into view!")
+    } else if hunger == Hunger::Weak || hunger == Hunger::Starving {
+        Some("You are too hungry to rest any longer.")
+    } else if is_rested(game_state, &objects[PLAYER]) {
+        Some("You feel well rested.")
+    } else if turns >= MAX_REST_TURNS {
+        Some("You get up again.")
+    } else {
+        None
+    };
+    if let Some(message) = interruption {
+        game_state.log.add(message, Severity::Info);
+        game_state.resting = None;
+        return PlayerAction::DidntTakeTurn;
+    }
+
+    if turns % REST_HEAL_INTERVAL == REST_HEAL_INTERVAL - 1 {
+        objects[PLAYER].heal(game_state, 1);
+    }
+    game_state.resting = Some(turns + 1);
+    PlayerAction::TookTurn
+}
+
+/// Start running in the given direction, which repeats the step turn after turn until
+/// something interesting comes up. Return true if the player has taken the first step.
+pub fn player_run(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+    dx: i32,
+    dy: i32,
+) -> bool {
+    if enemy_in_view(objects, fov_map) {
+        game_state
+            .log
+            .add("You cannot run with enemies nearby.", Severity::Info);
+        return false;
+    }
+    game_state.running = Some((dx, dy));
+    player_run_step(game_state, objects, fov_map) == PlayerAction::TookTurn
+}
+
+/// Take the next step in the direction the player is running. Running stops in front of
+/// obstacles and hazards and when an enemy comes into view, and after stepping onto an object,
+/// next to a door or to where the walls at the sides change, like at a junction.
+fn player_run_step(
+    game_state: &mut GameState,
+    objects: &mut [Object],
+    fov_map: &FovMap,
+) -> PlayerAction {
+    let (dx, dy) = match game_state.running {
+        Some(direction) => direction,
+        None => return PlayerAction::DidntTakeTurn,
+    };
+    let from = objects[PLAYER].pos();
+    let (x, y) = (from.0 + dx, from.1 + dy);
+    let move_mode = objects[PLAYER].move_mode;
+    if enemy_in_view(objects, fov_map)
+        || is_blocked_for(&game_state.world, objects, x, y, move_mode)
+        || game_state.world.get(x, y).is_hazardous()
+    {
+        game_state.running = None;
+        return PlayerAction::DidntTakeTurn;
+    }
+
+    let sides = open_sides(&game_state.world, from, (dx, dy));
+    player_move_or_attack(game_state, objects, dx, dy);
+    let to = objects[PLAYER].pos();
+    let on_object = objects
+        .iter()
+        .skip(PLAYER + 1)
+        .any(|object| object.pos() == to && !object.is_hidden());
+    // secret doors must not give themselves away
+    let is_door = |(x, y): (i32, i32)| match game_state.world.get(x, y).door {
+        Some(Door::Secret) | None => false,
+        Some(_) => true,
+    };
+    let at_door = is_door(to) || is_door((to.0 + dx, to.1 + dy));
+    if to == from || on_object || at_door || open_sides(&game_state.world, to, (dx, dy)) != sides {
+        game_state.running = None;
+    }
+    PlayerAction::TookTurn
+}
+
+/// Return whether the tiles to the left and to the right of the position, seen when facing in
+/// the given direction, are open.
+fn open_sides(world: &World, (x, y): (i32, i32), (dx, dy): (i32, i32)) -> (bool, bool) {
+    (
+        !world.get(x + dy, y - dx).blocked,
+        !world.get(x - dy, y + dx).blocked,
+    )
+}
+
+/// Add an item to the player's inventory and remove it from the map.
+pub fn pick_item_up(game_state: &mut GameState, objects: &mut Vec<Object>, object_id: usize) {
+    if objects[object_id].item == Some(Item::Gold) {
+        let gold = objects.swap_remove(object_id);
+        game_state.gold += gold.count;
+        game_state
+            .log
+            .add(format!("You pick up {} gold.", gold.count), Severity::Good);
+    } else if !can_carry(game_state, &objects[object_id]) {
+        game_state.log.add(
+            format!(
+                "Your inventory is full, cannot pick up {}.",
+                objects[object_id].stack_name()
+            ),
+            Severity::Warning,
+        );
+    } else {
+        let item = objects.swap_remove(object_id);
+        game_state.log.add(
+            format!("You picked up a {}!", item.stack_name()),
+            Severity::Good,
+        );
+        add_to_inventory(&mut game_state.inventory, item);
+    }
+}
+
+/// Open all closed doors next to the player and close all open ones that are not obstructed.
+/// Locked doors are unlocked if the player carries a key.
+/// Return true if any door was opened, closed or unlocked.
+pub fn player_toggle_doors(game_state: &mut GameState, objects: &[Object]) -> bool {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let mut toggled = false;
+    for x in player_x - 1..=player_x + 1 {
+        for y in player_y - 1..=player_y + 1 {
+            let occupied = objects.iter().any(|object| object.pos() == (x, y));
+            match game_state.world.get(x, y).door {
+                Some(Door::Closed) => {
+                    game_state.world.get_mut(x, y).set_door(Door::Open);
+                    game_state.log.add("You open the door.", Severity::Info);
+                    toggled = true;
+                }
+                Some(Door::Open) if !occupied => {
+                    game_state.world.get_mut(x, y).set_door(Door::Closed);
+                    game_state.log.add("You close the door.", Severity::Info);
+                    game_state.noises.push(Noise::new(x, y, DOOR_NOISE));
+                    toggled = true;
+                }
+                Some(Door::Locked) => {
+                    toggled |= unlock_door(game_state, x, y);
+                }
+                _ => {}
+            }
+        }
+    }
+    toggled
+}
+
+/// Search the tiles around the player. Every adjacent secret door and hidden trap is found with
+/// a chance of `SEARCH_CHANCE`.
+pub fn player_search(game_state: &mut GameState, objects: &mut [Object]) {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let mut rng = rand::thread_rng();
+    for x in player_x - 1..=player_x + 1 {
+        for y in player_y - 1..=player_y + 1 {
+            if game_state.world.get(x, y).door == Some(Door::Secret)
+                && rng.gen::<f32>() < SEARCH_CHANCE
+            {
+                game_state.world.get_mut(x, y).set_door(Door::Closed);
+                game_state
+                    .log
+                    .add("You find a secret door!", Severity::Good);
+            }
+        }
+    }
+    for object in objects.iter_mut() {
+        let (dx, dy) = (object.x - player_x, object.y - player_y);
+        if object.is_hidden() && dx.abs() <= 1 && dy.abs() <= 1 && rng.gen::<f32>() < SEARCH_CHANCE
+        {
+            reveal_trap(object);
+            game_state
+                .log
+                .add(format!("You find a {}!", object.name), Severity::Good);
         }
     }
 }
 
 pub fn move_towards(
-    world: &World,
+    world: &mut World,
     objects: &mut [Object],
     id: usize,
     target_x: i32,
@@ -172,24 +1030,149 @@ pub fn move_towards(
     move_by(world, objects, id, dx, dy);
 }
 
+/// Put the object on a free tile next to the player. It is lost if there is no room.
+fn place_near_player(world: &World, objects: &mut Vec<Object>, mut object: Object) {
+    let (x, y) = objects[PLAYER].pos();
+    let spot = (y - 1..=y + 1)
+        .flat_map(|spot_y| (x - 1..=x + 1).map(move |spot_x| (spot_x, spot_y)))
+        .find(|&(spot_x, spot_y)| {
+            world.in_bounds(spot_x, spot_y)
+                && !is_blocked_for(world, objects, spot_x, spot_y, object.move_mode)
+                && !world.get(spot_x, spot_y).is_hazardous()
+        });
+    if let Some((spot_x, spot_y)) = spot {
+        object.set_pos(spot_x, spot_y);
+        objects.push(object);
+    }
+}
+
 /// Advance to the next level
 pub fn next_level(game_io: &mut GameIO, objects: &mut Vec<Object>, game_state: &mut GameState) {
-    game_state.log.add(
-        "You take a moment to rest, and recover your strength.",
-        colors::VIOLET,
-    );
-    let heal_hp = objects[PLAYER].max_hp(game_state) / 2;
-    objects[PLAYER].heal(game_state, heal_hp);
+    let next = game_state.level.below();
+    if stored_level(game_state, next).is_some() {
+        game_state
+            .log
+            .add("You descend the stairs once more.", Severity::Info);
+    } else {
+        game_state.log.add(
+            "You take a moment to rest, and recover your strength.",
+            Severity::Good,
+        );
+        let heal_hp = objects[PLAYER].max_hp(game_state) / 2;
+        objects[PLAYER].heal(game_state, heal_hp);
 
+        game_state.log.add(
+            "After a rare moment of peace, you descend deeper into the heart of the dungeon...",
+            Severity::Info,
+        );
+    }
+    change_level(game_io, objects, game_state, next, "up stairs");
+}
+
+/// Return to the level above
+pub fn previous_level(game_io: &mut GameIO, objects: &mut Vec<Object>, game_state: &mut GameState) {
+    game_state
+        .log
+        .add("You climb up the stairs.", Severity::Info);
+    // leaving the first level of a branch leads out of its entrance
+    let arrival = if game_state.level.depth == 1 {
+        BRANCHES[game_state.level.branch].entrance_name()
+    } else {
+        "stairs".into()
+    };
+    let previous = game_state.level.above();
+    change_level(game_io, objects, game_state, previous, &arrival);
+}
+
+/// Go down into the first level of the given branch from its entrance.
+pub fn enter_branch(
+    game_io: &mut GameIO,
+    objects: &mut Vec<Object>,
+    game_state: &mut GameState,
+    branch: usize,
+) {
     game_state.log.add(
-        "After a rare moment of peace, you descend deeper into the heart of the dungeon...",
-        colors::RED,
+        format!("You enter the {}.", BRANCHES[branch].name),
+        Severity::Info,
+    );
+    change_level(
+        game_io,
+        objects,
+        game_state,
+        LevelId::new(branch, 1),
+        "up stairs",
     );
-    game_state.dungeon_level += 1;
-    game_state.world = make_world(objects, game_state.dungeon_level);
+}
+
+/// Return the index of the stored level with the given id, if it has been visited before.
+fn stored_level(game_state: &GameState, id: LevelId) -> Option<usize> {
+    game_state.levels.iter().position(|level| level.id == id)
+}
+
+/// Leave the current level for the one with the given id. The current level is stored, so
+/// that it can be restored when the player returns. The player arrives at the object named
+/// `arrival`, usually the stairs leading back.
+fn change_level(
+    game_io: &mut GameIO,
+    objects: &mut Vec<Object>,
+    game_state: &mut GameState,
+    id: LevelId,
+    arrival: &str,
+) {
+    let mut level_objects = objects.split_off(PLAYER + 1);
+    // companions next to the player follow it
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let mut companions = vec![];
+    let mut i = 0;
+    while i < level_objects.len() {
+        if level_objects[i].ai == Some(Ai::Companion)
+            && level_objects[i].distance(player_x, player_y) < 2.0
+        {
+            companions.push(level_objects.remove(i));
+        } else {
+            i += 1;
+        }
+    }
+
+    let world = match stored_level(game_state, id) {
+        Some(index) => {
+            let stored = game_state.levels.swap_remove(index);
+            objects.extend(stored.objects);
+            stored.world
+        }
+        None => {
+            let world = make_world(objects, game_state.seed, id);
+            claim_artifacts(&mut game_state.artifacts, objects);
+            world
+        }
+    };
+    let arrival_pos = objects
+        .iter()
+        .find(|object| object.name == arrival)
+        .map(|object| object.pos());
+    if let Some((x, y)) = arrival_pos {
+        objects[PLAYER].set_pos(x, y);
+    }
+    for companion in companions {
+        place_near_player(&world, objects, companion);
+    }
+
+    let previous_world = mem::replace(&mut game_state.world, world);
+    game_state.levels.push(Level {
+        id: game_state.level,
+        world: previous_world,
+        objects: level_objects,
+    });
+    game_state.level = id;
+    if game_state.world.dark {
+        game_state
+            .log
+            .add("It is pitch dark here.", Severity::Warning);
+    }
     initialize_fov(&game_state.world, game_io);
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct Transition {
     pub level: u32,
     pub value: u32,
@@ -207,7 +1190,7 @@ pub fn from_dungeon_level(table: &[Transition], level: u32) -> u32 {
 
 pub fn level_up(objects: &mut [Object], game_state: &mut GameState, game_io: &mut GameIO) {
     let player = &mut objects[PLAYER];
-    let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
+    let level_up_xp = level_up_xp(player.level);
     // see if the player's experience is enough to level up
     if player.fighter.as_ref().map_or(0, |f| f.xp) >= level_up_xp {
         // exp is enough, lvl up
@@ -217,37 +1200,59 @@ pub fn level_up(objects: &mut [Object], game_state: &mut GameState, game_io: &mu
                 "Your battle skills grow stringer! You reached level {}!",
                 player.level
             ),
-            colors::YELLOW,
+            Severity::Good,
+        );
+        // besides raising an attribute, the player may learn one of the spells it doesn't know
+        let known = lasting_form(player)
+            .1
+            .map_or(vec![], |caster| caster.spells.clone());
+        let unknown: Vec<Spell> = PLAYER_SPELLS
+            .iter()
+            .cloned()
+            .filter(|spell| !known.contains(spell))
+            .collect();
+        let mut options: Vec<String> = ATTRIBUTES
+            .iter()
+            .map(|&attribute| {
+                format!(
+                    "{} (+{}, from {})",
+                    attribute.name(),
+                    ATTRIBUTE_PER_LEVEL,
+                    game_state.attributes.get(attribute)
+                )
+            })
+            .collect();
+        options.extend(
+            unknown
+                .iter()
+                .map(|spell| format!("Learn {} ({} mana)", spell.name(), spell.cost())),
         );
-        // TODO: increase player's stats
-        let fighter = player.fighter.as_mut().unwrap();
         let mut choice = None;
         while choice.is_none() {
             // keep asking until a choice is made
             choice = menu(
-                "Level up! Chose a stat to raise:\n",
-                &[
-                    format!("Constitution (+20 HP, from {})", fighter.base_max_hp),
-                    format!("Strength (+1 attack, from {})", fighter.base_power),
-                    format!("Agility (+1 defense, from {})", fighter.base_defense),
-                ],
+                "Level up! Chose an attribute to raise:\n",
+                &options,
                 LEVEL_SCREEN_WIDTH,
                 &mut game_io.root,
             );
         }
-        fighter.xp -= level_up_xp;
+        player.fighter.as_mut().unwrap().xp -= level_up_xp;
         match choice.unwrap() {
-            0 => {
-                fighter.base_max_hp += 20;
-                fighter.hp += 20;
-            }
-            1 => {
-                fighter.base_power += 1;
-            }
-            2 => {
-                fighter.base_defense += 1;
+            choice if choice < ATTRIBUTES.len() => raise_attribute(
+                player,
+                &mut game_state.attributes,
+                ATTRIBUTES[choice],
+                ATTRIBUTE_PER_LEVEL,
+            ),
+            choice => {
+                let spell = unknown[choice - ATTRIBUTES.len()];
+                if let Some(caster) = lasting_form(player).1 {
+                    caster.spells.push(spell);
+                }
             }
-            _ => unreachable!(),
         }
+        game_state.perk_points += 1;
+        spend_perk_point(&mut game_io.root, game_state);
     }
 }
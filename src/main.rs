@@ -1,13 +1,19 @@
 /// Module Main
 ///
 /// This module contains all structures and methods pertaining to the user interface.
+#[cfg(feature = "bracket-lib")]
+extern crate bracket_lib;
+#[macro_use]
+extern crate crossterm;
 extern crate rand;
 extern crate serde;
+#[cfg(feature = "tcod")]
 extern crate tcod;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
 
+mod backend;
 mod entity;
 mod color_palette;
 mod game_io;
@@ -15,14 +21,130 @@ mod game_state;
 mod util;
 mod world;
 
+use std::env;
+use std::str::FromStr;
+
 // internal modules
-use game_io::{initialize_io, main_menu, GameIO};
+#[cfg(feature = "bracket-lib")]
+use backend::bracket;
+use backend::colors;
+#[cfg(feature = "tcod")]
+use backend::libtcod::TcodBackend;
+use backend::terminal::TerminalBackend;
+use backend::Backend;
+use entity::object::Object;
+use game_io::{initialize_io, main_menu, GameIO, LIMIT_FPS};
+#[cfg(any(feature = "tcod", feature = "bracket-lib"))]
+use game_io::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use world::{ascii_map, load_assets, make_world, LevelId, BRANCHES};
+
+// title of the game window
+#[cfg(any(feature = "tcod", feature = "bracket-lib"))]
+const TITLE: &str = "innit alpha v0.0.1";
+
+/// Read the value of a command line option, given as `<name> <value>`.
+fn arg_value<T: FromStr>(name: &str) -> Option<T> {
+    let args: Vec<String> = env::args().collect();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|value| value.parse().ok())
+}
+
+/// Read the world seed from the command line, given as `--seed <number>`.
+fn seed_from_args() -> Option<u64> {
+    arg_value("--seed")
+}
+
+/// Generate a single level without starting the game and print it to stdout. The level is
+/// chosen with `--branch <index>` and `--depth <number>`, which default to the first level of
+/// the main dungeon.
+fn print_map() {
+    let seed = seed_from_args().unwrap_or_else(rand::random);
+    let branch = arg_value("--branch").unwrap_or(0);
+    let depth = arg_value("--depth").unwrap_or(1);
+    if branch >= BRANCHES.len() {
+        eprintln!("there is no branch {}", branch);
+        return;
+    }
+    let id = LevelId::new(branch, depth);
+
+    // the world generator places the player, so it needs one
+    let mut objects = vec![Object::new(0, 0, "player", true, '@', colors::WHITE)];
+    let world = make_world(&mut objects, seed, id);
+    println!(
+        "seed {}, {} depth {} ({})",
+        seed,
+        BRANCHES[id.branch].name,
+        id.depth,
+        world.theme.name()
+    );
+    print!("{}", ascii_map(&world, &objects));
+}
+
+/// Open the game in its own window, or with `--terminal` inside the terminal.
+#[cfg(feature = "tcod")]
+fn make_backend() -> Box<dyn Backend> {
+    if env::args().any(|arg| arg == "--terminal") {
+        Box::new(TerminalBackend::new(LIMIT_FPS))
+    } else {
+        Box::new(TcodBackend::new(
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            TITLE,
+            LIMIT_FPS,
+        ))
+    }
+}
+
+/// Without libtcod, the game always runs inside the terminal.
+#[cfg(not(feature = "tcod"))]
+fn make_backend() -> Box<dyn Backend> {
+    Box::new(TerminalBackend::new(LIMIT_FPS))
+}
 
+/// Start the game.
 fn launch_game() {
-    let mut game_io: GameIO = initialize_io();
-    main_menu(&mut game_io);
+    let mut game_io: GameIO = initialize_io(make_backend());
+    main_menu(&mut game_io, seed_from_args());
+}
+
+/// Start the game in a bracket-lib window, with scanlines on top if `--scanlines` is given.
+#[cfg(feature = "bracket-lib")]
+fn launch_bracket_game() {
+    let seed = seed_from_args();
+    let scanlines = env::args().any(|arg| arg == "--scanlines");
+    let result = bracket::run(
+        SCREEN_WIDTH,
+        SCREEN_HEIGHT,
+        TITLE,
+        LIMIT_FPS,
+        scanlines,
+        move |backend| {
+            // the assets are cached per thread, and the game runs on a thread of its own
+            load_assets();
+            let mut game_io: GameIO = initialize_io(Box::new(backend));
+            main_menu(&mut game_io, seed);
+        },
+    );
+    if let Err(e) = result {
+        eprintln!("unable to open the bracket-lib window: {}", e);
+    }
+}
+
+/// Without bracket-lib, there is no window to open.
+#[cfg(not(feature = "bracket-lib"))]
+fn launch_bracket_game() {
+    eprintln!("this build has no bracket-lib backend, enable the `bracket-lib` feature");
 }
 
 fn main() {
-    launch_game();
+    load_assets();
+    if env::args().any(|arg| arg == "--genmap") {
+        print_map();
+    } else if env::args().any(|arg| arg == "--bracket") {
+        launch_bracket_game();
+    } else {
+        launch_game();
+    }
 }
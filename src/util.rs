@@ -3,6 +3,39 @@
 /// This module contains useful utility functions that are unrelated
 /// to any of the main game modules.
 use std::cmp;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Parse every file with the given extension in a directory, in the order of their names,
+/// so that the same seed always yields the same result. The parser is given the file name
+/// without the extension and the contents of the file.
+pub fn load_dir<T, P, F>(dir: P, extension: &str, parse: F) -> Result<Vec<T>, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    F: Fn(&str, &str) -> Result<T, Box<dyn Error>>,
+{
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    let mut parsed = vec![];
+    for path in paths {
+        if path.extension().is_some_and(|ext| ext == extension) {
+            let mut text = String::new();
+            File::open(&path)?.read_to_string(&mut text)?;
+            parsed.push(
+                parse(
+                    &path.file_stem().unwrap_or_default().to_string_lossy(),
+                    &text,
+                )
+                .map_err(|e| format!("invalid file {}: {}", path.display(), e))?,
+            );
+        }
+    }
+    Ok(parsed)
+}
 
 /// Mutably borrow two *separate* elements from the given slice.
 /// Panics when the indices are equal or out of bounds.
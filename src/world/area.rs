@@ -0,0 +1,91 @@
+/// Module Area
+///
+/// Attacks like explosions and breath hit every tile of an area at once. This module works out
+/// which tiles these areas cover: a burst around a center, a cone spreading out from its origin
+/// and a ray along a straight line. Walls shield whatever lies behind them, creatures don't.
+use std::f32::consts::PI;
+
+// internal modules
+use world::{line, World, WORLD_HEIGHT, WORLD_WIDTH};
+
+// half of the opening angle of a cone
+const CONE_HALF_ANGLE: f32 = PI / 6.0;
+
+/// Return all tiles within the given radius around the center that the blast can reach.
+pub fn burst(world: &World, center: (i32, i32), radius: i32) -> Vec<(i32, i32)> {
+    tiles_around(center, radius)
+        .into_iter()
+        .filter(|&(x, y)| {
+            let (dx, dy) = (x - center.0, y - center.1);
+            dx * dx + dy * dy <= radius * radius && reaches(world, center, (x, y))
+        })
+        .collect()
+}
+
+/// Return all tiles of a cone that spreads out from `origin` towards `target`, up to the given
+/// range. The origin itself is not part of the cone.
+pub fn cone(world: &World, origin: (i32, i32), target: (i32, i32), range: i32) -> Vec<(i32, i32)> {
+    let direction = ((target.1 - origin.1) as f32).atan2((target.0 - origin.0) as f32);
+    tiles_around(origin, range)
+        .into_iter()
+        .filter(|&(x, y)| {
+            let (dx, dy) = (x - origin.0, y - origin.1);
+            if (dx, dy) == (0, 0) || dx * dx + dy * dy > range * range {
+                return false;
+            }
+            // the angle between the tile and the direction of the cone, wrapped into [0, PI]
+            let angle = ((dy as f32).atan2(dx as f32) - direction).abs();
+            let angle = angle.min(2.0 * PI - angle);
+            angle <= CONE_HALF_ANGLE && reaches(world, origin, (x, y))
+        })
+        .collect()
+}
+
+/// Return the tiles of a ray that is shot from `origin` through `target`, up to the given range
+/// or the first wall.
+pub fn ray(world: &World, origin: (i32, i32), target: (i32, i32), range: i32) -> Vec<(i32, i32)> {
+    if origin == target {
+        return vec![];
+    }
+    // extend the line beyond the target, so that the ray goes on behind it
+    let (dx, dy) = (target.0 - origin.0, target.1 - origin.1);
+    let scale = range / dx.abs().max(dy.abs()) + 1;
+    let end = (origin.0 + dx * scale, origin.1 + dy * scale);
+    let mut tiles = vec![];
+    for (x, y) in line(origin, end).into_iter().skip(1).take(range as usize) {
+        if !in_world(x, y) || world.get(x, y).blocked {
+            break;
+        }
+        tiles.push((x, y));
+    }
+    tiles
+}
+
+/// Return all tiles of the world in the square of the given radius around the center.
+fn tiles_around(center: (i32, i32), radius: i32) -> Vec<(i32, i32)> {
+    let mut tiles = vec![];
+    for y in center.1 - radius..=center.1 + radius {
+        for x in center.0 - radius..=center.0 + radius {
+            if in_world(x, y) {
+                tiles.push((x, y));
+            }
+        }
+    }
+    tiles
+}
+
+fn in_world(x: i32, y: i32) -> bool {
+    x >= 0 && y >= 0 && x < WORLD_WIDTH && y < WORLD_HEIGHT
+}
+
+/// Return true if nothing but open tiles lie between both positions. The target itself may be
+/// a wall, which is hit but shields everything behind it.
+fn reaches(world: &World, from: (i32, i32), to: (i32, i32)) -> bool {
+    let path = line(from, to);
+    if path.len() < 3 {
+        return true;
+    }
+    path[1..path.len() - 1]
+        .iter()
+        .all(|&(x, y)| !world.get(x, y).blocked)
+}
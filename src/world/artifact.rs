@@ -0,0 +1,104 @@
+/// Module Artifact
+///
+/// Artifacts are unique pieces of equipment with powers far beyond ordinary loot. Each of them
+/// exists at most once per game: they are only found in the treasure vaults of deep levels or
+/// carried by bosses, and the game remembers which of them have already been generated.
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// internal modules
+use backend::colors::{self, Color};
+use entity::equipment::{Bonus, Element, Equipment, Slot};
+use entity::item::Item;
+use entity::object::Object;
+
+/// Treasure vaults from this level on may hold an artifact.
+pub const ARTIFACT_LEVEL: u32 = 8;
+const ARTIFACT_COLOR: Color = colors::LIGHT_MAGENTA;
+
+struct Artifact {
+    name: &'static str,
+    chr: char,
+    slot: Slot,
+    bonuses: &'static [Bonus],
+}
+
+const ARTIFACTS: [Artifact; 5] = [
+    Artifact {
+        name: "Lancet of Pasteur",
+        chr: '/',
+        slot: Slot::RightHand,
+        bonuses: &[Bonus::Power(4), Bonus::Elemental(Element::Fire, 3)],
+    },
+    Artifact {
+        name: "Aegis of Jenner",
+        chr: '[',
+        slot: Slot::LeftHand,
+        bonuses: &[Bonus::Defense(4), Bonus::Regeneration(1)],
+    },
+    Artifact {
+        name: "Crown of Koch",
+        chr: '[',
+        slot: Slot::Head,
+        bonuses: &[Bonus::Defense(2), Bonus::MaxHp(40)],
+    },
+    Artifact {
+        name: "Mould Ring of Fleming",
+        chr: '=',
+        slot: Slot::LeftRing,
+        bonuses: &[Bonus::Regeneration(2), Bonus::Elemental(Element::Poison, 2)],
+    },
+    Artifact {
+        name: "Lens of Leeuwenhoek",
+        chr: '"',
+        slot: Slot::Neck,
+        bonuses: &[Bonus::Power(2), Bonus::Defense(2), Bonus::MaxHp(20)],
+    },
+];
+
+/// Create the artifact with the given index, counting around if it is too large.
+pub fn make_artifact(index: usize, x: i32, y: i32) -> Object {
+    let artifact = &ARTIFACTS[index % ARTIFACTS.len()];
+    let mut object = Object::new(x, y, artifact.name, false, artifact.chr, ARTIFACT_COLOR);
+    object.item = Some(Item::Equipment);
+    object.equipment = Some(Equipment::new(artifact.slot, artifact.bonuses.to_vec()));
+    object.always_visible = true;
+    object
+}
+
+/// Create a random artifact.
+pub fn random_artifact(rng: &mut StdRng, x: i32, y: i32) -> Object {
+    make_artifact(rng.gen_range(0, ARTIFACTS.len()), x, y)
+}
+
+fn is_artifact(object: &Object) -> bool {
+    ARTIFACTS
+        .iter()
+        .any(|artifact| artifact.name == object.name)
+}
+
+/// Remove all artifacts from a newly generated level that have been generated before, including
+/// those in chests and carried by monsters, and remember the others as generated.
+pub fn claim_artifacts(generated: &mut Vec<String>, objects: &mut Vec<Object>) {
+    objects.retain(|object| claim(generated, object));
+    for object in objects.iter_mut() {
+        if let Some(container) = object.container.as_mut() {
+            container.items.retain(|item| claim(generated, item));
+        }
+        if let Some(inventory) = object.inventory.as_mut() {
+            inventory.retain(|item| claim(generated, item));
+        }
+    }
+}
+
+/// Return false if the object is an artifact that has been generated before.
+fn claim(generated: &mut Vec<String>, object: &Object) -> bool {
+    if !is_artifact(object) {
+        return true;
+    }
+    if generated.contains(&object.name) {
+        return false;
+    }
+    generated.push(object.name.clone());
+    true
+}
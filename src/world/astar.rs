@@ -0,0 +1,114 @@
+/// Module Astar
+///
+/// Finds the cheapest path between two tiles with the A* algorithm. The cost of every step is
+/// given by a callback, so that callers can decide which tiles can be entered and at what cost.
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A tile waiting to be expanded, ordered so that the lowest estimate comes first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Candidate {
+    estimate: f32,
+    position: (i32, i32),
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Return the cheapest path from `from` to `to` on a grid of the given size, moving in all
+/// eight directions. `cost` returns the cost of a step from one tile onto a neighbouring one,
+/// or 0.0 if the step is impossible, and diagonal steps cost `diagonal_cost` times as much.
+/// Step costs are expected to be at least 1.0. The path does not contain the starting position
+/// and is empty if there is no path.
+pub fn astar<F>(
+    width: i32,
+    height: i32,
+    from: (i32, i32),
+    to: (i32, i32),
+    diagonal_cost: f32,
+    mut cost: F,
+) -> Vec<(i32, i32)>
+where
+    F: FnMut((i32, i32), (i32, i32)) -> f32,
+{
+    let inside = |(x, y): (i32, i32)| x >= 0 && y >= 0 && x < width && y < height;
+    if !inside(from) || !inside(to) {
+        return vec![];
+    }
+    let index = |(x, y): (i32, i32)| (y * width + x) as usize;
+    // the cost of the cheapest possible path, if every step costs 1.0
+    let heuristic = |(x, y): (i32, i32)| {
+        let (dx, dy) = ((x - to.0).abs() as f32, (y - to.1).abs() as f32);
+        dx.max(dy) + (diagonal_cost - 1.0) * dx.min(dy)
+    };
+
+    let size = (width * height) as usize;
+    let mut spent = vec![f32::INFINITY; size];
+    let mut previous: Vec<Option<(i32, i32)>> = vec![None; size];
+    let mut done = vec![false; size];
+    let mut open = BinaryHeap::new();
+    spent[index(from)] = 0.0;
+    open.push(Candidate {
+        estimate: heuristic(from),
+        position: from,
+    });
+
+    while let Some(Candidate { position, .. }) = open.pop() {
+        if position == to {
+            let mut path = vec![];
+            let mut step = to;
+            while step != from {
+                path.push(step);
+                step = previous[index(step)].unwrap();
+            }
+            path.reverse();
+            return path;
+        }
+        if done[index(position)] {
+            continue;
+        }
+        done[index(position)] = true;
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let next = (position.0 + dx, position.1 + dy);
+                if (dx, dy) == (0, 0) || !inside(next) || done[index(next)] {
+                    continue;
+                }
+                let step_cost = cost(position, next);
+                if step_cost <= 0.0 {
+                    continue;
+                }
+                let step_cost = if dx != 0 && dy != 0 {
+                    step_cost * diagonal_cost
+                } else {
+                    step_cost
+                };
+                let total = spent[index(position)] + step_cost;
+                if total < spent[index(next)] {
+                    spent[index(next)] = total;
+                    previous[index(next)] = Some(position);
+                    open.push(Candidate {
+                        estimate: total + heuristic(next),
+                        position: next,
+                    });
+                }
+            }
+        }
+    }
+    vec![]
+}
@@ -0,0 +1,120 @@
+/// Module Branch
+///
+/// The dungeon consists of several branches. Main branches are entered from the overworld,
+/// side branches from special stairs on a level of another branch. Every level is identified
+/// by its branch and its depth within that branch.
+use world::theme::Theme;
+
+/// Identifies a level by the branch it belongs to and its depth within that branch. Depth 0
+/// is the overworld, which all branches share.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelId {
+    /// Index into `BRANCHES`.
+    pub branch: usize,
+    pub depth: u32,
+}
+
+impl LevelId {
+    pub fn new(branch: usize, depth: u32) -> Self {
+        // there is only one overworld, no matter which branch it is reached from
+        let branch = if depth == 0 { 0 } else { branch };
+        LevelId { branch, depth }
+    }
+
+    /// Return the level that lies one level below this one.
+    pub fn below(self) -> Self {
+        LevelId::new(self.branch, self.depth + 1)
+    }
+
+    /// Return the level that is reached by going up from this one. From the first level of a
+    /// branch, this is the level that holds its entrance.
+    pub fn above(self) -> Self {
+        match BRANCHES[self.branch].entry {
+            Some(entry) if self.depth == 1 => entry,
+            _ => LevelId::new(self.branch, self.depth.saturating_sub(1)),
+        }
+    }
+
+    /// Return the level that is used to look up themes and spawn tables. Levels of more
+    /// difficult branches count as deeper than they are.
+    pub fn difficulty(self) -> u32 {
+        if self.depth == 0 {
+            0
+        } else {
+            self.depth + BRANCHES[self.branch].difficulty
+        }
+    }
+
+    /// Return true if there are no levels below this one.
+    pub fn is_bottom(self) -> bool {
+        BRANCHES[self.branch]
+            .max_depth
+            .is_some_and(|max_depth| self.depth >= max_depth)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Branch {
+    pub name: &'static str,
+    /// Added to the depth to get the level used for themes and spawn tables, so that the first
+    /// level of a difficult branch is as dangerous as a deep level of an easy one.
+    pub difficulty: u32,
+    /// The level that holds the entrance to this branch, or `None` for branches that are
+    /// entered from the overworld.
+    pub entry: Option<LevelId>,
+    /// The number of levels of this branch, or `None` if it goes on forever.
+    pub max_depth: Option<u32>,
+    /// The theme of all levels of this branch, instead of the theme that fits their depth.
+    pub theme: Option<Theme>,
+}
+
+impl Branch {
+    /// Name of the object that leads into this branch.
+    pub fn entrance_name(&self) -> String {
+        format!("entrance to the {}", self.name)
+    }
+}
+
+pub const BRANCHES: &[Branch] = &[
+    Branch {
+        name: "Old Sewers",
+        difficulty: 0,
+        entry: None,
+        max_depth: None,
+        theme: None,
+    },
+    Branch {
+        name: "Dripping Caves",
+        difficulty: 3,
+        entry: None,
+        max_depth: None,
+        theme: None,
+    },
+    Branch {
+        name: "Forgotten Crypts",
+        difficulty: 6,
+        entry: None,
+        max_depth: None,
+        theme: None,
+    },
+    Branch {
+        name: "Flooded Cistern",
+        difficulty: 2,
+        entry: Some(LevelId {
+            branch: 0,
+            depth: 2,
+        }),
+        max_depth: Some(3),
+        theme: Some(Theme::Flooded),
+    },
+    Branch {
+        name: "Fungal Mines",
+        difficulty: 4,
+        entry: Some(LevelId {
+            branch: 1,
+            depth: 2,
+        }),
+        max_depth: Some(4),
+        theme: Some(Theme::Caves),
+    },
+];
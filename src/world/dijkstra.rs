@@ -0,0 +1,124 @@
+/// Module Dijkstra
+///
+/// Dijkstra maps store for every tile how far it is from the nearest goal, e.g. the player.
+/// Any number of creatures can share a map and reach the goal by stepping to the neighbouring
+/// tile with the lowest value, which is much cheaper than searching a path for each of them.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// internal modules
+use entity::object::{MoveMode, Object};
+use world::{is_blocked_for, Door, World};
+
+/// Value of tiles from which no goal can be reached.
+pub const UNREACHABLE: i32 = i32::MAX;
+// fleeing creatures value the distance from the goal by this factor, in tenths, which makes
+// them prefer running past the goal into open areas over being cornered in dead ends
+const FLEE_FACTOR: i32 = -12;
+
+#[derive(Clone, Debug)]
+pub struct DijkstraMap {
+    width: i32,
+    height: i32,
+    /// The way of moving that the map was computed for.
+    pub move_mode: MoveMode,
+    values: Vec<i32>,
+}
+
+impl DijkstraMap {
+    /// Compute the map for creatures that move in the given way, leading to the nearest goal.
+    pub fn new(world: &World, goals: &[(i32, i32)], move_mode: MoveMode) -> Self {
+        let mut map = DijkstraMap {
+            width: world.width,
+            height: world.height,
+            move_mode,
+            values: vec![UNREACHABLE; world.tiles.len()],
+        };
+        for &(x, y) in goals {
+            map.values[world.index(x, y)] = 0;
+        }
+        map.scan(world);
+        map
+    }
+
+    /// Return the value of the given position.
+    pub fn get(&self, x: i32, y: i32) -> i32 {
+        self.values[(y * self.width + x) as usize]
+    }
+
+    /// Return a map that leads away from the goals of this one. Descending it moves creatures
+    /// away from the goals, but not into dead ends if there is another way out.
+    pub fn inverted(&self, world: &World) -> Self {
+        let mut map = self.clone();
+        for value in map.values.iter_mut().filter(|value| **value != UNREACHABLE) {
+            *value = *value * FLEE_FACTOR / 10;
+        }
+        map.scan(world);
+        map
+    }
+
+    /// Return a map whose values are the sum of this map's and the given percentage of the
+    /// other map's values. Tiles that cannot be reached on the other map keep their value.
+    pub fn added(&self, other: &DijkstraMap, percent: i32) -> Self {
+        let mut map = self.clone();
+        for (value, &other) in map.values.iter_mut().zip(other.values.iter()) {
+            if *value != UNREACHABLE && other != UNREACHABLE {
+                *value += other * percent / 100;
+            }
+        }
+        map
+    }
+
+    /// Return the neighbour of the given position that leads down the map the fastest and that
+    /// is not occupied by another object. Return `None` if there is no way down.
+    pub fn descend(&self, world: &World, objects: &[Object], x: i32, y: i32) -> Option<(i32, i32)> {
+        neighbours(x, y)
+            .filter(|&(nx, ny)| world.in_bounds(nx, ny) && self.get(nx, ny) < self.get(x, y))
+            .filter(|&(nx, ny)| {
+                world.get(nx, ny).door == Some(Door::Closed)
+                    || !is_blocked_for(world, objects, nx, ny, self.move_mode)
+            })
+            .min_by_key(|&(nx, ny)| self.get(nx, ny))
+    }
+
+    /// Lower the value of every tile to the lowest value of its neighbours plus the cost of
+    /// entering it, until no value changes anymore.
+    fn scan(&mut self, world: &World) {
+        let mut queue: BinaryHeap<Reverse<(i32, i32, i32)>> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y) != UNREACHABLE)
+            .map(|(x, y)| Reverse((self.get(x, y), x, y)))
+            .collect();
+        while let Some(Reverse((value, x, y))) = queue.pop() {
+            if value > self.get(x, y) {
+                // already reached in a cheaper way
+                continue;
+            }
+            for (nx, ny) in neighbours(x, y) {
+                if !world.in_bounds(nx, ny) {
+                    continue;
+                }
+                let tile = world.get(nx, ny);
+                let cost = if tile.door == Some(Door::Closed) {
+                    tile.move_cost + 1
+                } else if tile.blocks(self.move_mode) || tile.is_hazardous() {
+                    continue;
+                } else {
+                    tile.move_cost
+                };
+                let index = world.index(nx, ny);
+                if value + cost < self.values[index] {
+                    self.values[index] = value + cost;
+                    queue.push(Reverse((value + cost, nx, ny)));
+                }
+            }
+        }
+    }
+}
+
+/// Return all eight positions around the given one.
+fn neighbours(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(move |&position| position != (x, y))
+}
@@ -0,0 +1,150 @@
+/// Module Drops
+///
+/// What a monster leaves behind when it dies is rolled from its drop table, read from a
+/// plain-text file. Every table starts with the name of the monster, followed by the items it
+/// may drop on indented lines, each with its chance and optionally the range of its amount:
+///
+/// ```text
+/// monster virus
+///     gold 0.4 1-8
+///     membrane 0.3
+///     dagger 0.02
+/// ```
+///
+/// Every item is rolled on its own, so a monster can drop several of them at once. Monsters
+/// without a table drop nothing but their corpse.
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use rand::Rng;
+
+// internal modules
+use entity::object::Object;
+use world::make_item;
+
+/// File that holds the drop tables of all monsters.
+pub const DROP_FILE: &str = "assets/drops.txt";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Drop {
+    item: String,
+    chance: f32,
+    /// Smallest and largest number of items dropped at once.
+    amount: (i32, i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropTable {
+    monster: String,
+    drops: Vec<Drop>,
+}
+
+thread_local! {
+    // the drop tables, loaded once at startup
+    static TABLES: RefCell<Option<Vec<DropTable>>> = const { RefCell::new(None) };
+}
+
+/// Load the drop tables from the drop file, unless they have been loaded already.
+pub fn cache_drop_tables() {
+    TABLES.with(|tables| {
+        if tables.borrow().is_some() {
+            return;
+        }
+        let loaded = load_drop_tables(DROP_FILE).unwrap_or_else(|e| {
+            eprintln!("unable to load drop tables: {}", e);
+            vec![]
+        });
+        *tables.borrow_mut() = Some(loaded);
+    });
+}
+
+/// Roll the drop table of the given monster and return the dropped items at the given position.
+pub fn roll_drops(monster: &str, x: i32, y: i32) -> Vec<Object> {
+    cache_drop_tables();
+    TABLES.with(|tables| {
+        let tables = tables.borrow();
+        let table = match tables
+            .as_ref()
+            .and_then(|tables| tables.iter().find(|table| table.monster == monster))
+        {
+            Some(table) => table,
+            None => return vec![],
+        };
+
+        let mut rng = rand::thread_rng();
+        let mut dropped = vec![];
+        for drop in &table.drops {
+            if rng.gen::<f32>() >= drop.chance {
+                continue;
+            }
+            if let Some(mut item) = make_item(&drop.item, x, y) {
+                let (min, max) = drop.amount;
+                item.count = rng.gen_range(min, max + 1);
+                dropped.push(item);
+            }
+        }
+        dropped
+    })
+}
+
+/// Load all drop tables from the given file.
+pub fn load_drop_tables<P: AsRef<Path>>(path: P) -> Result<Vec<DropTable>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let tables = parse_drop_tables(&text)
+        .map_err(|e| format!("invalid drop file {}: {}", path.display(), e))?;
+    Ok(tables)
+}
+
+fn parse_drop_tables(text: &str) -> Result<Vec<DropTable>, Box<dyn Error>> {
+    let mut tables: Vec<DropTable> = vec![];
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(monster) = line.strip_prefix("monster ") {
+            tables.push(DropTable {
+                monster: monster.trim().to_string(),
+                drops: vec![],
+            });
+            continue;
+        }
+
+        let line = line.trim();
+        let table = tables
+            .last_mut()
+            .ok_or_else(|| format!("drop '{}' outside of a table", line))?;
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        let amount = match words.last().and_then(|word| parse_amount(word)) {
+            Some(amount) => {
+                words.pop();
+                amount
+            }
+            None => (1, 1),
+        };
+        let chance = match words.pop().map(|word| word.parse::<f32>()) {
+            Some(Ok(chance)) if !words.is_empty() => chance,
+            _ => return Err(format!("invalid drop '{}'", line).into()),
+        };
+        let item = words.join(" ");
+        if make_item(&item, 0, 0).is_none() {
+            return Err(format!("unknown item '{}'", item).into());
+        }
+        table.drops.push(Drop {
+            item,
+            chance,
+            amount,
+        });
+    }
+    Ok(tables)
+}
+
+/// Parse an amount given as a range `min-max`.
+fn parse_amount(word: &str) -> Option<(i32, i32)> {
+    let dash = word.find('-')?;
+    match (word[..dash].parse(), word[dash + 1..].parse()) {
+        (Ok(min), Ok(max)) if 0 < min && min <= max => Some((min, max)),
+        _ => None,
+    }
+}
@@ -0,0 +1,141 @@
+/// Module Fov
+///
+/// The field of view, computed with recursive shadowcasting. The eight octants around the
+/// viewer are scanned row by row, moving outwards, and every opaque tile hides the part of the
+/// following rows that lies in its shadow.
+// the transformations that map the first octant onto each of the eight octants
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+/// Which tiles can be seen through, and which ones were in view the last time the field of
+/// view was computed.
+#[derive(Clone, Debug)]
+pub struct FovMap {
+    width: i32,
+    height: i32,
+    transparent: Vec<bool>,
+    in_fov: Vec<bool>,
+}
+
+impl FovMap {
+    /// Create a map of the given size on which every tile blocks the view.
+    pub fn new(width: i32, height: i32) -> Self {
+        let size = (width * height) as usize;
+        FovMap {
+            width,
+            height,
+            transparent: vec![false; size],
+            in_fov: vec![false; size],
+        }
+    }
+
+    /// Set whether the tile at the given position can be seen through.
+    pub fn set(&mut self, x: i32, y: i32, transparent: bool) {
+        if let Some(index) = self.index(x, y) {
+            self.transparent[index] = transparent;
+        }
+    }
+
+    /// Return true if the tile at the given position was in view.
+    pub fn is_in_fov(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|index| self.in_fov[index])
+    }
+
+    /// Compute which tiles can be seen from the given position, up to the given distance. A
+    /// radius of zero has no limit. Opaque tiles are only in view themselves if `light_walls`
+    /// is set.
+    pub fn compute_fov(&mut self, x: i32, y: i32, radius: i32, light_walls: bool) {
+        for in_fov in &mut self.in_fov {
+            *in_fov = false;
+        }
+        let index = match self.index(x, y) {
+            Some(index) => index,
+            None => return,
+        };
+        self.in_fov[index] = true;
+        let radius = if radius > 0 {
+            radius
+        } else {
+            self.width.max(self.height)
+        };
+        for &octant in &OCTANTS {
+            self.cast_light((x, y), radius, light_walls, 1, (1.0, 0.0), octant);
+        }
+    }
+
+    /// Scan one octant from the given row outwards, between the start and end slopes.
+    fn cast_light(
+        &mut self,
+        (x, y): (i32, i32),
+        radius: i32,
+        light_walls: bool,
+        row: i32,
+        (mut start, end): (f32, f32),
+        (xx, xy, yx, yy): (i32, i32, i32, i32),
+    ) {
+        if start < end {
+            return;
+        }
+        for distance in row..=radius {
+            let dy = -distance;
+            let mut blocked = false;
+            let mut next_start = start;
+            for dx in -distance..=0 {
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+                let index = self.index(x + dx * xx + dy * xy, y + dx * yx + dy * yy);
+                let opaque = !index.is_some_and(|index| self.transparent[index]);
+                if let Some(index) = index {
+                    if dx * dx + dy * dy <= radius * radius && (light_walls || !opaque) {
+                        self.in_fov[index] = true;
+                    }
+                }
+                if blocked {
+                    if opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if opaque && distance < radius {
+                    // the lit part of the next rows, up to this tile, is scanned on its own
+                    blocked = true;
+                    self.cast_light(
+                        (x, y),
+                        radius,
+                        light_walls,
+                        distance + 1,
+                        (start, left_slope),
+                        (xx, xy, yx, yy),
+                    );
+                    next_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+}
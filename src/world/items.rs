@@ -0,0 +1,305 @@
+/// Module Items
+///
+/// All ordinary items are defined in a plain-text file, so that new ones can be added without
+/// recompiling the game. Every definition starts with the name under which the item is created,
+/// followed by its properties on indented lines:
+///
+/// ```text
+/// item sword
+///     glyph /
+///     color sky
+///     kind equipment
+///     slot right_hand
+///     bonus power 3
+/// ```
+///
+/// `glyph`, `color` and `kind` are required. Colors are given by name or as red, green and blue
+/// values. A `slot` makes the item equipment, with any number of `bonus` lines, and weapons
+/// roll the dice given by `damage`, like `2d4+1`. `name` gives the item a different name than
+/// the one it is created by, and `count` creates a whole stack.
+/// The bonuses `burn`, `freeze`, `lifesteal` and `knockback` give weapons a chance in percent to trigger
+/// these effects when they hit. `block` and `parry` are chances in percent to fend off a blow
+/// that would have hit the wearer, and `vampiric` heals the wielder by a percentage of the damage
+/// of every hit.
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// internal modules
+use backend::colors::{self, Color};
+use entity::container::Container;
+use entity::dice::Dice;
+use entity::equipment::{Bonus, Element, Equipment, Proc, Slot};
+use entity::item::Item;
+use entity::object::Object;
+
+/// File that holds the definitions of all items.
+pub const ITEM_FILE: &str = "assets/items.txt";
+
+#[derive(Clone, Debug)]
+pub struct ItemDef {
+    /// Name under which the item is created.
+    key: String,
+    name: String,
+    chr: char,
+    color: Color,
+    kind: Item,
+    slot: Option<Slot>,
+    bonuses: Vec<Bonus>,
+    damage: Option<Dice>,
+    count: i32,
+}
+
+thread_local! {
+    // the item registry, loaded once the first item is made
+    static REGISTRY: RefCell<Option<Vec<ItemDef>>> = const { RefCell::new(None) };
+}
+
+/// Load the item registry from the item file, unless it has been loaded already.
+pub fn load_registry() {
+    REGISTRY.with(|registry| {
+        if registry.borrow().is_some() {
+            return;
+        }
+        let items = load_items(ITEM_FILE).unwrap_or_else(|e| {
+            eprintln!("unable to load items: {}", e);
+            vec![]
+        });
+        *registry.borrow_mut() = Some(items);
+    });
+}
+
+/// Create the item defined under the given name at the given position.
+pub fn create_item(key: &str, x: i32, y: i32) -> Option<Object> {
+    load_registry();
+    REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let def = registry.as_ref()?.iter().find(|def| def.key == key)?;
+        let mut item = Object::new(x, y, &def.name, false, def.chr, def.color);
+        item.item = Some(def.kind);
+        item.count = def.count;
+        if let Some(slot) = def.slot {
+            let mut equipment = Equipment::new(slot, def.bonuses.clone());
+            equipment.damage = def.damage;
+            item.equipment = Some(equipment);
+        }
+        if def.kind == Item::Bag {
+            item.container = Some(Container {
+                items: vec![],
+                open: true,
+            });
+        }
+        item.always_visible = true;
+        Some(item)
+    })
+}
+
+/// Load all item definitions from the given file.
+pub fn load_items<P: AsRef<Path>>(path: P) -> Result<Vec<ItemDef>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let items =
+        parse_items(&text).map_err(|e| format!("invalid item file {}: {}", path.display(), e))?;
+    Ok(items)
+}
+
+/// An item definition that is still being read, whose required properties may be missing.
+struct Draft {
+    key: String,
+    name: String,
+    chr: Option<char>,
+    color: Option<Color>,
+    kind: Option<Item>,
+    slot: Option<Slot>,
+    bonuses: Vec<Bonus>,
+    damage: Option<Dice>,
+    count: i32,
+}
+
+impl Draft {
+    fn new(key: &str) -> Self {
+        Draft {
+            key: key.to_string(),
+            name: key.to_string(),
+            chr: None,
+            color: None,
+            kind: None,
+            slot: None,
+            bonuses: vec![],
+            damage: None,
+            count: 1,
+        }
+    }
+
+    /// Turn the draft into a definition, provided all required properties are given.
+    fn finish(self) -> Result<ItemDef, String> {
+        match (self.chr, self.color, self.kind) {
+            (Some(chr), Some(color), Some(kind)) => Ok(ItemDef {
+                key: self.key,
+                name: self.name,
+                chr,
+                color,
+                kind,
+                slot: self.slot,
+                bonuses: self.bonuses,
+                damage: self.damage,
+                count: self.count,
+            }),
+            _ => Err(format!("item '{}' lacks a glyph, color or kind", self.key)),
+        }
+    }
+}
+
+fn parse_items(text: &str) -> Result<Vec<ItemDef>, Box<dyn Error>> {
+    let mut drafts: Vec<Draft> = vec![];
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(name) = line.strip_prefix("item ") {
+            drafts.push(Draft::new(name.trim()));
+            continue;
+        }
+
+        let line = line.trim();
+        let draft = drafts
+            .last_mut()
+            .ok_or_else(|| format!("property '{}' outside of an item", line))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["name", ..] => draft.name = words[1..].join(" "),
+            ["glyph", glyph] if glyph.chars().count() == 1 => draft.chr = glyph.chars().next(),
+            ["color", ..] => draft.color = Some(parse_color(&words[1..])?),
+            ["kind", word] => draft.kind = Some(parse_kind(word)?),
+            ["slot", word] => draft.slot = Some(parse_slot(word)?),
+            ["bonus", word, amount] => {
+                let amount = amount
+                    .parse::<i32>()
+                    .map_err(|_| format!("invalid amount in '{}'", line))?;
+                draft.bonuses.push(parse_bonus(word, amount)?);
+            }
+            ["damage", dice] => draft.damage = Some(dice.parse::<Dice>()?),
+            ["count", count] => {
+                draft.count = count
+                    .parse::<i32>()
+                    .map_err(|_| format!("invalid count in '{}'", line))?;
+            }
+            _ => return Err(format!("invalid property '{}'", line).into()),
+        }
+    }
+    let items = drafts
+        .into_iter()
+        .map(Draft::finish)
+        .collect::<Result<Vec<ItemDef>, String>>()?;
+    Ok(items)
+}
+
+fn parse_color(words: &[&str]) -> Result<Color, String> {
+    if let [r, g, b] = words {
+        if let (Ok(r), Ok(g), Ok(b)) = (r.parse(), g.parse(), b.parse()) {
+            return Ok(Color::new(r, g, b));
+        }
+    }
+    match words {
+        [name] => match *name {
+            "white" => Ok(colors::WHITE),
+            "flame" => Ok(colors::FLAME),
+            "gold" => Ok(colors::GOLD),
+            "green" => Ok(colors::GREEN),
+            "sky" => Ok(colors::SKY),
+            "sepia" => Ok(colors::SEPIA),
+            "violet" => Ok(colors::VIOLET),
+            "light_amber" => Ok(colors::LIGHT_AMBER),
+            "light_blue" => Ok(colors::LIGHT_BLUE),
+            "light_cyan" => Ok(colors::LIGHT_CYAN),
+            "light_green" => Ok(colors::LIGHT_GREEN),
+            "light_grey" => Ok(colors::LIGHT_GREY),
+            "light_lime" => Ok(colors::LIGHT_LIME),
+            "light_orange" => Ok(colors::LIGHT_ORANGE),
+            "light_pink" => Ok(colors::LIGHT_PINK),
+            "light_red" => Ok(colors::LIGHT_RED),
+            "light_sepia" => Ok(colors::LIGHT_SEPIA),
+            "light_violet" => Ok(colors::LIGHT_VIOLET),
+            "light_yellow" => Ok(colors::LIGHT_YELLOW),
+            "lighter_blue" => Ok(colors::LIGHTER_BLUE),
+            "lighter_grey" => Ok(colors::LIGHTER_GREY),
+            "dark_grey" => Ok(colors::DARK_GREY),
+            "dark_sepia" => Ok(colors::DARK_SEPIA),
+            "darker_orange" => Ok(colors::DARKER_ORANGE),
+            _ => Err(format!("unknown color '{}'", name)),
+        },
+        _ => Err(format!("invalid color '{}'", words.join(" "))),
+    }
+}
+
+fn parse_kind(word: &str) -> Result<Item, String> {
+    match word {
+        "key" => Ok(Item::Key),
+        "heal" => Ok(Item::Heal),
+        "pickaxe" => Ok(Item::Pickaxe),
+        "see_invisible" => Ok(Item::SeeInvisible),
+        "strength" => Ok(Item::Strength),
+        "speed" => Ok(Item::Speed),
+        "poison" => Ok(Item::Poison),
+        "blindness" => Ok(Item::Blindness),
+        "paralysis" => Ok(Item::Paralysis),
+        "confusion" => Ok(Item::Confusion),
+        "regeneration" => Ok(Item::Regeneration),
+        "restoration" => Ok(Item::Restoration),
+        "polymorph" => Ok(Item::Polymorph),
+        "equipment" => Ok(Item::Equipment),
+        "dagger" => Ok(Item::Dagger),
+        "bow" => Ok(Item::Bow),
+        "arrow" => Ok(Item::Arrow),
+        "remove_curse" => Ok(Item::RemoveCurse),
+        "teleport" => Ok(Item::Teleport),
+        "magic_mapping" => Ok(Item::MagicMapping),
+        "enchant_weapon" => Ok(Item::EnchantWeapon),
+        "enchant_armor" => Ok(Item::EnchantArmor),
+        "fireball" => Ok(Item::Fireball),
+        "food" => Ok(Item::Food),
+        "material" => Ok(Item::Material),
+        "gold" => Ok(Item::Gold),
+        "bag" => Ok(Item::Bag),
+        "whetstone" => Ok(Item::Whetstone),
+        "repair_kit" => Ok(Item::RepairKit),
+        _ => Err(format!("unknown kind '{}'", word)),
+    }
+}
+
+fn parse_slot(word: &str) -> Result<Slot, String> {
+    match word {
+        "left_hand" => Ok(Slot::LeftHand),
+        "right_hand" => Ok(Slot::RightHand),
+        "both_hands" => Ok(Slot::BothHands),
+        "head" => Ok(Slot::Head),
+        "body" => Ok(Slot::Body),
+        "feet" => Ok(Slot::Feet),
+        "left_ring" => Ok(Slot::LeftRing),
+        "right_ring" => Ok(Slot::RightRing),
+        "neck" => Ok(Slot::Neck),
+        _ => Err(format!("unknown slot '{}'", word)),
+    }
+}
+
+fn parse_bonus(word: &str, amount: i32) -> Result<Bonus, String> {
+    match word {
+        "power" => Ok(Bonus::Power(amount)),
+        "defense" => Ok(Bonus::Defense(amount)),
+        "max_hp" => Ok(Bonus::MaxHp(amount)),
+        "regeneration" => Ok(Bonus::Regeneration(amount)),
+        "fire" => Ok(Bonus::Elemental(Element::Fire, amount)),
+        "frost" => Ok(Bonus::Elemental(Element::Frost, amount)),
+        "poison" => Ok(Bonus::Elemental(Element::Poison, amount)),
+        "critical" => Ok(Bonus::Critical(amount)),
+        "light" => Ok(Bonus::Light(amount)),
+        "block" => Ok(Bonus::Block(amount)),
+        "parry" => Ok(Bonus::Parry(amount)),
+        "vampiric" => Ok(Bonus::Vampiric(amount)),
+        "burn" => Ok(Bonus::Proc(Proc::Burn, amount)),
+        "freeze" => Ok(Bonus::Proc(Proc::Freeze, amount)),
+        "lifesteal" => Ok(Bonus::Proc(Proc::Lifesteal, amount)),
+        "knockback" => Ok(Bonus::Proc(Proc::Knockback, amount)),
+        _ => Err(format!("unknown bonus '{}'", word)),
+    }
+}
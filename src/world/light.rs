@@ -0,0 +1,107 @@
+/// Module Light
+///
+/// Lighting on top of the field of view. Light sources like braziers, lava and the player's torch
+/// light up the tiles around them, as long as nothing blocks the way between source and tile.
+/// Each tile stores how brightly it is lit, between 0 (pitch dark) and 1 (fully lit).
+/// How far the player sees depends on light as well: on dark levels it sees no further than its
+/// own light reaches.
+// internal modules
+use entity::object::Object;
+use game_state::{GameState, PLAYER};
+use world::{line, Terrain, World};
+
+/// Radius of the light that is given off by braziers and lava.
+pub const BRAZIER_LIGHT_RADIUS: i32 = 6;
+pub const LAVA_LIGHT_RADIUS: i32 = 2;
+/// Distance up to which the player sees on levels that aren't dark.
+pub const SIGHT_RADIUS: i32 = 10;
+// distance up to which the player sees on dark levels without any light
+const DARK_SIGHT_RADIUS: i32 = 2;
+
+/// Return the radius of the light that the player gives off: that of its torch, if it is lit,
+/// plus what the light sources among its equipment add.
+pub fn player_light_radius(game_state: &GameState, player: &Object) -> i32 {
+    let bonus: i32 = player
+        .get_all_equipped(game_state)
+        .iter()
+        .map(|e| e.light_bonus())
+        .sum();
+    (player.light_radius + bonus).max(0)
+}
+
+/// Return how far the player can see. On dark levels the player's own light limits its sight.
+/// Blindness doesn't change the field of view, only what the player perceives of it.
+pub fn sight_radius(game_state: &GameState, player: &Object) -> i32 {
+    let light = player_light_radius(game_state, player);
+    if game_state.world.dark {
+        light.max(DARK_SIGHT_RADIUS)
+    } else {
+        light.max(SIGHT_RADIUS)
+    }
+}
+
+/// Recompute the light level of every tile from the ambient light of the world's theme and
+/// from all light sources, with the player's light having the given radius. Needs to be called
+/// whenever a light source moves.
+pub fn update_light(world: &mut World, objects: &[Object], player_light: i32) {
+    // dark levels have no ambient light at all
+    let ambient = if world.dark {
+        0.0
+    } else {
+        world.theme.ambient_light()
+    };
+    for tile in &mut world.tiles {
+        tile.light = ambient;
+    }
+
+    let mut sources: Vec<(i32, i32, i32)> = objects
+        .iter()
+        .enumerate()
+        .map(|(id, object)| {
+            let radius = if id == PLAYER {
+                player_light
+            } else {
+                object.light_radius
+            };
+            (object.x, object.y, radius)
+        })
+        .filter(|&(_, _, radius)| radius > 0)
+        .collect();
+    for y in 0..world.height {
+        for x in 0..world.width {
+            if world.get(x, y).terrain == Terrain::Lava {
+                sources.push((x, y, LAVA_LIGHT_RADIUS));
+            }
+        }
+    }
+
+    for (source_x, source_y, radius) in sources {
+        for y in source_y - radius..=source_y + radius {
+            for x in source_x - radius..=source_x + radius {
+                if !world.in_bounds(x, y) {
+                    continue;
+                }
+                let distance = (((x - source_x).pow(2) + (y - source_y).pow(2)) as f32).sqrt();
+                if distance > radius as f32 || !is_lit_by(world, (source_x, source_y), (x, y)) {
+                    continue;
+                }
+                // light fades out towards the edge of the radius
+                let light = 1.0 - distance / (radius + 1) as f32;
+                let tile = world.get_mut(x, y);
+                tile.light = tile.light.max(light);
+            }
+        }
+    }
+}
+
+/// Return true if no tile between the light source and the target blocks sight. The target
+/// itself may block sight, so that walls are lit as well.
+fn is_lit_by(world: &World, source: (i32, i32), target: (i32, i32)) -> bool {
+    let points = line(source, target);
+    let between = points.len().saturating_sub(2);
+    points
+        .iter()
+        .skip(1)
+        .take(between)
+        .all(|&(x, y)| !world.get(x, y).block_sight)
+}
@@ -0,0 +1,197 @@
+/// Module Loot
+///
+/// Weapons and armor found in the dungeon can be better than their plain versions. Depending on
+/// its rarity, a piece of equipment gets a random prefix, suffix or both, like a "flaming sword
+/// of the bear", each of which adds a bonus. Rarer equipment turns up more often further down.
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// internal modules
+use backend::colors::{self, Color};
+use entity::equipment::{Bonus, Element, Slot};
+use entity::object::Object;
+use game_state::{from_dungeon_level, Transition};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Rarity {
+    Common,
+    /// Has either a prefix or a suffix.
+    Magic,
+    /// Has both a prefix and a suffix.
+    Rare,
+}
+
+impl Rarity {
+    fn color(self) -> Option<Color> {
+        match self {
+            Rarity::Common => None,
+            Rarity::Magic => Some(colors::LIGHT_BLUE),
+            Rarity::Rare => Some(colors::YELLOW),
+        }
+    }
+}
+
+/// An affix that can be rolled on equipment of the given slots.
+struct ItemAffix {
+    name: &'static str,
+    bonus: Bonus,
+    slots: &'static [Slot],
+}
+
+const WEAPONS: &[Slot] = &[Slot::RightHand, Slot::BothHands];
+const ARMOR: &[Slot] = &[Slot::LeftHand, Slot::Head, Slot::Body, Slot::Feet];
+const ALL: &[Slot] = &[
+    Slot::RightHand,
+    Slot::BothHands,
+    Slot::LeftHand,
+    Slot::Head,
+    Slot::Body,
+    Slot::Feet,
+];
+
+const PREFIXES: [ItemAffix; 6] = [
+    ItemAffix {
+        name: "flaming",
+        bonus: Bonus::Elemental(Element::Fire, 2),
+        slots: WEAPONS,
+    },
+    ItemAffix {
+        name: "freezing",
+        bonus: Bonus::Elemental(Element::Frost, 1),
+        slots: WEAPONS,
+    },
+    ItemAffix {
+        name: "venomous",
+        bonus: Bonus::Elemental(Element::Poison, 1),
+        slots: WEAPONS,
+    },
+    ItemAffix {
+        name: "keen",
+        bonus: Bonus::Power(1),
+        slots: WEAPONS,
+    },
+    ItemAffix {
+        name: "sturdy",
+        bonus: Bonus::Defense(1),
+        slots: ARMOR,
+    },
+    ItemAffix {
+        name: "reinforced",
+        bonus: Bonus::Defense(2),
+        slots: ARMOR,
+    },
+];
+
+const SUFFIXES: [ItemAffix; 5] = [
+    ItemAffix {
+        name: "of the bear",
+        bonus: Bonus::MaxHp(15),
+        slots: ALL,
+    },
+    ItemAffix {
+        name: "of the troll",
+        bonus: Bonus::Regeneration(1),
+        slots: ALL,
+    },
+    ItemAffix {
+        name: "of might",
+        bonus: Bonus::Power(1),
+        slots: ALL,
+    },
+    ItemAffix {
+        name: "of protection",
+        bonus: Bonus::Defense(1),
+        slots: ALL,
+    },
+    ItemAffix {
+        name: "of precision",
+        bonus: Bonus::Critical(10),
+        slots: WEAPONS,
+    },
+];
+
+/// Roll the rarity of the given piece of equipment, found on the given level, and give it the
+/// matching number of random affixes.
+pub fn add_affixes(item: &mut Object, rng: &mut StdRng, level: u32) {
+    let slot = match item.equipment.as_ref() {
+        Some(equipment) => equipment.slot,
+        None => return,
+    };
+    let magic_chance = from_dungeon_level(
+        &[
+            Transition {
+                level: 1,
+                value: 15,
+            },
+            Transition {
+                level: 5,
+                value: 30,
+            },
+        ],
+        level,
+    );
+    let rare_chance = from_dungeon_level(
+        &[
+            Transition { level: 4, value: 5 },
+            Transition {
+                level: 8,
+                value: 15,
+            },
+        ],
+        level,
+    );
+    let chances = [
+        (Rarity::Common, 100),
+        (Rarity::Magic, magic_chance),
+        (Rarity::Rare, rare_chance),
+    ];
+    let dist = WeightedIndex::new(chances.iter().map(|chance| chance.1)).unwrap();
+    let rarity = chances[dist.sample(rng)].0;
+
+    let (prefix, suffix) = match rarity {
+        Rarity::Common => (false, false),
+        Rarity::Magic => {
+            let prefix: bool = rng.gen();
+            (prefix, !prefix)
+        }
+        Rarity::Rare => (true, true),
+    };
+    if prefix {
+        if let Some(affix) = choose_affix(&PREFIXES, slot, rng) {
+            item.name = format!("{} {}", affix.name, item.name);
+            add_bonus(item, affix.bonus);
+        }
+    }
+    if suffix {
+        if let Some(affix) = choose_affix(&SUFFIXES, slot, rng) {
+            item.name = format!("{} {}", item.name, affix.name);
+            add_bonus(item, affix.bonus);
+        }
+    }
+    if let Some(color) = rarity.color() {
+        item.color = color;
+    }
+}
+
+fn choose_affix<'a>(
+    affixes: &'a [ItemAffix],
+    slot: Slot,
+    rng: &mut StdRng,
+) -> Option<&'a ItemAffix> {
+    let fitting: Vec<&ItemAffix> = affixes
+        .iter()
+        .filter(|affix| affix.slots.contains(&slot))
+        .collect();
+    if fitting.is_empty() {
+        None
+    } else {
+        Some(fitting[rng.gen_range(0, fitting.len())])
+    }
+}
+
+fn add_bonus(item: &mut Object, bonus: Bonus) {
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.bonuses.push(bonus);
+    }
+}
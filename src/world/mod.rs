@@ -0,0 +1,2385 @@
+/// Module World
+///
+/// The world contains all structures and methods for terrain/dungeon generation
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp;
+
+// internal modules
+use backend::colors;
+use entity::ai::{disguise_as, Ai};
+use entity::behavior::{behavior, cache_behaviors};
+use entity::boss::boss_phases;
+use entity::champion::{make_champion, Affix};
+use entity::container::{Container, CHEST_CHAR, CHEST_COLOR};
+use entity::dice::Dice;
+use entity::equipment::curse;
+use entity::experience::cache_xp_curve;
+use entity::faction::Faction;
+use entity::fighter::{DeathCallback, Fighter};
+use entity::item::add_to_inventory;
+use entity::npc::Npc;
+use entity::object::{MoveMode, Object};
+use entity::spawner::Spawner;
+use entity::spell::{Caster, Spell};
+use entity::trap::{Trap, TrapKind};
+use game_state::{from_dungeon_level, Transition, PLAYER};
+use world::artifact::{make_artifact, random_artifact, ARTIFACT_LEVEL};
+use world::astar::astar;
+pub use world::branch::{LevelId, BRANCHES};
+use world::drops::cache_drop_tables;
+use world::items::{create_item, load_registry};
+use world::light::BRAZIER_LIGHT_RADIUS;
+use world::loot::add_affixes;
+use world::mutant::make_mutant;
+use world::overworld::make_overworld;
+use world::scent::ScentMap;
+use world::tables::{cache_tables, chances, max_monsters, Table};
+use world::theme::{Theme, THEMES};
+use world::vault::{cache_vaults, vaults_for_level, Vault};
+use world::wfc::{cache_samples, samples};
+
+pub mod area;
+pub mod artifact;
+mod astar;
+mod branch;
+pub mod dijkstra;
+pub mod drops;
+pub mod fov;
+pub mod items;
+pub mod light;
+mod loot;
+mod mutant;
+pub mod noise;
+mod overworld;
+pub mod scent;
+mod tables;
+pub mod theme;
+mod vault;
+mod wfc;
+
+// world constraints
+pub const WORLD_WIDTH: i32 = 80;
+pub const WORLD_HEIGHT: i32 = 43;
+// room generation constraints
+const ROOM_MAX_SIZE: i32 = 10;
+const ROOM_MIN_SIZE: i32 = 6;
+const MAX_ROOMS: i32 = 30;
+// chance that a room is replaced by a vault
+const VAULT_CHANCE: f32 = 0.1;
+// chance that a room entrance gets a door, and that such a door is locked
+const DOOR_CHANCE: f32 = 0.5;
+const LOCKED_DOOR_CHANCE: f32 = 0.15;
+// number of extra tunnels between nearby rooms, which turn the chain of rooms into loops
+const LOOP_TUNNELS: i32 = 4;
+// chance that a room gets an additional passage to another room, hidden behind secret doors
+const SECRET_PASSAGE_CHANCE: f32 = 0.15;
+// chance that a room contains a mimic
+const MIMIC_CHANCE: f32 = 0.05;
+// chance that a room contains a nest, and the number of turns between the monsters it spawns
+const NEST_CHANCE: f32 = 0.05;
+const NEST_INTERVAL: i32 = 20;
+// chance that a level has a prisoner or a trader in one of its rooms
+const DUNGEON_NPC_CHANCE: f32 = 0.3;
+// chance that a level has a shop, and how many items its keeper offers
+const SHOP_CHANCE: f32 = 0.15;
+const SHOP_STOCK_SIZE: i32 = 8;
+// chance that an awake monster in a room patrols between its room and the neighbouring ones
+const PATROL_CHANCE: f32 = 0.3;
+// maximum size of pools of water, lava or chasms
+const POOL_MAX_SIZE: i32 = 4;
+// every level that is a multiple of this is a boss level, and the size of its arena
+pub const BOSS_LEVEL_INTERVAL: u32 = 5;
+const ARENA_WIDTH: i32 = 50;
+const ARENA_HEIGHT: i32 = 25;
+// chance that a room contains a chest, and the maximum number of items in it
+const CHEST_CHANCE: f32 = 0.15;
+const CHEST_MAX_ITEMS: i32 = 3;
+// chance that a food ration lies around in a room
+const FOOD_CHANCE: f32 = 0.1;
+// chance that a medicinal herb grows in a room, and that a room holds a workbench
+const HERB_CHANCE: f32 = 0.15;
+const WORKBENCH_CHANCE: f32 = 0.05;
+// chance that gold lies around in a room, and how much more there is per level
+const GOLD_CHANCE: f32 = 0.25;
+const GOLD_PER_LEVEL: i32 = 5;
+// chance that a treasure vault deep enough holds an artifact
+const ARTIFACT_CHANCE: f32 = 0.5;
+// chance that equipment in a chest outside of treasure vaults is cursed
+const CURSE_CHANCE: f32 = 0.3;
+// chance that a level contains a sealed treasure vault, its minimum size, and how many levels
+// deeper its guardians usually live
+const TREASURE_VAULT_CHANCE: f32 = 0.2;
+const TREASURE_VAULT_MIN_SIZE: i32 = 4;
+const TREASURE_GUARDIAN_LEVELS: u32 = 3;
+// number of times the wave function collapse generator may run into a contradiction
+const WFC_ATTEMPTS: i32 = 10;
+// chance that a free floor tile is decorated
+const DECORATION_CHANCE: f32 = 0.03;
+// number of turns it takes to wade into shallow water or to climb onto rubble
+const SHALLOW_WATER_MOVE_COST: i32 = 2;
+const RUBBLE_MOVE_COST: i32 = 2;
+// cost of moving diagonally compared to moving straight, used when looking for paths
+const DIAGONAL_MOVE_COST: f32 = 1.41;
+// extra cost of a path through a tile occupied by another creature
+const CROWD_MOVE_COST: i32 = 5;
+// how far spitters can spit
+const SPITTER_RANGE: i32 = 6;
+// mana of slime molds, enough for a few spells in a row
+const SLIME_MOLD_MANA: i32 = 10;
+// mana of drakes, enough to breathe twice in a row
+const DRAKE_MANA: i32 = 12;
+// from this level on, monsters are replaced by random mutants with the given chance
+const MUTANT_LEVEL: u32 = 10;
+const MUTANT_CHANCE: f32 = 0.5;
+// chance that a monster that does not hunt in packs is a champion
+const CHAMPION_CHANCE: f32 = 0.05;
+// chance that a monster, or a whole pack, is asleep when the level is created
+const SLEEP_CHANCE: f32 = 0.75;
+// number of monsters in a pack
+const PACK_MIN_SIZE: usize = 3;
+const PACK_MAX_SIZE: usize = 5;
+// damage dealt to anything standing in lava each turn, and to anything falling into a chasm
+pub const LAVA_DAMAGE: i32 = 8;
+pub const FALL_DAMAGE: i32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Door {
+    Open,
+    Closed,
+    Locked,
+    /// Looks like a wall until it is found by searching.
+    Secret,
+}
+
+/// The kind of ground a tile consists of.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Terrain {
+    Floor,
+    Wall,
+    /// Can only be crossed by swimmers.
+    DeepWater,
+    /// Can be waded through, but slowly.
+    ShallowWater,
+    /// Burns anything that stands in it.
+    Lava,
+    /// Anything that walks into it falls down to the next level.
+    Chasm,
+    /// Blocks movement and sight like a wall, but stands freely within a room.
+    Pillar,
+}
+
+/// The material that solid terrain is made of, which decides how hard it is to dig through.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Material {
+    Earth,
+    Rock,
+    /// Masonry, like the walls of buildings and fortresses.
+    Stone,
+    /// Cannot be dug through at all, e.g. the border of the world.
+    Bedrock,
+}
+
+impl Material {
+    /// Return the number of strokes with a pickaxe it takes to dig through this material, or
+    /// `None` if it cannot be dug through.
+    pub fn hardness(self) -> Option<i32> {
+        match self {
+            Material::Earth => Some(1),
+            Material::Rock => Some(3),
+            Material::Stone => Some(6),
+            Material::Bedrock => None,
+        }
+    }
+}
+
+/// Furniture and other decorations that are scattered across levels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decoration {
+    Pillar,
+    Rubble,
+    Bones,
+    Brazier,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Tile {
+    pub terrain: Terrain,
+    pub blocked: bool,
+    pub block_sight: bool,
+    pub explored: bool,
+    pub door: Option<Door>,
+    pub material: Material,
+    // how far this tile has been dug into
+    pub dug: i32,
+    // how brightly this tile is lit, between 0 and 1
+    pub light: f32,
+    // number of turns it takes to enter this tile
+    pub move_cost: i32,
+}
+
+impl Tile {
+    /// Create a tile of the given terrain. Only walls and pillars block movement and sight.
+    /// Walls are made of rock, pillars of stone.
+    pub fn new(terrain: Terrain) -> Self {
+        let solid = terrain == Terrain::Wall || terrain == Terrain::Pillar;
+        let material = match terrain {
+            Terrain::Wall => Material::Rock,
+            Terrain::Pillar => Material::Stone,
+            _ => Material::Earth,
+        };
+        Tile {
+            terrain,
+            blocked: solid,
+            block_sight: solid,
+            explored: false,
+            door: None,
+            material,
+            dug: 0,
+            light: 0.0,
+            move_cost: if terrain == Terrain::ShallowWater {
+                SHALLOW_WATER_MOVE_COST
+            } else {
+                1
+            },
+        }
+    }
+
+    pub fn empty() -> Self {
+        Tile::new(Terrain::Floor)
+    }
+
+    pub fn wall() -> Self {
+        Tile::new(Terrain::Wall)
+    }
+
+    /// Return what the tile looks like to the player. Secret doors look like walls.
+    pub fn name(&self) -> &'static str {
+        match self.door {
+            Some(Door::Open) => return "open door",
+            Some(Door::Closed) => return "closed door",
+            Some(Door::Locked) => return "locked door",
+            Some(Door::Secret) => return "wall",
+            None => {}
+        }
+        match self.terrain {
+            Terrain::Floor => "floor",
+            Terrain::Wall => "wall",
+            Terrain::DeepWater => "deep water",
+            Terrain::ShallowWater => "shallow water",
+            Terrain::Lava => "lava",
+            Terrain::Chasm => "chasm",
+            Terrain::Pillar => "pillar",
+        }
+    }
+
+    /// Return true if a creature that moves in the given way cannot enter this tile.
+    pub fn blocks(&self, move_mode: MoveMode) -> bool {
+        match move_mode {
+            MoveMode::Phase => self.material == Material::Bedrock,
+            MoveMode::Swim => self.blocked,
+            MoveMode::Walk => self.blocked || self.terrain == Terrain::DeepWater,
+        }
+    }
+
+    /// Return true if standing on this tile hurts or otherwise endangers creatures.
+    pub fn is_hazardous(&self) -> bool {
+        self.terrain == Terrain::Lava || self.terrain == Terrain::Chasm
+    }
+
+    /// Create a closed door.
+    pub fn door() -> Self {
+        let mut tile = Tile::empty();
+        tile.set_door(Door::Closed);
+        tile
+    }
+
+    /// Return true if this tile is a wall or pillar that can be dug through.
+    pub fn is_diggable(&self) -> bool {
+        (self.terrain == Terrain::Wall || self.terrain == Terrain::Pillar)
+            && self.door.is_none()
+            && self.material.hardness().is_some()
+    }
+
+    /// Dig into this tile with the given strength. Once it has been dug into often enough for
+    /// its material, it turns into floor. Return true if the tile was dug through.
+    pub fn dig(&mut self, strength: i32) -> bool {
+        if !self.is_diggable() {
+            return false;
+        }
+        self.dug += strength;
+        if self.dug < self.material.hardness().unwrap() {
+            return false;
+        }
+        let explored = self.explored;
+        *self = Tile::empty();
+        self.explored = explored;
+        true
+    }
+
+    /// Return true if this tile can be walked on, possibly after opening a door.
+    pub fn is_walkable(&self) -> bool {
+        !self.blocked || self.door.is_some()
+    }
+
+    /// Open, close, lock or hide the door on this tile. All doors but open ones block movement
+    /// and sight.
+    pub fn set_door(&mut self, door: Door) {
+        self.door = Some(door);
+        self.blocked = door != Door::Open;
+        self.block_sight = door != Door::Open;
+    }
+}
+
+/// The world is a grid of tiles, stored row by row in a single vector.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct World {
+    pub width: i32,
+    pub height: i32,
+    pub theme: Theme,
+    tiles: Vec<Tile>,
+    /// The trail of scent the player has left in this world.
+    pub scent: ScentMap,
+    /// Dark levels have no ambient light, and the player sees only as far as its light reaches.
+    pub dark: bool,
+}
+
+impl World {
+    /// Create a world of the given size and theme that is completely filled with `tile`.
+    pub fn new(width: i32, height: i32, theme: Theme, tile: Tile) -> Self {
+        World {
+            width,
+            height,
+            theme,
+            tiles: vec![tile; (width * height) as usize],
+            scent: ScentMap::new(width, height),
+            dark: false,
+        }
+    }
+
+    /// Return true if the given coordinates lie within the world.
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    /// Return the tile at the given coordinates. Panics when they are out of bounds.
+    pub fn get(&self, x: i32, y: i32) -> &Tile {
+        &self.tiles[self.index(x, y)]
+    }
+
+    /// Mutably borrow the tile at the given coordinates. Panics when they are out of bounds.
+    pub fn get_mut(&mut self, x: i32, y: i32) -> &mut Tile {
+        let index = self.index(x, y);
+        &mut self.tiles[index]
+    }
+
+    /// Make the walls of the world's border impossible to dig through.
+    fn harden_border(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1 {
+                    self.get_mut(x, y).material = Material::Bedrock;
+                }
+            }
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        assert!(
+            self.in_bounds(x, y),
+            "({}, {}) is outside of the world",
+            x,
+            y
+        );
+        (y * self.width + x) as usize
+    }
+}
+
+/// Level styles the world generator can produce.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelStyle {
+    /// Rectangular rooms connected by straight tunnels.
+    Rooms,
+    /// Chaotic cave-like levels carved by a drunkard's walk.
+    DrunkardsWalk,
+    /// Levels that resemble one of the sample layouts, made by wave function collapse.
+    WaveFunctionCollapse,
+    /// A single large arena that is guarded by a boss.
+    BossArena,
+    /// The surface with the town and the entrances to all dungeons.
+    Overworld,
+}
+
+/// Load all game data from the asset files and report anything that is missing or invalid.
+/// This happens before the game takes over the screen, so errors are written to stderr.
+pub fn load_assets() {
+    load_registry();
+    cache_behaviors();
+    cache_drop_tables();
+    cache_samples();
+    cache_tables();
+    cache_vaults();
+    cache_xp_curve();
+    // monsters in the spawn tables may name the behavior they use after a slash
+    for &theme in &THEMES {
+        for (entry, _) in chances(theme, Table::Monsters, 1) {
+            if let Some(name) = entry.split('/').nth(1) {
+                if behavior(name).is_none() {
+                    eprintln!("unknown behavior {} in the {} tables", name, theme.name());
+                }
+            }
+        }
+    }
+}
+
+/// Generate the level with the given id. The same seed and id always produce the same world
+/// and objects.
+pub fn make_world(objects: &mut Vec<Object>, seed: u64, id: LevelId) -> World {
+    // every level gets its own random number generator, derived from the world seed
+    let level_seed = seed
+        .wrapping_add(u64::from(id.depth))
+        .wrapping_add((id.branch as u64) << 32);
+    let mut rng = StdRng::seed_from_u64(level_seed);
+
+    // difficult branches start out at a higher level, with more dangerous themes and tables
+    let level = id.difficulty();
+
+    // fill the world with `unblocked` tiles
+    let theme = BRANCHES[id.branch]
+        .theme
+        .filter(|_| id.depth > 0)
+        .unwrap_or_else(|| Theme::for_level(level));
+    let mut world = World::new(WORLD_WIDTH, WORLD_HEIGHT, theme, Tile::wall());
+
+    // PLayer is the first element, remove everything else.
+    // NOTE: works only if player is the first object!
+    assert_eq!(&objects[PLAYER] as *const _, &objects[0] as *const _);
+    objects.truncate(1);
+
+    let style = if level == 0 {
+        LevelStyle::Overworld
+    } else if level.is_multiple_of(BOSS_LEVEL_INTERVAL) {
+        LevelStyle::BossArena
+    } else {
+        let roll = rng.gen::<f32>();
+        if roll < theme.drunkard_chance() {
+            LevelStyle::DrunkardsWalk
+        } else if roll < theme.drunkard_chance() + theme.wfc_chance() {
+            LevelStyle::WaveFunctionCollapse
+        } else {
+            LevelStyle::Rooms
+        }
+    };
+
+    match style {
+        LevelStyle::Rooms => make_rooms(&mut world, objects, &mut rng, level),
+        LevelStyle::DrunkardsWalk => {
+            let coverage = theme.drunkard_coverage();
+            make_drunkards_walk(&mut world, objects, &mut rng, level, coverage)
+        }
+        LevelStyle::WaveFunctionCollapse => {
+            make_wave_function_collapse(&mut world, objects, &mut rng, level)
+        }
+        LevelStyle::BossArena => make_boss_arena(&mut world, objects, level),
+        LevelStyle::Overworld => make_overworld(&mut world, objects, &mut rng),
+    }
+
+    // the last level of a branch leads nowhere further down
+    if id.is_bottom() {
+        objects.retain(|object| object.name != "stairs" && object.name != "sealed stairs");
+        for tile in world
+            .tiles
+            .iter_mut()
+            .filter(|tile| tile.terrain == Terrain::Chasm)
+        {
+            *tile = Tile::empty();
+        }
+    }
+
+    ensure_connectivity(&mut world, objects);
+
+    // don't let the player start on a trap or have one hidden under the stairs
+    let (start_x, start_y) = objects[PLAYER].pos();
+    let stairs: Vec<(i32, i32)> = objects
+        .iter()
+        .filter(|object| object.name == "stairs")
+        .map(|object| object.pos())
+        .collect();
+    objects.retain(|object| {
+        object.trap.is_none()
+            || (object.pos() != (start_x, start_y) && !stairs.contains(&object.pos()))
+    });
+    place_keys(&world, objects, &mut rng);
+    place_branch_entrances(&world, objects, &mut rng, id);
+
+    // every dungeon level can be left upwards from where the player arrives
+    if level > 0 {
+        place_up_stairs(objects, start_x, start_y);
+    }
+
+    place_decorations(&mut world, objects, &mut rng);
+
+    // walls of plain rock are made of the theme's material instead, except for the border that
+    // nobody may dig through
+    let material = theme.wall_material();
+    for tile in world
+        .tiles
+        .iter_mut()
+        .filter(|tile| tile.terrain == Terrain::Wall && tile.material == Material::Rock)
+    {
+        tile.material = material;
+    }
+    world.harden_border();
+
+    // the town and the boss arenas are never shrouded in darkness
+    world.dark = (style == LevelStyle::Rooms
+        || style == LevelStyle::DrunkardsWalk
+        || style == LevelStyle::WaveFunctionCollapse)
+        && rng.gen::<f32>() < theme.dark_chance();
+
+    world
+}
+
+fn make_rooms(world: &mut World, objects: &mut Vec<Object>, rng: &mut StdRng, level: u32) {
+    // hand-designed vaults that fit this level
+    let vaults: Vec<Vault> = vaults_for_level(level);
+
+    // create rooms randomly
+    let mut rooms = vec![];
+
+    for _ in 0..MAX_ROOMS {
+        // occasionally use a vault instead of an empty room
+        let vault = if !vaults.is_empty() && rng.gen::<f32>() < VAULT_CHANCE {
+            Some(&vaults[rng.gen_range(0, vaults.len())])
+        } else {
+            None
+        };
+
+        // random width and height, or the size of the vault
+        let (w, h) = match vault {
+            // a room's walls lie on its borders, so it is one tile wider than its width
+            Some(vault) => (vault.width - 1, vault.height - 1),
+            None => (
+                rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1),
+                rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1),
+            ),
+        };
+
+        // random position without exceeding the boundaries of the map
+        let x = rng.gen_range(0, WORLD_WIDTH - w);
+        let y = rng.gen_range(0, WORLD_HEIGHT - h);
+
+        // create room and store in vector, vaults are always rectangular
+        let shape = match vault {
+            Some(_) => RoomShape::Rectangle,
+            None => RoomShape::random(rng),
+        };
+        let new_room = Room::new(Rect::new(x, y, w, h), shape);
+        let failed = rooms
+            .iter()
+            .any(|other_room| new_room.intersects_with(other_room));
+
+        if !failed {
+            // no intersections, we have a valid room.
+            match vault {
+                // vaults bring their own content
+                Some(vault) => vault.stamp(world, objects, x, y),
+                None => {
+                    create_room(world, new_room);
+
+                    // add some content to the room
+                    place_objects(world, objects, rng, new_room, level);
+                }
+            }
+
+            let (new_x, new_y) = new_room.center();
+            if rooms.is_empty() {
+                // this is the first room, save position as starting point for the player
+                objects[PLAYER].set_pos(new_x, new_y);
+            } else {
+                // all rooms after the first:
+                // connect it to the previous room with a tunnel
+
+                // center coordinates of the previous room
+                let prev = rooms[rooms.len() - 1].center();
+                dig_tunnel(world, rng, prev, (new_x, new_y));
+            }
+            // finally, append new room to list
+            rooms.push(new_room);
+        }
+    }
+
+    // connect rooms with their nearest neighbours, so that the level contains loops
+    if rooms.len() > 2 {
+        for _ in 0..LOOP_TUNNELS {
+            let i = rng.gen_range(0, rooms.len());
+            let (x, y) = rooms[i].center();
+            // rooms next to each other in the list are connected already
+            let nearest = (0..rooms.len())
+                .filter(|&j| j + 1 < i || j > i + 1)
+                .min_by_key(|&j| {
+                    let (other_x, other_y) = rooms[j].center();
+                    (other_x - x).pow(2) + (other_y - y).pow(2)
+                });
+            if let Some(j) = nearest {
+                dig_tunnel(world, rng, (x, y), rooms[j].center());
+            }
+        }
+    }
+
+    // connect some rooms with hidden passages
+    for i in 0..rooms.len() {
+        if rooms.len() > 2 && rng.gen::<f32>() < SECRET_PASSAGE_CHANCE {
+            let other = rng.gen_range(0, rooms.len());
+            // rooms next to each other in the list are connected already
+            if other + 1 < i || other > i + 1 {
+                dig_secret_passage(world, rng, rooms[i], rooms[other]);
+            }
+        }
+    }
+
+    // put doors into some of the room entrances
+    for room in &rooms {
+        place_doors(world, rng, *room);
+    }
+
+    assign_patrols(objects, rng, &rooms);
+
+    // create stairs at the center of the last room
+    let (last_room_x, last_room_y) = rooms[rooms.len() - 1].center();
+    place_stairs(objects, last_room_x, last_room_y);
+
+    // fill some rooms with pools, but keep the player's start and the stairs on solid ground
+    if rooms.len() > 2 {
+        for room in &rooms[1..rooms.len() - 1] {
+            if rng.gen::<f32>() < world.theme.pool_chance() {
+                place_pool(world, objects, rng, *room, level);
+            }
+        }
+    }
+
+    place_dungeon_npc(world, objects, rng, &rooms);
+    if rng.gen::<f32>() < SHOP_CHANCE {
+        place_shop(world, objects, rng, &rooms, level);
+    }
+
+    if !rooms.is_empty() && rng.gen::<f32>() < TREASURE_VAULT_CHANCE {
+        place_treasure_vault(world, objects, rng, &rooms, level);
+    }
+}
+
+/// Send some of the awake monsters on patrol. Their route leads from their room to the centers
+/// of the rooms it is connected to by tunnels, and back again.
+fn assign_patrols(objects: &mut [Object], rng: &mut StdRng, rooms: &[Room]) {
+    for monster in objects.iter_mut() {
+        if monster.ai != Some(Ai::Basic) || rng.gen::<f32>() >= PATROL_CHANCE {
+            continue;
+        }
+        let i = match rooms
+            .iter()
+            .position(|room| room.contains(monster.x, monster.y))
+        {
+            Some(i) => i,
+            None => continue,
+        };
+        // rooms next to each other in the list are connected by a tunnel
+        let mut route = vec![];
+        for j in [i.wrapping_sub(1), i + 1]
+            .iter()
+            .filter(|&&j| j < rooms.len())
+        {
+            route.push(rooms[i].center());
+            route.push(rooms[*j].center());
+        }
+        if !route.is_empty() {
+            monster.ai = Some(Ai::Patrol { route, waypoint: 0 });
+        }
+    }
+}
+
+/// Try to carve a small treasure vault into solid rock and connect it to the nearest room with
+/// a tunnel that is sealed by a locked or secret door.
+fn place_treasure_vault(
+    world: &mut World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    rooms: &[Room],
+    level: u32,
+) {
+    for _ in 0..MAX_ROOMS {
+        let w = rng.gen_range(TREASURE_VAULT_MIN_SIZE, ROOM_MIN_SIZE + 1);
+        let h = rng.gen_range(TREASURE_VAULT_MIN_SIZE, ROOM_MIN_SIZE + 1);
+        let x = rng.gen_range(1, WORLD_WIDTH - w - 1);
+        let y = rng.gen_range(1, WORLD_HEIGHT - h - 1);
+        let mut vault = Room::new(Rect::new(x, y, w, h), RoomShape::Rectangle);
+        vault.treasure = true;
+
+        // the vault and the rock around it must not have been touched by anything else
+        let rect = vault.rect;
+        let solid = (rect.x1 - 1..=rect.x2 + 1)
+            .all(|x| (rect.y1 - 1..=rect.y2 + 1).all(|y| world.get(x, y).terrain == Terrain::Wall));
+        if !solid {
+            continue;
+        }
+
+        let (center_x, center_y) = vault.center();
+        let nearest = rooms
+            .iter()
+            .min_by_key(|room| {
+                let (x, y) = room.center();
+                (x - center_x).pow(2) + (y - center_y).pow(2)
+            })
+            .unwrap();
+        let end = nearest.center();
+        let corner = if rng.gen() {
+            (end.0, center_y)
+        } else {
+            (center_x, end.1)
+        };
+        let mut path = straight_line((center_x, center_y), corner);
+        path.extend(straight_line(corner, end));
+
+        create_room(world, vault);
+        for &(x, y) in &path {
+            if world.get(x, y).terrain == Terrain::Wall {
+                *world.get_mut(x, y) = Tile::empty();
+            }
+        }
+        // the way in leads through the vault's wall
+        if let Some(&(x, y)) = path.iter().find(|&&(x, y)| !vault.contains(x, y)) {
+            let door = if rng.gen() {
+                Door::Locked
+            } else {
+                Door::Secret
+            };
+            world.get_mut(x, y).set_door(door);
+        }
+
+        place_objects(world, objects, rng, vault, level);
+        return;
+    }
+}
+
+/// Build a boss level: one large arena with the player's start on its left, the boss on its
+/// right and the boss' reward and the sealed down stairs behind it. The stairs open once the
+/// boss is dead.
+fn make_boss_arena(world: &mut World, objects: &mut Vec<Object>, level: u32) {
+    let x = (WORLD_WIDTH - ARENA_WIDTH) / 2;
+    let y = (WORLD_HEIGHT - ARENA_HEIGHT) / 2;
+    let arena = Rect::new(x, y, ARENA_WIDTH, ARENA_HEIGHT);
+    create_room(world, Room::new(arena, RoomShape::Rectangle));
+    let (center_x, center_y) = arena.center();
+
+    // two rows of pillars give some cover while fighting the boss
+    for dx in &[-12, -4, 4, 12] {
+        for dy in &[-5, 5] {
+            *world.get_mut(center_x + dx, center_y + dy) = Tile::new(Terrain::Pillar);
+        }
+    }
+
+    objects[PLAYER].set_pos(arena.x1 + 2, center_y);
+    objects.push(make_boss(world.theme, level, arena.x2 - 6, center_y));
+    for dy in &[-1, 1] {
+        objects.extend(make_item("healing potion", arena.x2 - 2, center_y + dy));
+    }
+
+    let mut stairs = Object::new(
+        arena.x2 - 2,
+        center_y,
+        "sealed stairs",
+        false,
+        '<',
+        colors::DARK_GREY,
+    );
+    stairs.always_visible = true;
+    objects.push(stairs);
+}
+
+/// Create a peaceful inhabitant of the town.
+fn make_npc(name: &str, x: i32, y: i32) -> Object {
+    let (chr, npc) = match name {
+        "shopkeeper" => (
+            '$',
+            Npc::Shopkeeper {
+                stock: ["healing potion", "key", "pickaxe", "food ration"]
+                    .iter()
+                    .filter_map(|name| make_item(name, 0, 0))
+                    .collect(),
+            },
+        ),
+        "prisoner" => ('@', Npc::Prisoner),
+        "smith" => ('&', Npc::Smith),
+        "trader" => (
+            '@',
+            Npc::Trader {
+                stock: vec![
+                    "healing potion".into(),
+                    "key".into(),
+                    "potion of true sight".into(),
+                ],
+            },
+        ),
+        _ => ('h', Npc::Healer),
+    };
+    let mut npc_object = Object::new(x, y, name, true, chr, colors::LIGHT_GREEN);
+    npc_object.npc = Some(npc);
+    npc_object.alive = true;
+    // those met in the dungeon can defend themselves
+    if name == "prisoner" || name == "trader" {
+        npc_object.fighter = Some(Fighter {
+            base_max_hp: 15,
+            hp: 15,
+            base_defense: 0,
+            base_power: 0,
+            damage: Dice::new(1, 5, 0),
+            on_death: DeathCallback::Monster,
+            xp: 0,
+        });
+    }
+    if name == "trader" {
+        if let Some(behavior) = behavior("wanderer") {
+            npc_object.ai = Some(Ai::Scripted { behavior, state: 0 });
+        }
+    }
+    npc_object
+}
+
+/// Occasionally put a prisoner or a wandering trader into one of the rooms, except for the
+/// first and the last one.
+fn place_dungeon_npc(world: &World, objects: &mut Vec<Object>, rng: &mut StdRng, rooms: &[Room]) {
+    if rooms.len() <= 2 || rng.gen::<f32>() >= DUNGEON_NPC_CHANCE {
+        return;
+    }
+    let room = rooms[rng.gen_range(1, rooms.len() - 1)];
+    let (x, y) = room.center();
+    if !is_blocked(world, objects, x, y) && !world.get(x, y).is_hazardous() {
+        let name = if rng.gen() { "prisoner" } else { "trader" };
+        objects.push(make_npc(name, x, y));
+    }
+}
+
+/// Turn one of the rooms, except for the first and the last one, into a shop whose keeper sells
+/// items that are usually found on this level.
+fn place_shop(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    rooms: &[Room],
+    level: u32,
+) {
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    if rooms.len() <= 2 {
+        return;
+    }
+    let room = rooms[rng.gen_range(1, rooms.len() - 1)];
+    let (x, y) = room.center();
+    if is_blocked(world, objects, x, y) || world.get(x, y).is_hazardous() {
+        return;
+    }
+    let item_chances = chances(world.theme, Table::Items, level);
+    let item_dist = match WeightedIndex::new(item_chances.iter().map(|item| item.1)) {
+        Ok(item_dist) => item_dist,
+        Err(_) => return,
+    };
+    let mut stock: Vec<Object> = vec![];
+    for _ in 0..SHOP_STOCK_SIZE {
+        // items in a shop have no position
+        if let Some(mut item) = make_item(&item_chances[item_dist.sample(rng)].0, 0, 0) {
+            add_affixes(&mut item, rng, level);
+            add_to_inventory(&mut stock, item);
+        }
+    }
+    let mut shopkeeper = make_npc("shopkeeper", x, y);
+    shopkeeper.npc = Some(Npc::Shopkeeper { stock });
+    objects.push(shopkeeper);
+}
+
+/// Create the boss of a boss level. Bosses get stronger the deeper they are found.
+fn make_boss(theme: Theme, level: u32, x: i32, y: i32) -> Object {
+    let tier = (level / BOSS_LEVEL_INTERVAL) as i32;
+    let mut boss = Object::new(x, y, theme.boss_name(), true, 'B', colors::LIGHT_FLAME);
+    boss.fighter = Some(Fighter {
+        base_max_hp: 40 + 30 * tier,
+        hp: 40 + 30 * tier,
+        base_defense: 1 + 2 * tier,
+        base_power: 3 + 3 * tier,
+        damage: Dice::new(1, 3, 0),
+        on_death: DeathCallback::Boss,
+        xp: 500 * tier,
+    });
+    boss.ai = Some(Ai::Boss {
+        phases: boss_phases(theme.boss_minion()),
+        recovering: false,
+    });
+    boss.faction = Faction::Prions;
+    // every boss guards an artifact of its own, dropped on death
+    boss.inventory = Some(vec![make_artifact((tier - 1) as usize, x, y)]);
+    boss.alive = true;
+    boss
+}
+
+/// Fill a random part of the room with water, lava or a chasm, depending on the level. Pools
+/// are only placed on free floor that is surrounded by more free floor, so creatures can always
+/// walk around them.
+fn place_pool(
+    world: &mut World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    room: Room,
+    level: u32,
+) {
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let pool_chances = world.theme.pool_chances(level);
+    let pool_dist = WeightedIndex::new(pool_chances.iter().map(|item| item.1)).unwrap();
+    let terrain = pool_chances[pool_dist.sample(rng)].0;
+
+    // the pool and a ring of floor around it have to fit into the room's interior
+    let room = room.rect;
+    let max_w = cmp::min(POOL_MAX_SIZE, room.x2 - room.x1 - 3);
+    let max_h = cmp::min(POOL_MAX_SIZE, room.y2 - room.y1 - 3);
+    if max_w < 1 || max_h < 1 {
+        return;
+    }
+    let w = rng.gen_range(1, max_w + 1);
+    let h = rng.gen_range(1, max_h + 1);
+    let x = rng.gen_range(room.x1 + 2, room.x2 - w);
+    let y = rng.gen_range(room.y1 + 2, room.y2 - h);
+
+    let free_floor = |x: i32, y: i32| {
+        world.get(x, y).terrain == Terrain::Floor
+            && world.get(x, y).door.is_none()
+            && !objects.iter().any(|object| object.pos() == (x, y))
+    };
+    let fits = (x - 1..=x + w).all(|px| (y - 1..=y + h).all(|py| free_floor(px, py)));
+    if !fits {
+        return;
+    }
+
+    for px in x..x + w {
+        for py in y..y + h {
+            *world.get_mut(px, py) = Tile::new(terrain);
+        }
+    }
+    // deep water is surrounded by shallows
+    if terrain == Terrain::DeepWater {
+        for px in x - 1..=x + w {
+            for py in y - 1..=y + h {
+                if world.get(px, py).terrain == Terrain::Floor {
+                    *world.get_mut(px, py) = Tile::new(Terrain::ShallowWater);
+                }
+            }
+        }
+    }
+
+    // some water pools are inhabited
+    if terrain == Terrain::DeepWater && rng.gen() {
+        if let Some(swimmer) = make_monster("paramecium", x, y) {
+            objects.push(swimmer);
+        }
+    }
+}
+
+/// Turn entrances along the walls of the given room into doors. An entrance is a floor tile in
+/// the room's wall that is flanked by walls, so the door sits neatly in a doorway.
+fn place_doors(world: &mut World, rng: &mut StdRng, room: Room) {
+    // the walls of a room are the tiles right next to its interior
+    let rect = room.rect;
+    let walls = (rect.x1..=rect.x2)
+        .flat_map(|x| (rect.y1..=rect.y2).map(move |y| (x, y)))
+        .filter(|&(x, y)| {
+            !room.contains(x, y)
+                && (room.contains(x - 1, y)
+                    || room.contains(x + 1, y)
+                    || room.contains(x, y - 1)
+                    || room.contains(x, y + 1))
+        });
+    for (x, y) in walls {
+        if !world.in_bounds(x, y) || world.get(x, y).blocked {
+            continue;
+        }
+        let is_wall = |x: i32, y: i32| !world.in_bounds(x, y) || world.get(x, y).blocked;
+        let in_doorway = (is_wall(x - 1, y)
+            && is_wall(x + 1, y)
+            && !is_wall(x, y - 1)
+            && !is_wall(x, y + 1))
+            || (is_wall(x, y - 1) && is_wall(x, y + 1) && !is_wall(x - 1, y) && !is_wall(x + 1, y));
+        if in_doorway && rng.gen::<f32>() < DOOR_CHANCE {
+            *world.get_mut(x, y) = Tile::door();
+            if rng.gen::<f32>() < LOCKED_DOOR_CHANCE {
+                world.get_mut(x, y).set_door(Door::Locked);
+            }
+        }
+    }
+}
+
+/// Dig a tunnel between the centers of both rooms whose ends are hidden behind secret doors.
+fn dig_secret_passage(world: &mut World, rng: &mut StdRng, from: Room, to: Room) {
+    let start = from.center();
+    let end = to.center();
+    let corner = if rng.gen() {
+        (end.0, start.1)
+    } else {
+        (start.0, end.1)
+    };
+    let mut path = straight_line(start, corner);
+    path.extend(straight_line(corner, end));
+
+    // the first and last wall on the way become secret doors, everything else is dug out
+    let walls: Vec<(i32, i32)> = path
+        .iter()
+        .cloned()
+        .filter(|&(x, y)| world.get(x, y).terrain == Terrain::Wall)
+        .collect();
+    let (first, last) = match (walls.first(), walls.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return,
+    };
+    for &(x, y) in &walls {
+        *world.get_mut(x, y) = Tile::empty();
+    }
+    for &(x, y) in &[first, last] {
+        world.get_mut(x, y).set_door(Door::Secret);
+    }
+}
+
+/// Return all positions on the line from `start` to `end`, both included, as drawn by
+/// Bresenham's algorithm.
+pub fn line(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((end.0 - start.0).abs(), -(end.1 - start.1).abs());
+    let (step_x, step_y) = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+    let mut error = dx + dy;
+    let (mut x, mut y) = start;
+    let mut points = vec![start];
+    while (x, y) != end {
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+        points.push((x, y));
+    }
+    points
+}
+
+/// Trace a projectile that flies from `from` towards `to`. Return the positions it passes, up
+/// to the first solid tile or blocking object it hits, or up to the target if nothing is in
+/// the way.
+pub fn trace_projectile(
+    world: &World,
+    objects: &[Object],
+    from: (i32, i32),
+    to: (i32, i32),
+) -> Vec<(i32, i32)> {
+    let mut path = vec![];
+    for (x, y) in line(from, to).into_iter().skip(1) {
+        path.push((x, y));
+        if is_blocked(world, objects, x, y) {
+            break;
+        }
+    }
+    path
+}
+
+/// Return true if a projectile from `from` would reach `to` without hitting anything on the
+/// way. Unlike being in view, this also requires the way to be free of creatures.
+pub fn has_line_of_fire(
+    world: &World,
+    objects: &[Object],
+    from: (i32, i32),
+    to: (i32, i32),
+) -> bool {
+    trace_projectile(world, objects, from, to).last() == Some(&to)
+}
+
+/// Return all positions on the horizontal or vertical line between both positions.
+fn straight_line(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+    let steps = cmp::max((end.0 - start.0).abs(), (end.1 - start.1).abs());
+    (0..=steps)
+        .map(|i| (start.0 + dx * i, start.1 + dy * i))
+        .collect()
+}
+
+/// Carve a cave-like level by letting a "drunk" walker stumble randomly through solid rock,
+/// starting in the center of the world, until `coverage` (a fraction between 0 and 1) of all
+/// tiles is walkable. Every carved tile lies on the walker's path, so the walkable area is
+/// always connected.
+fn make_drunkards_walk(
+    world: &mut World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    coverage: f32,
+) {
+    // keep a one tile thick border of walls around the level
+    let (min_x, max_x) = (1, WORLD_WIDTH - 2);
+    let (min_y, max_y) = (1, WORLD_HEIGHT - 2);
+    let max_floor = ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize;
+    let target_floor = cmp::min(
+        cmp::max((coverage * (WORLD_WIDTH * WORLD_HEIGHT) as f32) as usize, 1),
+        max_floor,
+    );
+
+    // all carved tiles in the order they were dug out
+    let mut floor: Vec<(i32, i32)> = vec![];
+    let (mut x, mut y) = (WORLD_WIDTH / 2, WORLD_HEIGHT / 2);
+    while floor.len() < target_floor {
+        if world.get(x, y).blocked {
+            *world.get_mut(x, y) = Tile::empty();
+            floor.push((x, y));
+        }
+        // stumble into one of the four directions, without leaving the level
+        let (dx, dy) = match rng.gen_range(0, 4) {
+            0 => (1, 0),
+            1 => (-1, 0),
+            2 => (0, 1),
+            _ => (0, -1),
+        };
+        x = cmp::max(min_x, cmp::min(max_x, x + dx));
+        y = cmp::max(min_y, cmp::min(max_y, y + dy));
+    }
+
+    populate_open_level(world, objects, rng, level, &floor);
+}
+
+/// Fill a level without rooms from a sample layout by wave function collapse. Without any
+/// samples, or if the generator fails, a level of rooms is made instead.
+fn make_wave_function_collapse(
+    world: &mut World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+) {
+    let samples = samples();
+    if samples.is_empty() {
+        return make_rooms(world, objects, rng, level);
+    }
+    let sample = &samples[rng.gen_range(0, samples.len())];
+
+    let layout = (0..WFC_ATTEMPTS)
+        .filter_map(|_| sample.generate(rng, world.width, world.height))
+        .next();
+    let layout = match layout {
+        Some(layout) => layout,
+        None => return make_rooms(world, objects, rng, level),
+    };
+    let mut floor = vec![];
+    for y in 0..world.height {
+        for x in 0..world.width {
+            let terrain = layout[world.index(x, y)];
+            *world.get_mut(x, y) = Tile::new(terrain);
+            if terrain == Terrain::Floor {
+                floor.push((x, y));
+            }
+        }
+    }
+    if floor.is_empty() {
+        return make_rooms(world, objects, rng, level);
+    }
+    // start on a random floor tile
+    let start = rng.gen_range(0, floor.len());
+    floor.swap(0, start);
+
+    populate_open_level(world, objects, rng, level, &floor);
+}
+
+/// Place the player on the first of the given floor tiles, the stairs on the one furthest away
+/// from it and monsters and traps on random ones.
+fn populate_open_level(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    floor: &[(i32, i32)],
+) {
+    let (start_x, start_y) = floor[0];
+    objects[PLAYER].set_pos(start_x, start_y);
+
+    // populate the level as if it consisted of rooms of average size
+    let avg_room_area = ((ROOM_MIN_SIZE + ROOM_MAX_SIZE) / 2).pow(2) as usize;
+    let random_floor = |rng: &mut StdRng| floor[rng.gen_range(0, floor.len())];
+    for _ in 0..cmp::max(floor.len() / avg_room_area, 1) {
+        place_monsters(world, objects, rng, level, 0, random_floor);
+        place_traps(world, objects, rng, level, random_floor);
+    }
+
+    // put the stairs on the carved tile that is furthest away from the start
+    let (stairs_x, stairs_y) = *floor
+        .iter()
+        .max_by_key(|&&(x, y)| (x - start_x).pow(2) + (y - start_y).pow(2))
+        .unwrap();
+    place_stairs(objects, stairs_x, stairs_y);
+}
+
+/// Make sure that every walkable tile, in particular the one with the stairs, can be reached
+/// from the player's starting position. Unreachable areas are connected to the nearest
+/// reachable tile with a tunnel.
+fn ensure_connectivity(world: &mut World, objects: &[Object]) {
+    // neither the player nor the stairs may be stuck inside a wall
+    for object in objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, object)| id == PLAYER || object.name == "stairs")
+        .map(|(_, object)| object)
+    {
+        *world.get_mut(object.x, object.y) = Tile::empty();
+    }
+
+    // deep water can't be crossed on foot, so areas beyond it have to be connected, too
+    let walkable = |tile: &Tile| tile.is_walkable() && tile.terrain != Terrain::DeepWater;
+    let (start_x, start_y) = objects[PLAYER].pos();
+    loop {
+        let reachable = flood_fill(world, start_x, start_y, walkable);
+
+        // find the first walkable tile that cannot be reached yet
+        let unreachable = (0..WORLD_WIDTH)
+            .flat_map(|x| (0..WORLD_HEIGHT).map(move |y| (x, y)))
+            .find(|&(x, y)| walkable(world.get(x, y)) && !reachable[world.index(x, y)]);
+
+        match unreachable {
+            Some((x, y)) => {
+                // connect it to the closest reachable tile
+                let (target_x, target_y) = (0..WORLD_WIDTH)
+                    .flat_map(|x| (0..WORLD_HEIGHT).map(move |y| (x, y)))
+                    .filter(|&(x, y)| reachable[world.index(x, y)])
+                    .min_by_key(|&(tx, ty)| (tx - x).abs() + (ty - y).abs())
+                    .unwrap();
+                create_h_tunnel(world, x, target_x, y);
+                create_v_tunnel(world, y, target_y, target_x);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Place one key for every locked door of the level. All keys lie in the area that the player
+/// can reach without passing any locked door, so every door can eventually be unlocked.
+fn place_keys(world: &World, objects: &mut Vec<Object>, rng: &mut StdRng) {
+    let num_locked = world
+        .tiles
+        .iter()
+        .filter(|tile| tile.door == Some(Door::Locked))
+        .count();
+    if num_locked == 0 {
+        return;
+    }
+
+    let (start_x, start_y) = objects[PLAYER].pos();
+    let reachable = flood_fill(world, start_x, start_y, |tile| {
+        tile.is_walkable() && tile.door != Some(Door::Locked)
+    });
+    let spots: Vec<(i32, i32)> = (0..world.width)
+        .flat_map(|x| (0..world.height).map(move |y| (x, y)))
+        .filter(|&(x, y)| reachable[world.index(x, y)] && world.get(x, y).terrain == Terrain::Floor)
+        .collect();
+    if spots.is_empty() {
+        return;
+    }
+
+    for _ in 0..num_locked {
+        let (x, y) = spots[rng.gen_range(0, spots.len())];
+        if let Some(key) = make_item("key", x, y) {
+            objects.push(key);
+        }
+    }
+}
+
+/// Return which tiles can be reached from the given position, only crossing tiles for which
+/// `passable` returns true. The result is indexed in the same way as the world's tiles.
+fn flood_fill<F>(world: &World, x: i32, y: i32, passable: F) -> Vec<bool>
+where
+    F: Fn(&Tile) -> bool,
+{
+    let mut reachable = vec![false; world.tiles.len()];
+    let mut stack = vec![(x, y)];
+    while let Some((x, y)) = stack.pop() {
+        if !world.in_bounds(x, y) || reachable[world.index(x, y)] || !passable(world.get(x, y)) {
+            continue;
+        }
+        reachable[world.index(x, y)] = true;
+        stack.extend_from_slice(&[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+    }
+    reachable
+}
+
+/// Scatter the decorations of the world's theme across its free floor. Decorations that block
+/// movement are only put where all surrounding tiles are free, so they never cut off a path.
+fn place_decorations(world: &mut World, objects: &mut Vec<Object>, rng: &mut StdRng) {
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let decoration_chances = world.theme.decoration_chances();
+    let decoration_dist = WeightedIndex::new(decoration_chances.iter().map(|item| item.1)).unwrap();
+
+    for y in 1..world.height - 1 {
+        for x in 1..world.width - 1 {
+            let open = |x: i32, y: i32| {
+                let tile = world.get(x, y);
+                tile.terrain == Terrain::Floor
+                    && !tile.blocked
+                    && tile.door.is_none()
+                    && !objects
+                        .iter()
+                        .any(|object| object.pos() == (x, y) && object.fighter.is_none())
+            };
+            if !open(x, y) || rng.gen::<f32>() >= DECORATION_CHANCE {
+                continue;
+            }
+            let decoration = decoration_chances[decoration_dist.sample(rng)].0;
+            let surrounded_by_floor =
+                (x - 1..=x + 1).all(|nx| (y - 1..=y + 1).all(|ny| open(nx, ny)));
+            let occupied = objects.iter().any(|object| object.pos() == (x, y));
+            match decoration {
+                Decoration::Pillar if surrounded_by_floor && !occupied => {
+                    *world.get_mut(x, y) = Tile::new(Terrain::Pillar);
+                }
+                Decoration::Brazier if surrounded_by_floor && !occupied => {
+                    objects.extend(make_decoration(decoration, x, y));
+                }
+                Decoration::Rubble => {
+                    world.get_mut(x, y).move_cost = RUBBLE_MOVE_COST;
+                    objects.extend(make_decoration(decoration, x, y));
+                }
+                Decoration::Bones => {
+                    objects.extend(make_decoration(decoration, x, y));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Create a decoration object. Pillars are terrain instead, so there is no object for them.
+fn make_decoration(decoration: Decoration, x: i32, y: i32) -> Option<Object> {
+    let mut object = match decoration {
+        Decoration::Rubble => Object::new(x, y, "rubble", false, ',', colors::GREY),
+        Decoration::Bones => Object::new(x, y, "bones", false, '%', colors::LIGHTEST_GREY),
+        Decoration::Brazier => {
+            let mut brazier = Object::new(x, y, "brazier", true, '&', colors::ORANGE);
+            brazier.light_radius = BRAZIER_LIGHT_RADIUS;
+            brazier
+        }
+        Decoration::Pillar => return None,
+    };
+    object.always_visible = true;
+    Some(object)
+}
+
+/// Place the entrances to all side branches that start on the given level on free floor.
+fn place_branch_entrances(world: &World, objects: &mut Vec<Object>, rng: &mut StdRng, id: LevelId) {
+    for branch in BRANCHES.iter().filter(|branch| branch.entry == Some(id)) {
+        let free: Vec<(i32, i32)> = (1..world.width - 1)
+            .flat_map(|x| (1..world.height - 1).map(move |y| (x, y)))
+            .filter(|&(x, y)| {
+                let tile = world.get(x, y);
+                tile.terrain == Terrain::Floor
+                    && !tile.blocked
+                    && !objects.iter().any(|object| object.pos() == (x, y))
+            })
+            .collect();
+        // without a free tile, the branch cannot be entered from this level
+        if free.is_empty() {
+            continue;
+        }
+        let (x, y) = free[rng.gen_range(0, free.len())];
+        let mut entrance = Object::new(
+            x,
+            y,
+            &branch.entrance_name(),
+            false,
+            '<',
+            colors::LIGHT_BLUE,
+        );
+        entrance.always_visible = true;
+        objects.push(entrance);
+    }
+}
+
+fn place_stairs(objects: &mut Vec<Object>, x: i32, y: i32) {
+    let mut stairs = Object::new(x, y, "stairs", false, '<', colors::WHITE);
+    stairs.always_visible = true;
+    objects.push(stairs);
+}
+
+fn place_up_stairs(objects: &mut Vec<Object>, x: i32, y: i32) {
+    let mut stairs = Object::new(x, y, "up stairs", false, '>', colors::WHITE);
+    stairs.always_visible = true;
+    objects.push(stairs);
+}
+
+// data structures for room generation
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x1: i32,
+    y1: i32,
+    x2: i32,
+    y2: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Rect {
+            x1: x,
+            y1: y,
+            x2: x + w,
+            y2: y + h,
+        }
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        let center_x = (self.x1 + self.x2) / 2;
+        let center_y = (self.y1 + self.y2) / 2;
+        (center_x, center_y)
+    }
+
+    /// Return true if this rect intersects with another one.
+    pub fn intersects_with(&self, other: &Rect) -> bool {
+        (self.x1 <= other.x2)
+            && (self.x2 >= other.x1)
+            && (self.y1 <= other.y2)
+            && (self.y2 >= other.y1)
+    }
+}
+
+/// The outline of a room's interior within its bounding rect.
+#[derive(Clone, Copy, Debug)]
+enum RoomShape {
+    Rectangle,
+    Circle,
+    Cross,
+    /// A rectangle that lacks the quarter in the direction (dx, dy) of its center.
+    LShape(i32, i32),
+}
+
+impl RoomShape {
+    fn random(rng: &mut StdRng) -> Self {
+        match rng.gen_range(0, 10) {
+            0..=4 => RoomShape::Rectangle,
+            5 | 6 => RoomShape::Circle,
+            7 | 8 => RoomShape::Cross,
+            _ => {
+                let dx = if rng.gen() { 1 } else { -1 };
+                let dy = if rng.gen() { 1 } else { -1 };
+                RoomShape::LShape(dx, dy)
+            }
+        }
+    }
+}
+
+/// A room occupies its bounding rect, which is used to keep rooms from overlapping, but only
+/// the part of the rect's interior that lies within its shape is carved out.
+#[derive(Clone, Copy, Debug)]
+struct Room {
+    rect: Rect,
+    shape: RoomShape,
+    // treasure vaults are filled with better loot and stronger monsters
+    treasure: bool,
+}
+
+impl Room {
+    pub fn new(rect: Rect, shape: RoomShape) -> Self {
+        Room {
+            rect,
+            shape,
+            treasure: false,
+        }
+    }
+
+    /// Return the center of the room, which is part of the interior for every shape.
+    pub fn center(&self) -> (i32, i32) {
+        self.rect.center()
+    }
+
+    /// Return true if this room's bounds intersect with those of another one.
+    pub fn intersects_with(&self, other: &Room) -> bool {
+        self.rect.intersects_with(&other.rect)
+    }
+
+    /// Return true if the given position lies within the room's interior.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let rect = self.rect;
+        if x <= rect.x1 || x >= rect.x2 || y <= rect.y1 || y >= rect.y2 {
+            return false;
+        }
+        let (center_x, center_y) = rect.center();
+        let (dx, dy) = (x - center_x, y - center_y);
+        match self.shape {
+            RoomShape::Rectangle => true,
+            RoomShape::Circle => {
+                let radius = cmp::min(rect.x2 - rect.x1, rect.y2 - rect.y1) / 2 - 1;
+                dx * dx + dy * dy <= radius * radius + radius
+            }
+            RoomShape::Cross => {
+                dx.abs() <= (rect.x2 - rect.x1) / 4 || dy.abs() <= (rect.y2 - rect.y1) / 4
+            }
+            RoomShape::LShape(corner_x, corner_y) => dx * corner_x <= 0 || dy * corner_y <= 0,
+        }
+    }
+}
+
+fn create_room(world: &mut World, room: Room) {
+    let rect = room.rect;
+    for x in (rect.x1 + 1)..rect.x2 {
+        for y in (rect.y1 + 1)..rect.y2 {
+            if room.contains(x, y) {
+                *world.get_mut(x, y) = Tile::empty();
+            }
+        }
+    }
+}
+
+/// Connect both positions with a horizontal and a vertical tunnel - in random order.
+fn dig_tunnel(world: &mut World, rng: &mut StdRng, from: (i32, i32), to: (i32, i32)) {
+    let ((x1, y1), (x2, y2)) = (from, to);
+    if rng.gen() {
+        // move horizontally, then vertically
+        create_h_tunnel(world, x1, x2, y1);
+        create_v_tunnel(world, y1, y2, x2);
+    } else {
+        // move vertically, then horizontally
+        create_v_tunnel(world, y1, y2, x1);
+        create_h_tunnel(world, x1, x2, y2);
+    }
+}
+
+fn create_h_tunnel(world: &mut World, x1: i32, x2: i32, y: i32) {
+    for x in cmp::min(x1, x2)..=cmp::max(x1, x2) {
+        *world.get_mut(x, y) = Tile::empty();
+    }
+}
+
+fn create_v_tunnel(world: &mut World, y1: i32, y2: i32, x: i32) {
+    for y in cmp::min(y1, y2)..=cmp::max(y1, y2) {
+        *world.get_mut(x, y) = Tile::empty();
+    }
+}
+
+fn place_objects(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    room: Room,
+    level: u32,
+) {
+    // choose random spots inside the room
+    let random_spot = |rng: &mut StdRng| loop {
+        let x = rng.gen_range(room.rect.x1 + 1, room.rect.x2);
+        let y = rng.gen_range(room.rect.y1 + 1, room.rect.y2);
+        if room.contains(x, y) {
+            return (x, y);
+        }
+    };
+    if room.treasure {
+        // treasure always lies in a chest and is guarded by monsters from deeper down
+        place_chest(world, objects, rng, level, true, random_spot);
+        let guardian_level = level + TREASURE_GUARDIAN_LEVELS;
+        place_monsters(world, objects, rng, guardian_level, 1, random_spot);
+        place_traps(world, objects, rng, level, random_spot);
+        return;
+    }
+    place_monsters(world, objects, rng, level, 0, random_spot);
+    place_traps(world, objects, rng, level, random_spot);
+    if rng.gen::<f32>() < NEST_CHANCE {
+        place_nest(world, objects, rng, level, random_spot);
+    }
+    if rng.gen::<f32>() < MIMIC_CHANCE {
+        place_mimic(world, objects, rng, random_spot);
+    }
+    if rng.gen::<f32>() < CHEST_CHANCE {
+        place_chest(world, objects, rng, level, false, random_spot);
+    }
+    if rng.gen::<f32>() < FOOD_CHANCE {
+        let (x, y) = random_spot(rng);
+        if !is_blocked(world, objects, x, y) {
+            objects.extend(make_item("food ration", x, y));
+        }
+    }
+    if rng.gen::<f32>() < GOLD_CHANCE {
+        let (x, y) = random_spot(rng);
+        if !is_blocked(world, objects, x, y) {
+            if let Some(mut gold) = make_item("gold", x, y) {
+                gold.count = rng.gen_range(1, 11) + GOLD_PER_LEVEL * level as i32;
+                objects.push(gold);
+            }
+        }
+    }
+    if rng.gen::<f32>() < HERB_CHANCE {
+        let (x, y) = random_spot(rng);
+        if !is_blocked(world, objects, x, y) {
+            objects.extend(make_item("medicinal herb", x, y));
+        }
+    }
+    if rng.gen::<f32>() < WORKBENCH_CHANCE {
+        let (x, y) = random_spot(rng);
+        if !is_blocked(world, objects, x, y) {
+            let mut workbench = Object::new(x, y, "workbench", true, '&', colors::LIGHT_SEPIA);
+            workbench.always_visible = true;
+            objects.push(workbench);
+        }
+    }
+}
+
+/// Place a chest filled with a few random items at a position yielded by `random_spot`. Chests
+/// in treasure vaults hold more and better items. Better armor is found further down.
+fn place_chest<F>(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    treasure: bool,
+    mut random_spot: F,
+) where
+    F: FnMut(&mut StdRng) -> (i32, i32),
+{
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let table = if treasure {
+        Table::Treasure
+    } else {
+        Table::Items
+    };
+    let item_chances = chances(world.theme, table, level);
+    let (min_items, max_items) = if treasure {
+        (2, CHEST_MAX_ITEMS + 2)
+    } else {
+        (1, CHEST_MAX_ITEMS)
+    };
+    let item_dist = match WeightedIndex::new(item_chances.iter().map(|item| item.1)) {
+        Ok(item_dist) => item_dist,
+        Err(_) => return,
+    };
+
+    let (x, y) = random_spot(rng);
+    if is_blocked(world, objects, x, y) || objects.iter().any(|object| object.pos() == (x, y)) {
+        return;
+    }
+    let mut items: Vec<Object> = (0..rng.gen_range(min_items, max_items + 1))
+        .filter_map(|_| make_item(&item_chances[item_dist.sample(rng)].0, x, y))
+        .collect();
+    // treasure is as good as the loot found further down
+    let loot_level = if treasure {
+        level + TREASURE_GUARDIAN_LEVELS
+    } else {
+        level
+    };
+    for item in items.iter_mut() {
+        add_affixes(item, rng, loot_level);
+        if !treasure && rng.gen::<f32>() < CURSE_CHANCE {
+            curse(item);
+        }
+    }
+    if treasure && level >= ARTIFACT_LEVEL && rng.gen::<f32>() < ARTIFACT_CHANCE {
+        items.push(random_artifact(rng, x, y));
+    }
+    objects.push(make_chest(x, y, items));
+}
+
+/// Create a closed chest that holds the given items.
+pub fn make_chest(x: i32, y: i32, items: Vec<Object>) -> Object {
+    let mut chest = Object::new(x, y, "chest", true, CHEST_CHAR, CHEST_COLOR);
+    chest.container = Some(Container { items, open: false });
+    chest.always_visible = true;
+    chest
+}
+
+/// Hide a random number of traps, appropriate for the given level, at the positions yielded by
+/// `random_spot`.
+fn place_traps<F>(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    mut random_spot: F,
+) where
+    F: FnMut(&mut StdRng) -> (i32, i32),
+{
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let max_traps = from_dungeon_level(
+        &[
+            Transition { level: 2, value: 1 },
+            Transition { level: 5, value: 2 },
+        ],
+        level,
+    );
+
+    // trap random table
+    let trap_chances = world.theme.trap_chances();
+    let trap_dist = WeightedIndex::new(trap_chances.iter().map(|item| item.1)).unwrap();
+
+    // choose random number of traps
+    let num_traps = rng.gen_range(0, max_traps + 1);
+    for _ in 0..num_traps {
+        let (x, y) = random_spot(rng);
+        let occupied = objects.iter().any(|object| object.pos() == (x, y));
+        if !world.get(x, y).blocked && !occupied {
+            let kind = trap_chances[trap_dist.sample(rng)].0;
+            objects.push(make_trap(kind, x, y));
+        }
+    }
+}
+
+/// Create a hidden trap of the given kind at the given position.
+pub fn make_trap(kind: TrapKind, x: i32, y: i32) -> Object {
+    let name = match kind {
+        TrapKind::SpikePit => "spike pit",
+        TrapKind::Dart => "dart trap",
+        TrapKind::Alarm => "alarm trap",
+        TrapKind::Gas => "gas trap",
+        TrapKind::Polymorph => "polymorph trap",
+    };
+    let mut trap = Object::new(x, y, name, false, '^', colors::LIGHT_RED);
+    trap.trap = Some(Trap { kind, hidden: true });
+    trap
+}
+
+/// Spawn a random number of monsters, appropriate for the given level, at the positions
+/// yielded by `random_spot`.
+fn place_monsters<F>(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    min_monsters: u32,
+    mut random_spot: F,
+) where
+    F: FnMut(&mut StdRng) -> (i32, i32),
+{
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let max_monsters = max_monsters(world.theme, level);
+
+    // monster random table
+    let monster_chances = world.theme.monster_chances(level);
+    let monster_dist = match WeightedIndex::new(monster_chances.iter().map(|item| item.1)) {
+        Ok(monster_dist) => monster_dist,
+        Err(_) => return,
+    };
+
+    // choose random number of monsters
+    let num_monsters = rng.gen_range(min_monsters, cmp::max(min_monsters, max_monsters) + 1);
+    for _ in 0..num_monsters {
+        // choose random spot for this monster
+        let (x, y) = random_spot(rng);
+
+        if !is_blocked(world, objects, x, y) {
+            let name = &monster_chances[monster_dist.sample(rng)].0;
+            let asleep = rng.gen::<f32>() < SLEEP_CHANCE;
+            // the spawn tables run out at some depth, below which mutants take over
+            let monster = if level >= MUTANT_LEVEL && rng.gen::<f32>() < MUTANT_CHANCE {
+                Some(make_mutant(rng, level, x, y))
+            } else {
+                spawn_monster(name, x, y)
+            };
+            if let Some(mut monster) = monster {
+                if let Some(Ai::Pack { .. }) = monster.ai {
+                    place_pack(world, objects, rng, name, x, y, asleep);
+                } else {
+                    if rng.gen::<f32>() < CHAMPION_CHANCE {
+                        make_champion(&mut monster, rng);
+                    }
+                    if asleep {
+                        put_to_sleep(&mut monster);
+                    }
+                    objects.push(monster);
+                }
+            }
+        }
+    }
+}
+
+/// Place a nest of one of the level's monsters at a position yielded by `random_spot`.
+fn place_nest<F>(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    level: u32,
+    mut random_spot: F,
+) where
+    F: FnMut(&mut StdRng) -> (i32, i32),
+{
+    use rand::distributions::{Distribution, WeightedIndex};
+
+    let monster_chances = world.theme.monster_chances(level);
+    let monster_dist = match WeightedIndex::new(monster_chances.iter().map(|item| item.1)) {
+        Ok(monster_dist) => monster_dist,
+        Err(_) => return,
+    };
+    let (x, y) = random_spot(rng);
+    if !is_blocked(world, objects, x, y) {
+        let entry = &monster_chances[monster_dist.sample(rng)].0;
+        if let Some(nest) = make_nest(entry, x, y) {
+            objects.push(nest);
+        }
+    }
+}
+
+/// Place a mimic that poses as a chest or a healing potion at a position yielded by
+/// `random_spot`.
+fn place_mimic<F>(world: &World, objects: &mut Vec<Object>, rng: &mut StdRng, mut random_spot: F)
+where
+    F: FnMut(&mut StdRng) -> (i32, i32),
+{
+    let (x, y) = random_spot(rng);
+    if is_blocked(world, objects, x, y) || objects.iter().any(|object| object.pos() == (x, y)) {
+        return;
+    }
+    if let Some(mut mimic) = make_monster("mimic", x, y) {
+        if rng.gen() {
+            disguise_as(&mut mimic, "chest", CHEST_CHAR, CHEST_COLOR);
+        } else {
+            disguise_as(&mut mimic, "healing potion", '!', colors::VIOLET);
+        }
+        objects.push(mimic);
+    }
+}
+
+/// Create a nest that spawns monsters of the given spawn table entry.
+fn make_nest(entry: &str, x: i32, y: i32) -> Option<Object> {
+    let monster = spawn_monster(entry, x, y)?;
+    let name = format!("{} nest", monster.name);
+    let mut nest = Object::new(x, y, &name, true, '&', colors::DARK_ORANGE);
+    nest.fighter = Some(Fighter {
+        base_max_hp: 20,
+        hp: 20,
+        base_defense: 0,
+        base_power: 0,
+        damage: Dice::new(0, 0, 0),
+        on_death: DeathCallback::Nest,
+        xp: 50,
+    });
+    nest.spawner = Some(Spawner::new(entry, NEST_INTERVAL));
+    nest.faction = monster.faction;
+    nest.alive = true;
+    Some(nest)
+}
+
+/// Place a pack of monsters of the given kind around the given position. The index of the first
+/// member in the objects serves as the id of the pack.
+fn place_pack(
+    world: &World,
+    objects: &mut Vec<Object>,
+    rng: &mut StdRng,
+    name: &str,
+    x: i32,
+    y: i32,
+    asleep: bool,
+) {
+    let pack = objects.len();
+    let size = rng.gen_range(PACK_MIN_SIZE, PACK_MAX_SIZE + 1);
+    let spots: Vec<(i32, i32)> = (y - 1..=y + 1)
+        .flat_map(|y| (x - 1..=x + 1).map(move |x| (x, y)))
+        .collect();
+    for &(x, y) in &spots {
+        if objects.len() - pack >= size {
+            break;
+        }
+        if !world.in_bounds(x, y)
+            || is_blocked(world, objects, x, y)
+            || world.get(x, y).is_hazardous()
+        {
+            continue;
+        }
+        if let Some(mut monster) = make_monster(name, x, y) {
+            monster.ai = Some(Ai::Pack { pack });
+            if asleep {
+                put_to_sleep(&mut monster);
+            }
+            objects.push(monster);
+        }
+    }
+}
+
+/// Let the monster sleep until it is woken up, after which it follows its current AI again.
+fn put_to_sleep(monster: &mut Object) {
+    monster.ai = monster.ai.take().map(|ai| Ai::Asleep {
+        previous_ai: Box::new(ai),
+    });
+}
+
+/// Create a monster from an entry of a spawn table. Entries of the form `monster/behavior`
+/// replace the monster's AI with the behavior of that name.
+pub fn spawn_monster(entry: &str, x: i32, y: i32) -> Option<Object> {
+    let mut parts = entry.splitn(2, '/');
+    let mut monster = make_monster(parts.next()?, x, y)?;
+    if let Some(name) = parts.next() {
+        if let Some(behavior) = behavior(name) {
+            monster.ai = Some(Ai::Scripted { behavior, state: 0 });
+        }
+    }
+    Some(monster)
+}
+
+/// Create a monster of the given kind at the given position.
+/// Returns `None` if there is no monster of that name.
+pub fn make_monster(name: &str, x: i32, y: i32) -> Option<Object> {
+    let mut monster = match name {
+        "virus" => {
+            let mut virus = Object::new(x, y, "virus", true, 'v', colors::DESATURATED_GREEN);
+            virus.fighter = Some(Fighter {
+                base_max_hp: 10,
+                hp: 10,
+                base_defense: 0,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 35,
+            });
+            virus.ai = Some(Ai::Basic);
+            virus.faction = Faction::Viruses;
+            virus
+        }
+        "bacteria" => {
+            let mut bacteria = Object::new(x, y, "bacteria", true, 'b', colors::DARKER_GREEN);
+            bacteria.fighter = Some(Fighter {
+                base_max_hp: 16,
+                hp: 16,
+                base_defense: 1,
+                base_power: 0,
+                damage: Dice::new(2, 3, 0),
+                on_death: DeathCallback::Monster,
+                xp: 100,
+            });
+            bacteria.ai = Some(Ai::Basic);
+            bacteria.faction = Faction::Bacteria;
+            bacteria.inventory = Some(vec![]);
+            bacteria.tracks_scent = true;
+            bacteria
+        }
+        "paramecium" => {
+            let mut paramecium = Object::new(x, y, "paramecium", true, 'p', colors::LIGHT_SKY);
+            paramecium.fighter = Some(Fighter {
+                base_max_hp: 8,
+                hp: 8,
+                base_defense: 0,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 30,
+            });
+            paramecium.ai = Some(Ai::Basic);
+            paramecium.faction = Faction::Protists;
+            paramecium.move_mode = MoveMode::Swim;
+            paramecium.tracks_scent = true;
+            paramecium
+        }
+        "spitter" => {
+            let mut spitter = Object::new(x, y, "spitter", true, 'S', colors::LIME);
+            spitter.fighter = Some(Fighter {
+                base_max_hp: 8,
+                hp: 8,
+                base_defense: 0,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 60,
+            });
+            spitter.ai = Some(Ai::Ranged {
+                range: SPITTER_RANGE,
+            });
+            spitter.faction = Faction::Fungi;
+            spitter
+        }
+        "phage" => {
+            let mut phage = Object::new(x, y, "phage", true, 'x', colors::LIGHTER_GREEN);
+            phage.fighter = Some(Fighter {
+                base_max_hp: 6,
+                hp: 6,
+                base_defense: 0,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 20,
+            });
+            phage.ai = Some(Ai::Pack { pack: 0 });
+            phage.faction = Faction::Viruses;
+            phage
+        }
+        "slime mold" => {
+            let mut slime_mold = Object::new(x, y, "slime mold", true, 'm', colors::LIGHT_PURPLE);
+            slime_mold.fighter = Some(Fighter {
+                base_max_hp: 12,
+                hp: 12,
+                base_defense: 0,
+                base_power: 0,
+                damage: Dice::new(1, 3, 0),
+                on_death: DeathCallback::Monster,
+                xp: 90,
+            });
+            slime_mold.ai = Some(Ai::Caster);
+            slime_mold.caster = Some(Caster::new(
+                vec![Spell::Bolt, Spell::Heal, Spell::Haste, Spell::Repel],
+                SLIME_MOLD_MANA,
+            ));
+            slime_mold.faction = Faction::Protists;
+            slime_mold
+        }
+        "stalker" => {
+            let mut stalker = Object::new(x, y, "stalker", true, 's', colors::LIGHT_GREY);
+            stalker.fighter = Some(Fighter {
+                base_max_hp: 14,
+                hp: 14,
+                base_defense: 1,
+                base_power: 0,
+                damage: Dice::new(2, 4, 0),
+                on_death: DeathCallback::Monster,
+                xp: 120,
+            });
+            stalker.ai = Some(Ai::Basic);
+            stalker.faction = Faction::Prions;
+            stalker.invisible = true;
+            stalker
+        }
+        "ghost" => {
+            let mut ghost = Object::new(x, y, "ghost", true, 'G', colors::LIGHTEST_GREY);
+            ghost.fighter = Some(Fighter {
+                base_max_hp: 12,
+                hp: 12,
+                base_defense: 2,
+                base_power: 0,
+                damage: Dice::new(2, 3, 0),
+                on_death: DeathCallback::Monster,
+                xp: 100,
+            });
+            ghost.ai = Some(Ai::Basic);
+            ghost.faction = Faction::Prions;
+            ghost.move_mode = MoveMode::Phase;
+            ghost.affixes = vec![Affix::Draining];
+            ghost
+        }
+        "mimic" => {
+            let mut mimic = Object::new(x, y, "mimic", true, 'M', colors::DARK_CRIMSON);
+            mimic.fighter = Some(Fighter {
+                base_max_hp: 18,
+                hp: 18,
+                base_defense: 1,
+                base_power: 0,
+                damage: Dice::new(2, 4, 0),
+                on_death: DeathCallback::Monster,
+                xp: 110,
+            });
+            mimic.ai = Some(Ai::Basic);
+            mimic.faction = Faction::Protists;
+            mimic
+        }
+        "lymphocyte" => {
+            let mut lymphocyte = Object::new(x, y, "lymphocyte", true, 'l', colors::LIGHT_AZURE);
+            lymphocyte.fighter = Some(Fighter {
+                base_max_hp: 20,
+                hp: 20,
+                base_defense: 1,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 0,
+            });
+            lymphocyte.ai = Some(Ai::Companion);
+            lymphocyte.faction = Faction::Allies;
+            lymphocyte
+        }
+        "fungus" => {
+            let mut fungus = Object::new(x, y, "fungus", true, 'f', colors::DARK_AMBER);
+            fungus.fighter = Some(Fighter {
+                base_max_hp: 24,
+                hp: 24,
+                base_defense: 2,
+                base_power: 0,
+                damage: Dice::new(1, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 80,
+            });
+            fungus.ai = Some(Ai::Basic);
+            fungus.faction = Faction::Fungi;
+            fungus
+        }
+        "drake" | "frost drake" => {
+            let (breath, color) = if name == "drake" {
+                (Spell::FireBreath, colors::FLAME)
+            } else {
+                (Spell::FrostBreath, colors::LIGHT_CYAN)
+            };
+            let mut drake = Object::new(x, y, name, true, 'D', color);
+            drake.fighter = Some(Fighter {
+                base_max_hp: 30,
+                hp: 30,
+                base_defense: 3,
+                base_power: 0,
+                damage: Dice::new(2, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 200,
+            });
+            drake.ai = Some(Ai::Caster);
+            drake.caster = Some(Caster::new(vec![breath], DRAKE_MANA));
+            drake.faction = Faction::Prions;
+            drake
+        }
+        "prion" => {
+            let mut prion = Object::new(x, y, "prion", true, 'P', colors::LIGHT_VIOLET);
+            prion.fighter = Some(Fighter {
+                base_max_hp: 20,
+                hp: 20,
+                base_defense: 2,
+                base_power: 0,
+                damage: Dice::new(2, 5, 0),
+                on_death: DeathCallback::Monster,
+                xp: 150,
+            });
+            prion.ai = Some(Ai::Basic);
+            prion.faction = Faction::Prions;
+            prion.inventory = Some(vec![]);
+            prion
+        }
+        _ => return None,
+    };
+
+    monster.drops = Some(name.to_string());
+    monster.alive = true;
+    Some(monster)
+}
+
+/// Create an item of the given kind at the given position, as defined in the item file.
+/// Returns `None` if there is no item of that name.
+pub fn make_item(name: &str, x: i32, y: i32) -> Option<Object> {
+    create_item(name, x, y)
+}
+
+/// Return the cheapest path from `from` to `to` for a creature that moves in the given way,
+/// taking into account how long it takes to enter each tile. Closed doors are opened on the
+/// way and hazards are avoided. Other creatures are walked around if the detour is short,
+/// otherwise the path leads through them in the hope that they move out of the way. The path
+/// does not contain the starting position and is empty if there is no path.
+pub fn find_path(
+    world: &World,
+    objects: &[Object],
+    from: (i32, i32),
+    to: (i32, i32),
+    move_mode: MoveMode,
+) -> Vec<(i32, i32)> {
+    path_between(world, objects, from, to, move_mode, false)
+}
+
+/// Return the cheapest path like `find_path`, but only across tiles the player has explored.
+pub fn find_known_path(
+    world: &World,
+    objects: &[Object],
+    from: (i32, i32),
+    to: (i32, i32),
+    move_mode: MoveMode,
+) -> Vec<(i32, i32)> {
+    path_between(world, objects, from, to, move_mode, true)
+}
+
+fn path_between(
+    world: &World,
+    objects: &[Object],
+    from: (i32, i32),
+    to: (i32, i32),
+    move_mode: MoveMode,
+    explored_only: bool,
+) -> Vec<(i32, i32)> {
+    let cost = |_from: (i32, i32), (x, y): (i32, i32)| {
+        let tile = world.get(x, y);
+        if explored_only && !tile.explored {
+            return 0.0;
+        }
+        let cost = if tile.door == Some(Door::Closed) {
+            // opening the door takes an extra turn
+            tile.move_cost + 1
+        } else if tile.blocks(move_mode) || tile.is_hazardous() {
+            return 0.0;
+        } else {
+            tile.move_cost
+        };
+        let blocker = objects
+            .iter()
+            .find(|object| object.blocks && object.pos() == (x, y) && (x, y) != to);
+        match blocker {
+            None => cost as f32,
+            Some(blocker) if blocker.ai.is_some() => (cost + CROWD_MOVE_COST) as f32,
+            Some(_) => 0.0,
+        }
+    };
+    astar(
+        world.width,
+        world.height,
+        from,
+        to,
+        DIAGONAL_MOVE_COST,
+        cost,
+    )
+}
+
+/// Draw the world and all objects in it as plain text, one line per row of tiles. Everything is
+/// shown, including unexplored tiles, secret doors (`s`) and hidden traps.
+pub fn ascii_map(world: &World, objects: &[Object]) -> String {
+    let mut rows: Vec<Vec<char>> = (0..world.height)
+        .map(|y| {
+            (0..world.width)
+                .map(|x| {
+                    let tile = world.get(x, y);
+                    match tile.door {
+                        Some(Door::Open) => '\'',
+                        Some(Door::Closed) | Some(Door::Locked) => '+',
+                        Some(Door::Secret) => 's',
+                        None => match tile.terrain {
+                            Terrain::Floor => '.',
+                            Terrain::Wall => '#',
+                            Terrain::DeepWater => '~',
+                            Terrain::ShallowWater => ',',
+                            Terrain::Lava => '=',
+                            Terrain::Chasm => ':',
+                            Terrain::Pillar => 'O',
+                        },
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    // blocking objects are drawn on top of everything else
+    let mut sorted: Vec<&Object> = objects.iter().collect();
+    sorted.sort_by_key(|object| object.blocks);
+    for object in sorted {
+        if world.in_bounds(object.x, object.y) {
+            rows[object.y as usize][object.x as usize] = object.chr;
+        }
+    }
+
+    rows.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+pub fn is_blocked(world: &World, objects: &[Object], x: i32, y: i32) -> bool {
+    is_blocked_for(world, objects, x, y, MoveMode::Walk)
+}
+
+/// Return true if a creature that moves in the given way cannot enter the given position.
+pub fn is_blocked_for(
+    world: &World,
+    objects: &[Object],
+    x: i32,
+    y: i32,
+    move_mode: MoveMode,
+) -> bool {
+    // first test the world tile
+    if world.get(x, y).blocks(move_mode) {
+        return true;
+    }
+    // now check for any blocking objects
+    objects
+        .iter()
+        .any(|object| object.blocks && object.pos() == (x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_are_stored_row_by_row() {
+        let mut world = World::new(4, 3, Theme::Sewers, Tile::wall());
+        *world.get_mut(3, 1) = Tile::empty();
+        assert_eq!(world.index(3, 1), 7);
+        assert_eq!(world.get(3, 1).terrain, Terrain::Floor);
+        assert_eq!(world.get(1, 2).terrain, Terrain::Wall);
+        let floors = world
+            .tiles
+            .iter()
+            .filter(|tile| tile.terrain == Terrain::Floor);
+        assert_eq!(floors.count(), 1);
+    }
+
+    #[test]
+    fn bounds_are_checked() {
+        let world = World::new(4, 3, Theme::Sewers, Tile::wall());
+        assert!(world.in_bounds(0, 0));
+        assert!(world.in_bounds(3, 2));
+        assert!(!world.in_bounds(4, 0));
+        assert!(!world.in_bounds(0, 3));
+        assert!(!world.in_bounds(-1, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tiles_outside_the_world_panic() {
+        let world = World::new(4, 3, Theme::Sewers, Tile::wall());
+        world.get(0, 3);
+    }
+
+    // an open field split by a wall from top to bottom, except for a gap in its middle
+    fn walled_field() -> World {
+        let mut world = World::new(10, 7, Theme::Sewers, Tile::empty());
+        for y in 0..7 {
+            if y != 3 {
+                *world.get_mut(5, y) = Tile::wall();
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn paths_lead_around_walls() {
+        let world = walled_field();
+        let path = find_path(&world, &[], (1, 0), (8, 0), MoveMode::Walk);
+        assert!(!path.contains(&(1, 0)));
+        assert_eq!(path.last(), Some(&(8, 0)));
+        assert!(path.contains(&(5, 3)));
+        for step in [vec![(1, 0)], path.clone()].concat().windows(2) {
+            let (dx, dy) = (step[1].0 - step[0].0, step[1].1 - step[0].1);
+            assert!(
+                dx.abs() <= 1 && dy.abs() <= 1,
+                "{:?} is not a single step",
+                step
+            );
+            assert!(!world.get(step[1].0, step[1].1).blocked);
+        }
+        // the shortest detour through the gap takes diagonal steps wherever it can
+        assert_eq!(path.len(), 7);
+    }
+
+    #[test]
+    fn paths_avoid_hazards_and_end_at_walls() {
+        let mut world = walled_field();
+        *world.get_mut(5, 3) = Tile::new(Terrain::Lava);
+        assert!(find_path(&world, &[], (1, 0), (8, 0), MoveMode::Walk).is_empty());
+        assert!(find_path(&world, &[], (1, 0), (5, 0), MoveMode::Walk).is_empty());
+    }
+
+    #[test]
+    fn every_walkable_tile_becomes_reachable() {
+        let mut world = World::new(WORLD_WIDTH, WORLD_HEIGHT, Theme::Sewers, Tile::wall());
+        // three separate rooms, the last one behind a moat of deep water
+        for &(x1, y1, x2, y2) in &[(2, 2, 10, 8), (30, 20, 40, 30), (60, 5, 75, 15)] {
+            for x in x1..=x2 {
+                for y in y1..=y2 {
+                    *world.get_mut(x, y) = Tile::empty();
+                }
+            }
+        }
+        for y in 5..=15 {
+            *world.get_mut(62, y) = Tile::new(Terrain::DeepWater);
+        }
+        let objects = vec![
+            Object::new(5, 5, "player", true, '@', colors::WHITE),
+            Object::new(70, 10, "stairs", false, '<', colors::WHITE),
+        ];
+
+        ensure_connectivity(&mut world, &objects);
+
+        let walkable = |tile: &Tile| tile.is_walkable() && tile.terrain != Terrain::DeepWater;
+        let reachable = flood_fill(&world, 5, 5, walkable);
+        for x in 0..WORLD_WIDTH {
+            for y in 0..WORLD_HEIGHT {
+                if walkable(world.get(x, y)) {
+                    assert!(reachable[world.index(x, y)], "({}, {}) is cut off", x, y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+/// Module Mutant
+///
+/// The monsters of the deepest levels are mutants that are put together at random. Each of them
+/// has a base body, one or two mutations and stats that grow with the level it is found on, so
+/// that the late game stays varied without a hand-written entry for every monster.
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// internal modules
+use backend::colors::{self, Color};
+use entity::ai::Ai;
+use entity::champion::Affix;
+use entity::dice::Dice;
+use entity::faction::Faction;
+use entity::fighter::{DeathCallback, Fighter};
+use entity::object::{MoveMode, Object};
+
+// range of mutants whose body attacks from afar
+const STRAND_RANGE: i32 = 5;
+
+/// The base that a mutant is built upon.
+struct Body {
+    name: &'static str,
+    chr: char,
+    color: Color,
+    hp: i32,
+    defense: i32,
+    power: i32,
+    ranged: bool,
+}
+
+const BODIES: [Body; 3] = [
+    Body {
+        name: "blob",
+        chr: 'o',
+        color: colors::LIGHT_LIME,
+        hp: 20,
+        defense: 1,
+        power: 4,
+        ranged: false,
+    },
+    Body {
+        name: "cyst",
+        chr: 'c',
+        color: colors::LIGHT_CRIMSON,
+        hp: 16,
+        defense: 3,
+        power: 4,
+        ranged: false,
+    },
+    Body {
+        name: "strand",
+        chr: 'z',
+        color: colors::LIGHT_TURQUOISE,
+        hp: 12,
+        defense: 0,
+        power: 5,
+        ranged: true,
+    },
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mutation {
+    Giant,
+    Armored,
+    Spiked,
+    Swift,
+    Toxic,
+    Amphibious,
+}
+
+const MUTATIONS: [Mutation; 6] = [
+    Mutation::Giant,
+    Mutation::Armored,
+    Mutation::Spiked,
+    Mutation::Swift,
+    Mutation::Toxic,
+    Mutation::Amphibious,
+];
+
+impl Mutation {
+    fn adjective(self) -> &'static str {
+        match self {
+            Mutation::Giant => "giant",
+            Mutation::Armored => "armored",
+            Mutation::Spiked => "spiked",
+            Mutation::Swift => "swift",
+            Mutation::Toxic => "toxic",
+            Mutation::Amphibious => "amphibious",
+        }
+    }
+
+    /// Change the mutant according to this mutation.
+    fn apply(self, mutant: &mut Object) {
+        if let Some(fighter) = mutant.fighter.as_mut() {
+            match self {
+                Mutation::Giant => {
+                    fighter.base_max_hp += fighter.base_max_hp / 2;
+                    fighter.hp = fighter.base_max_hp;
+                }
+                Mutation::Armored => fighter.base_defense += 2,
+                Mutation::Spiked => fighter.base_power += 2,
+                _ => {}
+            }
+        }
+        match self {
+            Mutation::Swift => mutant.affixes.push(Affix::Fast),
+            Mutation::Toxic => mutant.affixes.push(Affix::Venomous),
+            Mutation::Amphibious => mutant.move_mode = MoveMode::Swim,
+            _ => {}
+        }
+    }
+}
+
+/// Create a random mutant whose strength fits the given level.
+pub fn make_mutant(rng: &mut StdRng, level: u32, x: i32, y: i32) -> Object {
+    let body = &BODIES[rng.gen_range(0, BODIES.len())];
+    let first = MUTATIONS[rng.gen_range(0, MUTATIONS.len())];
+    let second = MUTATIONS[rng.gen_range(0, MUTATIONS.len())];
+    let mutations = if first == second || rng.gen() {
+        vec![first]
+    } else {
+        vec![first, second]
+    };
+
+    let adjectives: Vec<&str> = mutations.iter().map(|m| m.adjective()).collect();
+    let name = format!("{} {}", adjectives.join(" "), body.name);
+    let level = level as i32;
+    let mut mutant = Object::new(x, y, &name, true, body.chr, body.color);
+    mutant.fighter = Some(Fighter {
+        base_max_hp: body.hp + level * 2,
+        hp: body.hp + level * 2,
+        base_defense: body.defense + level / 5,
+        base_power: body.power - 2 + level / 3,
+        damage: Dice::new(1, 3, 0),
+        on_death: DeathCallback::Monster,
+        xp: 20 * level,
+    });
+    mutant.ai = Some(if body.ranged {
+        Ai::Ranged {
+            range: STRAND_RANGE,
+        }
+    } else {
+        Ai::Basic
+    });
+    mutant.faction = Faction::Prions;
+    mutant.alive = true;
+    for mutation in mutations {
+        mutation.apply(&mut mutant);
+    }
+    mutant
+}
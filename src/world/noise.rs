@@ -0,0 +1,63 @@
+/// Module Noise
+///
+/// Noise is made by fights, slamming doors, alarms and the like. It spreads from its source
+/// around corners, growing quieter with every tile, and wakes up sleeping monsters that hear it.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// internal modules
+use world::World;
+
+/// Loudness of the different kinds of noise, which is about the number of tiles it carries.
+pub const COMBAT_NOISE: i32 = 8;
+pub const DOOR_NOISE: i32 = 6;
+pub const DIG_NOISE: i32 = 10;
+pub const ALARM_NOISE: i32 = 30;
+// loudness lost when noise passes a closed door
+const DOOR_MUFFLING: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Noise {
+    pub x: i32,
+    pub y: i32,
+    pub loudness: i32,
+}
+
+impl Noise {
+    pub fn new(x: i32, y: i32, loudness: i32) -> Self {
+        Noise { x, y, loudness }
+    }
+}
+
+/// Return how loud the noise is on every tile, indexed in the same way as the world's tiles.
+/// Tiles that don't hear the noise at all have a loudness of 0.
+pub fn spread_noise(world: &World, noise: Noise) -> Vec<i32> {
+    let mut loudness = vec![0; world.tiles.len()];
+    loudness[world.index(noise.x, noise.y)] = noise.loudness;
+    let mut queue = BinaryHeap::new();
+    queue.push((noise.loudness, Reverse((noise.x, noise.y))));
+    while let Some((level, Reverse((x, y)))) = queue.pop() {
+        if level < loudness[world.index(x, y)] {
+            // already heard louder from another direction
+            continue;
+        }
+        for &(dx, dy) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if !world.in_bounds(nx, ny) {
+                continue;
+            }
+            let tile = world.get(nx, ny);
+            let loss = match tile.door {
+                Some(_) if tile.blocked => 1 + DOOR_MUFFLING,
+                None if tile.block_sight => continue,
+                _ => 1,
+            };
+            let index = world.index(nx, ny);
+            if level - loss > loudness[index] {
+                loudness[index] = level - loss;
+                queue.push((level - loss, Reverse((nx, ny))));
+            }
+        }
+    }
+    loudness
+}
@@ -0,0 +1,173 @@
+/// Module Overworld
+///
+/// The overworld is the surface above all dungeons. It is shaped by terrain noise into meadows,
+/// lakes and mountains, with the town in its center and the entrances to the main branches of
+/// the dungeon scattered around it.
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// internal modules
+use backend::colors;
+use entity::object::Object;
+use game_state::PLAYER;
+use world::branch::BRANCHES;
+use world::{
+    create_room, make_npc, Material, Rect, Room, RoomShape, Terrain, Tile, World, MAX_ROOMS,
+    ROOM_MAX_SIZE, ROOM_MIN_SIZE,
+};
+
+// size of the town and the number of buildings in it
+const TOWN_WIDTH: i32 = 36;
+const TOWN_HEIGHT: i32 = 20;
+const TOWN_BUILDINGS: i32 = 4;
+// terrain noise below this becomes water, above that mountains
+const WATER_LEVEL: f32 = 0.3;
+const MOUNTAIN_LEVEL: f32 = 0.68;
+// terrain noise just above the water level becomes shallow water along the shore
+const SHORE_WIDTH: f32 = 0.04;
+// minimum distance between two branch entrances
+const ENTRANCE_DISTANCE: f32 = 15.0;
+
+/// Build the overworld: noise-shaped terrain with the town in its center and one entrance per
+/// main branch outside of the town.
+pub fn make_overworld(world: &mut World, objects: &mut Vec<Object>, rng: &mut StdRng) {
+    // two layers of noise, a coarse one for the landscape and a finer one for its details
+    let coarse = value_noise(rng, world.width, world.height, 10);
+    let fine = value_noise(rng, world.width, world.height, 4);
+    for y in 1..world.height - 1 {
+        for x in 1..world.width - 1 {
+            let i = world.index(x, y);
+            let height = 0.7 * coarse[i] + 0.3 * fine[i];
+            let terrain = if height < WATER_LEVEL {
+                Terrain::DeepWater
+            } else if height < WATER_LEVEL + SHORE_WIDTH {
+                Terrain::ShallowWater
+            } else if height > MOUNTAIN_LEVEL {
+                Terrain::Wall
+            } else {
+                Terrain::Floor
+            };
+            *world.get_mut(x, y) = Tile::new(terrain);
+        }
+    }
+
+    let (center_x, center_y) = (world.width / 2, world.height / 2);
+    let town = Rect::new(
+        center_x - TOWN_WIDTH / 2,
+        center_y - TOWN_HEIGHT / 2,
+        TOWN_WIDTH,
+        TOWN_HEIGHT,
+    );
+    make_town(world, objects, rng, town);
+
+    let main_branches = BRANCHES.iter().filter(|branch| branch.entry.is_none());
+    for (i, branch) in main_branches.enumerate() {
+        let (x, y) = find_entrance_spot(world, objects, rng, town);
+        let color = [colors::WHITE, colors::YELLOW, colors::LIGHT_RED][i % 3];
+        let mut entrance = Object::new(x, y, &branch.entrance_name(), false, '<', color);
+        entrance.always_visible = true;
+        objects.push(entrance);
+    }
+}
+
+/// Find a free spot outside of the town that is far enough from all other entrances. If there
+/// is none, any spot outside of the town is used.
+fn find_entrance_spot(
+    world: &mut World,
+    objects: &[Object],
+    rng: &mut StdRng,
+    town: Rect,
+) -> (i32, i32) {
+    let mut spot = (1, 1);
+    for _ in 0..100 {
+        let x = rng.gen_range(2, world.width - 2);
+        let y = rng.gen_range(2, world.height - 2);
+        let in_town = x >= town.x1 && x <= town.x2 && y >= town.y1 && y <= town.y2;
+        if in_town {
+            continue;
+        }
+        spot = (x, y);
+        let far_enough = objects
+            .iter()
+            .filter(|object| object.name.starts_with("entrance"))
+            .all(|object| object.distance(x, y) >= ENTRANCE_DISTANCE);
+        if far_enough {
+            break;
+        }
+    }
+    // entrances may be found in the middle of a lake or mountain, so clear the ground
+    *world.get_mut(spot.0, spot.1) = Tile::empty();
+    spot
+}
+
+/// Build the town in the given area: an open square surrounded by buildings, each with a door
+/// facing south. The first building is a shop, the second one the healer's home and the third
+/// one a smithy. The player starts in the middle of the square.
+fn make_town(world: &mut World, objects: &mut Vec<Object>, rng: &mut StdRng, town: Rect) {
+    create_room(world, Room::new(town, RoomShape::Rectangle));
+    let (center_x, center_y) = town.center();
+
+    // keep the square in the middle free of buildings
+    let mut buildings = vec![Rect::new(center_x - 5, center_y - 3, 10, 6)];
+    for _ in 0..MAX_ROOMS {
+        if buildings.len() as i32 > TOWN_BUILDINGS {
+            break;
+        }
+        let w = rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
+        let h = rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE);
+        let x = rng.gen_range(town.x1 + 2, town.x2 - w - 1);
+        let y = rng.gen_range(town.y1 + 2, town.y2 - h - 2);
+        // leave a gap between buildings, so every door leads into the street
+        let building = Rect::new(x, y, w, h);
+        let surroundings = Rect::new(x - 1, y - 1, w + 2, h + 2);
+        if buildings
+            .iter()
+            .any(|other| surroundings.intersects_with(other))
+        {
+            continue;
+        }
+
+        for bx in x..=x + w {
+            for by in y..=y + h {
+                *world.get_mut(bx, by) = Tile::wall();
+                world.get_mut(bx, by).material = Material::Stone;
+            }
+        }
+        create_room(world, Room::new(building, RoomShape::Rectangle));
+        let (door_x, door_y) = (building.center().0, building.y2);
+        *world.get_mut(door_x, door_y) = Tile::door();
+
+        let (inside_x, inside_y) = building.center();
+        match buildings.len() {
+            1 => objects.push(make_npc("shopkeeper", inside_x, inside_y)),
+            2 => objects.push(make_npc("healer", inside_x, inside_y)),
+            3 => objects.push(make_npc("smith", inside_x, inside_y)),
+            _ => {}
+        }
+        buildings.push(building);
+    }
+
+    objects[PLAYER].set_pos(center_x, center_y);
+}
+
+/// Generate smooth random noise between 0 and 1 for every tile of a world of the given size, by
+/// interpolating between random values on a grid whose cells are `scale` tiles wide.
+fn value_noise(rng: &mut StdRng, width: i32, height: i32, scale: i32) -> Vec<f32> {
+    let grid_width = width / scale + 2;
+    let grid_height = height / scale + 2;
+    let grid: Vec<f32> = (0..grid_width * grid_height).map(|_| rng.gen()).collect();
+    let at = |x: i32, y: i32| grid[(y * grid_width + x) as usize];
+
+    let mut noise = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let (grid_x, grid_y) = (x / scale, y / scale);
+            let fx = (x % scale) as f32 / scale as f32;
+            let fy = (y % scale) as f32 / scale as f32;
+            let top = at(grid_x, grid_y) * (1.0 - fx) + at(grid_x + 1, grid_y) * fx;
+            let bottom = at(grid_x, grid_y + 1) * (1.0 - fx) + at(grid_x + 1, grid_y + 1) * fx;
+            noise.push(top * (1.0 - fy) + bottom * fy);
+        }
+    }
+    noise
+}
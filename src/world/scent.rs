@@ -0,0 +1,61 @@
+/// Module Scent
+///
+/// The player leaves a trail of scent behind that fades over time. Like a Dijkstra map, the
+/// scent is shared by all monsters, which can follow it uphill to the player without seeing it.
+// internal modules
+use entity::object::{MoveMode, Object};
+use world::{is_blocked_for, World};
+
+// strength of fresh scent, which is also the number of turns until it has faded away
+const SCENT_STRENGTH: i32 = 30;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScentMap {
+    width: i32,
+    values: Vec<i32>,
+}
+
+impl ScentMap {
+    /// Create a map of the given size without any scent on it.
+    pub fn new(width: i32, height: i32) -> Self {
+        ScentMap {
+            width,
+            values: vec![0; (width * height) as usize],
+        }
+    }
+
+    /// Return the strength of the scent at the given position.
+    pub fn get(&self, x: i32, y: i32) -> i32 {
+        self.values[(y * self.width + x) as usize]
+    }
+
+    /// Let all scent fade a little and leave fresh scent at the given position. Needs to be
+    /// called once per turn.
+    pub fn update(&mut self, x: i32, y: i32) {
+        for value in self.values.iter_mut().filter(|value| **value > 0) {
+            *value -= 1;
+        }
+        self.values[(y * self.width + x) as usize] = SCENT_STRENGTH;
+    }
+
+    /// Return the neighbour of the given position with the strongest scent, if it is stronger
+    /// than the scent at the position itself and not occupied.
+    pub fn follow(
+        &self,
+        world: &World,
+        objects: &[Object],
+        x: i32,
+        y: i32,
+        move_mode: MoveMode,
+    ) -> Option<(i32, i32)> {
+        (y - 1..=y + 1)
+            .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+            .filter(|&(nx, ny)| {
+                world.in_bounds(nx, ny)
+                    && self.get(nx, ny) > self.get(x, y)
+                    && !is_blocked_for(world, objects, nx, ny, move_mode)
+                    && !world.get(nx, ny).is_hazardous()
+            })
+            .max_by_key(|&(nx, ny)| self.get(nx, ny))
+    }
+}
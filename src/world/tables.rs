@@ -0,0 +1,209 @@
+/// Module Tables
+///
+/// The chances of monsters and items to appear on a level are read from plain-text files, one
+/// per theme, so that they can be balanced without recompiling the game. Each table lists
+/// entries with their chances, which are either a single number or a list of `level:chance`
+/// transitions, as in `from_dungeon_level`:
+///
+/// ```text
+/// max_monsters 1:2 4:3 6:5
+///
+/// monsters:
+///     virus 80
+///     bacteria 3:15 5:30 7:60
+/// items:
+///     healing potion 60
+/// treasure:
+///     sword 15
+/// ```
+///
+/// `items` are found in ordinary chests and shops, `treasure` in treasure vaults. Anything a
+/// theme's file leaves out is taken from the file `default.txt`.
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// internal modules
+use game_state::{from_dungeon_level, Transition};
+use world::theme::{Theme, THEMES};
+
+/// Directory that holds the tables of all themes.
+pub const TABLE_DIR: &str = "assets/loot";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Table {
+    Monsters,
+    Items,
+    Treasure,
+}
+
+type Entries = Vec<(String, Vec<Transition>)>;
+
+#[derive(Clone, Debug, Default)]
+pub struct LootTables {
+    max_monsters: Option<Vec<Transition>>,
+    monsters: Option<Entries>,
+    items: Option<Entries>,
+    treasure: Option<Entries>,
+}
+
+impl LootTables {
+    /// Fill in everything that these tables leave out from the given ones.
+    fn or(self, other: LootTables) -> Self {
+        LootTables {
+            max_monsters: self.max_monsters.or(other.max_monsters),
+            monsters: self.monsters.or(other.monsters),
+            items: self.items.or(other.items),
+            treasure: self.treasure.or(other.treasure),
+        }
+    }
+
+    fn entries(&self, table: Table) -> Option<&Entries> {
+        match table {
+            Table::Monsters => self.monsters.as_ref(),
+            Table::Items => self.items.as_ref(),
+            Table::Treasure => self.treasure.as_ref(),
+        }
+    }
+}
+
+thread_local! {
+    // the tables of every theme, loaded once at startup
+    static CACHE: RefCell<Vec<(Theme, LootTables)>> = const { RefCell::new(vec![]) };
+}
+
+/// Return the relative chances of the entries of the given table for a level of the given
+/// theme and depth.
+pub fn chances(theme: Theme, table: Table, level: u32) -> Vec<(String, u32)> {
+    with_tables(theme, |tables| {
+        tables.entries(table).map_or(vec![], |entries| {
+            entries
+                .iter()
+                .map(|(name, transitions)| (name.clone(), from_dungeon_level(transitions, level)))
+                .collect()
+        })
+    })
+}
+
+/// Return the maximum number of monsters in a room of a level of the given theme and depth.
+pub fn max_monsters(theme: Theme, level: u32) -> u32 {
+    with_tables(theme, |tables| {
+        tables
+            .max_monsters
+            .as_ref()
+            .map_or(0, |transitions| from_dungeon_level(transitions, level))
+    })
+}
+
+/// Load the tables of every theme, unless they have been loaded already.
+pub fn cache_tables() {
+    CACHE.with(|cache| {
+        for &theme in &THEMES {
+            if cache.borrow().iter().any(|&(other, _)| other == theme) {
+                continue;
+            }
+            let tables = load_theme_tables(TABLE_DIR, theme).unwrap_or_else(|e| {
+                eprintln!("unable to load loot tables: {}", e);
+                LootTables::default()
+            });
+            cache.borrow_mut().push((theme, tables));
+        }
+    });
+}
+
+/// Call `f` with the tables of the given theme, loading them first if necessary.
+fn with_tables<F, T>(theme: Theme, f: F) -> T
+where
+    F: FnOnce(&LootTables) -> T,
+{
+    cache_tables();
+    CACHE.with(|cache| {
+        let cache = cache.borrow();
+        let (_, tables) = cache.iter().find(|&&(other, _)| other == theme).unwrap();
+        f(tables)
+    })
+}
+
+/// Load the tables of the given theme from the given directory, completed by the default ones.
+pub fn load_theme_tables<P: AsRef<Path>>(
+    dir: P,
+    theme: Theme,
+) -> Result<LootTables, Box<dyn Error>> {
+    let defaults = load_tables(dir.as_ref().join("default.txt"))?;
+    let path = dir
+        .as_ref()
+        .join(format!("{}.txt", theme.name().to_lowercase()));
+    if !path.exists() {
+        return Ok(defaults);
+    }
+    Ok(load_tables(path)?.or(defaults))
+}
+
+fn load_tables<P: AsRef<Path>>(path: P) -> Result<LootTables, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let tables =
+        parse_tables(&text).map_err(|e| format!("invalid table file {}: {}", path.display(), e))?;
+    Ok(tables)
+}
+
+fn parse_tables(text: &str) -> Result<LootTables, Box<dyn Error>> {
+    let mut tables = LootTables::default();
+    let mut current: Option<Table> = None;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if !line.starts_with(' ') {
+            current = match words.as_slice() {
+                ["max_monsters", ..] => {
+                    tables.max_monsters = Some(parse_transitions(&words[1..])?);
+                    None
+                }
+                ["monsters:"] => Some(Table::Monsters),
+                ["items:"] => Some(Table::Items),
+                ["treasure:"] => Some(Table::Treasure),
+                _ => return Err(format!("invalid line '{}'", line).into()),
+            };
+            continue;
+        }
+
+        let entries = match current {
+            Some(Table::Monsters) => tables.monsters.get_or_insert_with(Vec::new),
+            Some(Table::Items) => tables.items.get_or_insert_with(Vec::new),
+            Some(Table::Treasure) => tables.treasure.get_or_insert_with(Vec::new),
+            None => return Err(format!("entry '{}' outside of a table", line.trim()).into()),
+        };
+        // the name is followed by the chances, which contain no letters
+        let split = words
+            .iter()
+            .rposition(|word| word.chars().any(|c| c.is_alphabetic()))
+            .map_or(0, |i| i + 1);
+        if split == 0 || split == words.len() {
+            return Err(format!("invalid entry '{}'", line.trim()).into());
+        }
+        entries.push((
+            words[..split].join(" "),
+            parse_transitions(&words[split..])?,
+        ));
+    }
+    Ok(tables)
+}
+
+/// Parse chances given as a single number or as `level:chance` transitions.
+fn parse_transitions(words: &[&str]) -> Result<Vec<Transition>, String> {
+    words
+        .iter()
+        .map(|word| {
+            let (level, value) = match word.find(':') {
+                Some(colon) => (word[..colon].parse(), word[colon + 1..].parse()),
+                None => (Ok(0), word.parse()),
+            };
+            match (level, value) {
+                (Ok(level), Ok(value)) => Ok(Transition { level, value }),
+                _ => Err(format!("invalid chance '{}'", word)),
+            }
+        })
+        .collect()
+}
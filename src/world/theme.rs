@@ -0,0 +1,248 @@
+/// Module Theme
+///
+/// Themes give levels of different depths their own look and inhabitants. Each theme decides
+/// which generator carves its levels, which colors its tiles have and which monsters, traps
+/// and pools appear in it.
+use entity::trap::TrapKind;
+use game_state::{from_dungeon_level, Transition};
+use world::tables::{chances, Table};
+use world::{Decoration, Material, Terrain};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Theme {
+    /// The surface above the dungeon.
+    Town,
+    Sewers,
+    Caves,
+    Crypts,
+    Fortress,
+    /// Half drowned levels, only found in side branches.
+    Flooded,
+}
+
+/// All themes, in the order of the levels they appear on.
+pub const THEMES: [Theme; 6] = [
+    Theme::Town,
+    Theme::Sewers,
+    Theme::Caves,
+    Theme::Crypts,
+    Theme::Fortress,
+    Theme::Flooded,
+];
+
+impl Theme {
+    /// Return the theme of the level at the given depth.
+    pub fn for_level(level: u32) -> Self {
+        let theme = from_dungeon_level(
+            &[
+                Transition { level: 1, value: 1 },
+                Transition { level: 4, value: 2 },
+                Transition { level: 7, value: 3 },
+                Transition {
+                    level: 10,
+                    value: 4,
+                },
+            ],
+            level,
+        );
+        match theme {
+            0 => Theme::Town,
+            1 => Theme::Sewers,
+            2 => Theme::Caves,
+            3 => Theme::Crypts,
+            _ => Theme::Fortress,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Town => "Town",
+            Theme::Sewers => "Sewers",
+            Theme::Caves => "Caves",
+            Theme::Crypts => "Crypts",
+            Theme::Fortress => "Fortress",
+            Theme::Flooded => "Flooded",
+        }
+    }
+
+    /// Name of the boss that guards the boss levels of this theme.
+    pub fn boss_name(self) -> &'static str {
+        match self {
+            Theme::Town => "mayor",
+            Theme::Sewers => "giant amoeba",
+            Theme::Caves => "mould colossus",
+            Theme::Crypts => "prion lord",
+            Theme::Fortress => "superbug",
+            Theme::Flooded => "giant hydra",
+        }
+    }
+
+    /// Spawn table entry of the minions that the boss of this theme calls for help.
+    pub fn boss_minion(self) -> &'static str {
+        match self {
+            Theme::Town => "virus",
+            Theme::Sewers => "bacteria",
+            Theme::Caves => "fungus",
+            Theme::Crypts => "prion",
+            Theme::Fortress => "phage",
+            Theme::Flooded => "paramecium",
+        }
+    }
+
+    /// Chance that a level of this theme is carved by a drunkard's walk instead of consisting
+    /// of rooms and tunnels.
+    pub fn drunkard_chance(self) -> f32 {
+        match self {
+            Theme::Sewers => 0.1,
+            Theme::Town => 0.0,
+            Theme::Caves => 0.8,
+            Theme::Crypts | Theme::Fortress => 0.0,
+            Theme::Flooded => 0.3,
+        }
+    }
+
+    /// Fraction of a level of this theme that is carved out when it is made by a drunkard's
+    /// walk. Caves are wide and open, sewers stay cramped.
+    pub fn drunkard_coverage(self) -> f32 {
+        match self {
+            Theme::Sewers => 0.3,
+            Theme::Caves => 0.5,
+            Theme::Flooded => 0.45,
+            Theme::Town | Theme::Crypts | Theme::Fortress => 0.4,
+        }
+    }
+
+    /// Chance that a level of this theme is made by wave function collapse from a sample
+    /// layout.
+    pub fn wfc_chance(self) -> f32 {
+        match self {
+            Theme::Town | Theme::Sewers => 0.0,
+            Theme::Caves | Theme::Flooded => 0.1,
+            Theme::Crypts | Theme::Fortress => 0.3,
+        }
+    }
+
+    /// Light level of all tiles of a level of this theme that are not lit by any light source.
+    pub fn ambient_light(self) -> f32 {
+        match self {
+            // daylight
+            Theme::Town => 1.0,
+            Theme::Sewers => 0.1,
+            _ => 0.0,
+        }
+    }
+
+    /// Chance that a level of this theme is dark, without any ambient light.
+    pub fn dark_chance(self) -> f32 {
+        match self {
+            Theme::Town => 0.0,
+            Theme::Sewers | Theme::Flooded | Theme::Fortress => 0.1,
+            Theme::Caves => 0.2,
+            Theme::Crypts => 0.3,
+        }
+    }
+
+    /// Material that the walls of a level of this theme are made of.
+    pub fn wall_material(self) -> Material {
+        match self {
+            Theme::Flooded => Material::Earth,
+            Theme::Town | Theme::Sewers | Theme::Caves => Material::Rock,
+            Theme::Crypts | Theme::Fortress => Material::Stone,
+        }
+    }
+
+    /// Chance that a room of a level of this theme contains a pool.
+    pub fn pool_chance(self) -> f32 {
+        match self {
+            Theme::Flooded => 0.9,
+            _ => 0.3,
+        }
+    }
+
+    /// Relative chances of the monsters that populate a level of this theme, as listed in its
+    /// loot tables. A monster can be given a behavior from a file with `monster/behavior`.
+    pub fn monster_chances(self, level: u32) -> Vec<(String, u32)> {
+        chances(self, Table::Monsters, level)
+    }
+
+    /// Relative chances of the traps hidden in a level of this theme.
+    pub fn trap_chances(self) -> Vec<(TrapKind, u32)> {
+        match self {
+            Theme::Town => vec![],
+            Theme::Sewers | Theme::Caves => vec![
+                (TrapKind::SpikePit, 50),
+                (TrapKind::Dart, 30),
+                (TrapKind::Alarm, 20),
+                (TrapKind::Gas, 15),
+            ],
+            Theme::Flooded => vec![(TrapKind::Dart, 70), (TrapKind::Alarm, 30)],
+            Theme::Crypts => vec![
+                (TrapKind::SpikePit, 20),
+                (TrapKind::Dart, 70),
+                (TrapKind::Alarm, 10),
+                (TrapKind::Gas, 20),
+                (TrapKind::Polymorph, 10),
+            ],
+            Theme::Fortress => vec![
+                (TrapKind::SpikePit, 20),
+                (TrapKind::Dart, 30),
+                (TrapKind::Alarm, 50),
+                (TrapKind::Polymorph, 10),
+            ],
+        }
+    }
+
+    /// Relative chances of the decorations scattered across a level of this theme.
+    pub fn decoration_chances(self) -> Vec<(Decoration, u32)> {
+        match self {
+            Theme::Town => vec![(Decoration::Rubble, 70), (Decoration::Brazier, 30)],
+            Theme::Sewers | Theme::Flooded => {
+                vec![(Decoration::Rubble, 60), (Decoration::Bones, 40)]
+            }
+            Theme::Caves => vec![(Decoration::Pillar, 40), (Decoration::Rubble, 60)],
+            Theme::Crypts => vec![
+                (Decoration::Pillar, 30),
+                (Decoration::Bones, 50),
+                (Decoration::Brazier, 20),
+            ],
+            Theme::Fortress => vec![
+                (Decoration::Pillar, 50),
+                (Decoration::Rubble, 20),
+                (Decoration::Brazier, 30),
+            ],
+        }
+    }
+
+    /// Relative chances of the terrain that fills pools in a level of this theme.
+    pub fn pool_chances(self, level: u32) -> Vec<(Terrain, u32)> {
+        let lava_chance = from_dungeon_level(
+            &[Transition {
+                level: 3,
+                value: 20,
+            }],
+            level,
+        );
+        let chasm_chance = from_dungeon_level(
+            &[Transition {
+                level: 2,
+                value: 15,
+            }],
+            level,
+        );
+        match self {
+            Theme::Town | Theme::Flooded => vec![(Terrain::DeepWater, 100)],
+            Theme::Sewers => vec![(Terrain::DeepWater, 90), (Terrain::Chasm, chasm_chance)],
+            Theme::Caves => vec![
+                (Terrain::DeepWater, 40),
+                (Terrain::Lava, lava_chance * 2),
+                (Terrain::Chasm, chasm_chance),
+            ],
+            Theme::Crypts => vec![(Terrain::DeepWater, 20), (Terrain::Chasm, chasm_chance * 3)],
+            Theme::Fortress => vec![
+                (Terrain::DeepWater, 30),
+                (Terrain::Lava, lava_chance),
+                (Terrain::Chasm, chasm_chance),
+            ],
+        }
+    }
+}
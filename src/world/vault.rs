@@ -0,0 +1,207 @@
+/// Module Vault
+///
+/// Vaults are hand-designed room templates that the world generator can stamp into a level.
+/// They are described in plain-text files consisting of a header, a legend and a layout:
+///
+/// ```text
+/// name: guard post
+/// min_level: 2
+/// legend:
+/// # wall
+/// . floor
+/// ~ water
+/// b monster bacteria
+/// k item key
+/// layout:
+/// #######
+/// #..b..#
+/// #######
+/// ```
+///
+/// Each legend line maps a layout character to the terrain (wall, floor, water, shallow water,
+/// lava, chasm or pillar) or the object that is placed there. Objects always stand on floor.
+/// Spaces in the layout leave the underlying tile as it is. The name only tells the vault files
+/// apart and is not used by the game.
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::Path;
+
+// internal modules
+use entity::object::Object;
+use util::load_dir;
+use world::{make_item, make_monster, Terrain, Tile, World, WORLD_HEIGHT, WORLD_WIDTH};
+
+/// Directory that is searched for vault files.
+pub const VAULT_DIR: &str = "assets/vaults";
+
+/// Content of a single vault tile.
+#[derive(Clone, Debug, PartialEq)]
+enum Cell {
+    Keep,
+    Terrain(Terrain),
+    Monster(String),
+    Item(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Vault {
+    pub min_level: u32,
+    pub width: i32,
+    pub height: i32,
+    cells: Vec<Vec<Cell>>, // indexed by [y][x], like the layout in the file
+}
+
+impl Vault {
+    /// Stamp this vault into the world with its top-left corner at the given position,
+    /// spawning all objects it contains.
+    pub fn stamp(&self, world: &mut World, objects: &mut Vec<Object>, x: i32, y: i32) {
+        for (dy, row) in self.cells.iter().enumerate() {
+            for (dx, cell) in row.iter().enumerate() {
+                let (cell_x, cell_y) = (x + dx as i32, y + dy as i32);
+                let tile = world.get_mut(cell_x, cell_y);
+                match *cell {
+                    Cell::Keep => {}
+                    Cell::Terrain(terrain) => *tile = Tile::new(terrain),
+                    Cell::Monster(ref name) => {
+                        *tile = Tile::empty();
+                        if let Some(monster) = make_monster(name, cell_x, cell_y) {
+                            objects.push(monster);
+                        }
+                    }
+                    Cell::Item(ref name) => {
+                        *tile = Tile::empty();
+                        if let Some(item) = make_item(name, cell_x, cell_y) {
+                            objects.push(item);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    // the vaults from the vault directory, loaded once at startup
+    static VAULTS: RefCell<Option<Vec<Vault>>> = const { RefCell::new(None) };
+}
+
+/// Load the vaults from the vault directory, unless they have been loaded already.
+pub fn cache_vaults() {
+    VAULTS.with(|vaults| {
+        if vaults.borrow().is_some() {
+            return;
+        }
+        let loaded = load_vaults(VAULT_DIR).unwrap_or_else(|e| {
+            eprintln!("unable to load vaults: {}", e);
+            vec![]
+        });
+        *vaults.borrow_mut() = Some(loaded);
+    });
+}
+
+/// Return all vaults that may appear on the given level.
+pub fn vaults_for_level(level: u32) -> Vec<Vault> {
+    cache_vaults();
+    VAULTS.with(|vaults| {
+        vaults
+            .borrow()
+            .iter()
+            .flatten()
+            .filter(|vault| vault.min_level <= level)
+            .cloned()
+            .collect()
+    })
+}
+
+/// Load all vault files (ending in `.txt`) from the given directory.
+pub fn load_vaults<P: AsRef<Path>>(dir: P) -> Result<Vec<Vault>, Box<dyn Error>> {
+    load_dir(dir, "txt", |_, text| parse_vault(text))
+}
+
+enum Section {
+    Header,
+    Legend,
+    Layout,
+}
+
+fn parse_vault(text: &str) -> Result<Vault, Box<dyn Error>> {
+    let mut min_level = 1;
+    let mut legend: Vec<(char, Cell)> = vec![(' ', Cell::Keep)];
+    let mut rows: Vec<&str> = vec![];
+
+    let mut section = Section::Header;
+    for line in text.lines() {
+        match section {
+            Section::Header | Section::Legend if line.trim() == "legend:" => {
+                section = Section::Legend;
+            }
+            Section::Header | Section::Legend if line.trim() == "layout:" => {
+                section = Section::Layout;
+            }
+            _ if line.trim().is_empty() => {}
+            Section::Header => {
+                let mut parts = line.splitn(2, ':').map(|part| part.trim());
+                match (parts.next(), parts.next()) {
+                    (Some("name"), Some(_)) => {}
+                    (Some("min_level"), Some(value)) => min_level = value.parse()?,
+                    _ => return Err(format!("unknown header line '{}'", line).into()),
+                }
+            }
+            Section::Legend => {
+                let mut chars = line.chars();
+                let symbol = chars.next().unwrap();
+                let description: Vec<&str> = chars.as_str().split_whitespace().collect();
+                let cell = match description.as_slice() {
+                    ["wall"] => Cell::Terrain(Terrain::Wall),
+                    ["floor"] => Cell::Terrain(Terrain::Floor),
+                    ["water"] => Cell::Terrain(Terrain::DeepWater),
+                    ["shallow", "water"] => Cell::Terrain(Terrain::ShallowWater),
+                    ["lava"] => Cell::Terrain(Terrain::Lava),
+                    ["chasm"] => Cell::Terrain(Terrain::Chasm),
+                    ["pillar"] => Cell::Terrain(Terrain::Pillar),
+                    ["monster", monster] if make_monster(monster, 0, 0).is_some() => {
+                        Cell::Monster(monster.to_string())
+                    }
+                    ["item", item] if make_item(item, 0, 0).is_some() => {
+                        Cell::Item(item.to_string())
+                    }
+                    _ => return Err(format!("invalid legend entry '{}'", line).into()),
+                };
+                legend.push((symbol, cell));
+            }
+            Section::Layout => rows.push(line),
+        }
+    }
+
+    let cells = rows
+        .iter()
+        .map(|row| {
+            row.chars()
+                .map(|c| {
+                    legend
+                        .iter()
+                        .find(|&&(symbol, _)| symbol == c)
+                        .map(|(_, cell)| cell.clone())
+                        .ok_or_else(|| format!("character '{}' is missing in the legend", c))
+                })
+                .collect::<Result<Vec<Cell>, String>>()
+        })
+        .collect::<Result<Vec<Vec<Cell>>, String>>()?;
+
+    let height = cells.len() as i32;
+    let width = cells.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+    if width < 3 || height < 3 || width >= WORLD_WIDTH || height >= WORLD_HEIGHT {
+        return Err(format!(
+            "layout size {}x{} does not fit into the world",
+            width, height
+        )
+        .into());
+    }
+
+    Ok(Vault {
+        min_level,
+        width,
+        height,
+        cells,
+    })
+}
@@ -0,0 +1,228 @@
+/// Module Wfc
+///
+/// A level generator based on wave function collapse. It learns which terrain may lie next to
+/// which from small sample layouts and then fills the level tile by tile, always collapsing the
+/// least certain tile first, so that every pair of neighbours also occurs in the sample.
+///
+/// Samples are plain-text files that contain nothing but the layout:
+///
+/// ```text
+/// ##########
+/// #..~~....#
+/// #.O......#
+/// ##########
+/// ```
+///
+/// `#` is wall, `.` floor, `~` water, `=` lava and `O` a pillar.
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::Path;
+
+// internal modules
+use util::load_dir;
+use world::Terrain;
+
+/// Directory that is searched for sample files.
+pub const SAMPLE_DIR: &str = "assets/wfc";
+
+// the four neighbours of a tile
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// The adjacency rules learned from a sample layout.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    terrains: Vec<Terrain>,
+    // how often each terrain occurs in the sample
+    weights: Vec<u32>,
+    // indexed by [direction][a][b]: whether terrain b may lie in the direction from terrain a
+    allowed: Vec<Vec<Vec<bool>>>,
+}
+
+impl Sample {
+    /// Generate a layout of the given size, stored row by row, whose border consists of walls.
+    /// Return `None` if the generator ran into a contradiction.
+    pub fn generate(&self, rng: &mut StdRng, width: i32, height: i32) -> Option<Vec<Terrain>> {
+        let n = self.terrains.len();
+        // the terrains every tile can still become
+        let mut wave = vec![vec![true; n]; (width * height) as usize];
+
+        let mut changed = vec![];
+        if let Some(wall) = self.terrains.iter().position(|&t| t == Terrain::Wall) {
+            for y in 0..height {
+                for x in 0..width {
+                    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                        let i = (y * width + x) as usize;
+                        wave[i] = (0..n).map(|t| t == wall).collect();
+                        changed.push(i);
+                    }
+                }
+            }
+        }
+        if !self.propagate(&mut wave, width, height, changed) {
+            return None;
+        }
+
+        loop {
+            // collapse the undecided tile with the fewest options, ties are broken randomly
+            let undecided = (0..wave.len())
+                .map(|i| (i, wave[i].iter().filter(|&&option| option).count()))
+                .filter(|&(_, options)| options > 1)
+                .min_by_key(|&(_, options)| (options, rng.gen::<u32>()));
+            let i = match undecided {
+                Some((i, _)) => i,
+                None => break,
+            };
+
+            let total: u32 = (0..n)
+                .filter(|&t| wave[i][t])
+                .map(|t| self.weights[t])
+                .sum();
+            let mut roll = rng.gen_range(0, total);
+            let mut chosen = 0;
+            for t in (0..n).filter(|&t| wave[i][t]) {
+                if roll < self.weights[t] {
+                    chosen = t;
+                    break;
+                }
+                roll -= self.weights[t];
+            }
+            wave[i] = (0..n).map(|t| t == chosen).collect();
+            if !self.propagate(&mut wave, width, height, vec![i]) {
+                return None;
+            }
+        }
+
+        Some(
+            wave.iter()
+                .map(|options| self.terrains[options.iter().position(|&o| o).unwrap()])
+                .collect(),
+        )
+    }
+
+    /// Remove all options from the neighbours of the changed tiles that are no longer allowed
+    /// next to them, and so on. Return false if a tile has no options left.
+    fn propagate(
+        &self,
+        wave: &mut [Vec<bool>],
+        width: i32,
+        height: i32,
+        mut changed: Vec<usize>,
+    ) -> bool {
+        let n = self.terrains.len();
+        while let Some(i) = changed.pop() {
+            let options = wave[i].clone();
+            let (x, y) = (i as i32 % width, i as i32 / width);
+            for (dir, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                let j = (ny * width + nx) as usize;
+                let mut reduced = false;
+                for (b, option) in wave[j].iter_mut().enumerate() {
+                    let possible = (0..n).any(|a| options[a] && self.allowed[dir][a][b]);
+                    if *option && !possible {
+                        *option = false;
+                        reduced = true;
+                    }
+                }
+                if reduced {
+                    if !wave[j].contains(&true) {
+                        return false;
+                    }
+                    changed.push(j);
+                }
+            }
+        }
+        true
+    }
+}
+
+thread_local! {
+    // the samples from the sample directory, loaded once at startup
+    static SAMPLES: RefCell<Option<Vec<Sample>>> = const { RefCell::new(None) };
+}
+
+/// Load the samples from the sample directory, unless they have been loaded already.
+pub fn cache_samples() {
+    SAMPLES.with(|samples| {
+        if samples.borrow().is_some() {
+            return;
+        }
+        let loaded = load_samples(SAMPLE_DIR).unwrap_or_else(|e| {
+            eprintln!("unable to load wave function collapse samples: {}", e);
+            vec![]
+        });
+        *samples.borrow_mut() = Some(loaded);
+    });
+}
+
+/// Return all wave function collapse samples.
+pub fn samples() -> Vec<Sample> {
+    cache_samples();
+    SAMPLES.with(|samples| samples.borrow().iter().flatten().cloned().collect())
+}
+
+/// Load all sample files (ending in `.txt`) from the given directory.
+pub fn load_samples<P: AsRef<Path>>(dir: P) -> Result<Vec<Sample>, Box<dyn Error>> {
+    load_dir(dir, "txt", |_, text| learn_sample(text))
+}
+
+fn learn_sample(text: &str) -> Result<Sample, Box<dyn Error>> {
+    let rows = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => Ok(Terrain::Wall),
+                    '.' => Ok(Terrain::Floor),
+                    '~' => Ok(Terrain::DeepWater),
+                    '=' => Ok(Terrain::Lava),
+                    'O' => Ok(Terrain::Pillar),
+                    _ => Err(format!("unknown character '{}'", c)),
+                })
+                .collect::<Result<Vec<Terrain>, String>>()
+        })
+        .collect::<Result<Vec<Vec<Terrain>>, String>>()?;
+    if rows.is_empty() {
+        return Err("empty sample".into());
+    }
+
+    let mut terrains: Vec<Terrain> = vec![];
+    let mut weights = vec![];
+    for &terrain in rows.iter().flat_map(|row| row.iter()) {
+        match terrains.iter().position(|&t| t == terrain) {
+            Some(t) => weights[t] += 1,
+            None => {
+                terrains.push(terrain);
+                weights.push(1);
+            }
+        }
+    }
+
+    let n = terrains.len();
+    let index = |terrain: Terrain| terrains.iter().position(|&t| t == terrain).unwrap();
+    let mut allowed = vec![vec![vec![false; n]; n]; DIRECTIONS.len()];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &terrain) in row.iter().enumerate() {
+            for (dir, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || ny as usize >= rows.len() {
+                    continue;
+                }
+                if let Some(&neighbour) = rows[ny as usize].get(nx as usize) {
+                    allowed[dir][index(terrain)][index(neighbour)] = true;
+                }
+            }
+        }
+    }
+
+    Ok(Sample {
+        terrains,
+        weights,
+        allowed,
+    })
+}